@@ -0,0 +1,102 @@
+"""Import an orbits keyframe export into Blender.
+
+Record a run with:
+
+    orbits --export_keyframes run.json
+
+then in Blender open the Scripting workspace, load this file, set PATH below and run it (or run
+`blender --python scripts/blender_import.py -- run.json`). Every planet becomes a sphere moving as
+it was sampled, and every satellite an animated sphere that appears when it was spawned and vanishes
+when it died, with its colour stored in a per-object material so it can be restyled freely.
+
+Screen pixels are mapped to Blender units with SCALE, and the screen's y axis (pointing down) is
+flipped so the scene is not mirrored. Runs with --three_d export satellites' heights out of the
+plane, which become Blender's z axis; everything else stays at z = 0.
+"""
+
+import json
+import sys
+
+import bpy
+
+PATH = "run.json"
+SCALE = 0.01  # Blender units per pixel
+FPS = 30      # Blender frames per second of simulation time
+
+
+def material(name, color):
+    mat = bpy.data.materials.new(name)
+    mat.diffuse_color = color
+    mat.use_nodes = True
+    mat.node_tree.nodes["Principled BSDF"].inputs["Base Color"].default_value = color
+    return mat
+
+
+def location(x, y, z, height):
+    return (x * SCALE, (height - y) * SCALE, z * SCALE)
+
+
+def sample(key):
+    # (time, x, y) in flat runs, (time, x, y, z) when satellites move out of the plane
+    t, x, y = key[:3]
+    return t, x, y, key[3] if len(key) > 3 else 0.0
+
+
+def sphere(name, x, y, z, radius, color, height):
+    bpy.ops.mesh.primitive_uv_sphere_add(radius=radius * SCALE, location=location(x, y, z, height))
+    obj = bpy.context.active_object
+    obj.name = name
+    obj.data.materials.append(material(name, color))
+    return obj
+
+
+def frame(time):
+    return int(round(time * FPS))
+
+
+def main(path):
+    with open(path) as f:
+        run = json.load(f)
+    height = run["height"]
+    scene = bpy.context.scene
+    scene.render.fps = FPS
+
+    last = 0
+    for i, planet in enumerate(run["planets"]):
+        obj = sphere("planet.{}".format(i), planet["x"], planet["y"], 0.0, planet["radius"], planet["color"], height)
+        # Exports from before planets moved have no samples, and the planet stays where it started
+        for t, x, y, z in map(sample, planet.get("keys", [])):
+            obj.location = location(x, y, z, height)
+            obj.keyframe_insert("location", frame=frame(t))
+            last = max(last, frame(t))
+
+    for sat in run["satellites"]:
+        if not sat["keys"]:
+            continue
+        t, x, y, z = sample(sat["keys"][0])
+        obj = sphere("satellite.{}".format(sat["id"]), x, y, z, sat["radius"], sat["color"], height)
+
+        # Hidden until spawned and after death
+        obj.hide_render = obj.hide_viewport = True
+        obj.keyframe_insert("hide_render", frame=0)
+        obj.keyframe_insert("hide_viewport", frame=0)
+        obj.hide_render = obj.hide_viewport = False
+        obj.keyframe_insert("hide_render", frame=frame(sat["born"]))
+        obj.keyframe_insert("hide_viewport", frame=frame(sat["born"]))
+        if sat["died"] is not None:
+            obj.hide_render = obj.hide_viewport = True
+            obj.keyframe_insert("hide_render", frame=frame(sat["died"]))
+            obj.keyframe_insert("hide_viewport", frame=frame(sat["died"]))
+
+        for t, x, y, z in map(sample, sat["keys"]):
+            obj.location = location(x, y, z, height)
+            obj.keyframe_insert("location", frame=frame(t))
+            last = max(last, frame(t))
+
+    scene.frame_start = 0
+    scene.frame_end = last
+
+
+if __name__ == "__main__":
+    args = sys.argv[sys.argv.index("--") + 1:] if "--" in sys.argv else []
+    main(args[0] if args else PATH)
@@ -0,0 +1,288 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+
+use crate::svg::paint;
+
+// Frames per second of Lottie animations. Bodies move between keyframes in straight lines, so this
+// only sets where the keyframes can fall, not how smooth the motion is.
+const LOTTIE_FPS: f64 = 30.0;
+
+// Colour of the background behind everything
+const BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+// Width and opacity of trails, relative to their satellite's colour
+const TRAIL_WIDTH: f64 = 1.0;
+const TRAIL_ALPHA: f32 = 0.5;
+
+// Times, scaled as for tracks, and the positions a planet passed through at them
+type Motion = (Vec<f64>, Vec<(f64, f64)>);
+
+// Reads (time, x, y) samples, dropping the height of satellites exported in three dimensions since
+// animations are drawn from above. Samples too short to place are left out of the animation.
+fn flat_keys<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(f64, f64, f64)>, D::Error> {
+    let keys: Vec<Vec<f64>> = Deserialize::deserialize(deserializer)?;
+    Ok(keys.iter().map(|key| match key[..] {
+        [t, x, y, ..] => (t, x, y),
+        _ => (f64::NAN, f64::NAN, f64::NAN),
+    }).collect())
+}
+
+#[derive(Deserialize)]
+struct PlanetRecord {
+    color: [f32; 4],
+    radius: f64,
+    x: f64,
+    y: f64,
+    #[serde(default, deserialize_with = "flat_keys")]
+    keys: Vec<(f64, f64, f64)>, // (time, x, y) samples, if the planet moved
+}
+
+impl PlanetRecord {
+    // How the planet moved, if it was sampled
+    fn motion(&self, start: f64, length: f64) -> Option<Motion> {
+        let keys: Vec<&(f64, f64, f64)> = self.keys.iter().filter(|(t, x, y)| t.is_finite() && x.is_finite() && y.is_finite()).collect();
+        if keys.is_empty() {
+            return None;
+        }
+        let times = keys.iter().map(|(t, _, _)| ((t - start) / length).clamp(0.0, 1.0)).collect();
+        Some((times, keys.iter().map(|&&(_, x, y)| (x, y)).collect()))
+    }
+}
+
+#[derive(Deserialize)]
+struct SatelliteRecord {
+    color: [f32; 4],
+    radius: f64,
+    #[serde(deserialize_with = "flat_keys")]
+    keys: Vec<(f64, f64, f64)>, // (time, x, y) samples
+}
+
+// A keyframe export or highlight clip, as written by the export module
+#[derive(Deserialize)]
+struct Recording {
+    width: f64,
+    height: f64,
+    planets: Vec<PlanetRecord>,
+    satellites: Vec<SatelliteRecord>,
+}
+
+// A satellite's path through the animation, at times scaled so the whole animation runs from 0 to 1
+struct Track<'a> {
+    satellite: &'a SatelliteRecord,
+    times: Vec<f64>,
+    points: Vec<(f64, f64)>,
+    grown: Vec<f64>, // Fraction of the trail's full length laid down by each time
+}
+
+impl<'a> Track<'a> {
+    fn new(satellite: &'a SatelliteRecord, start: f64, length: f64) -> Option<Track<'a>> {
+        let keys: Vec<&(f64, f64, f64)> = satellite.keys.iter().filter(|(t, x, y)| t.is_finite() && x.is_finite() && y.is_finite()).collect();
+        if keys.is_empty() {
+            return None;
+        }
+        let times = keys.iter().map(|(t, _, _)| ((t - start) / length).clamp(0.0, 1.0)).collect();
+        let points: Vec<(f64, f64)> = keys.iter().map(|&&(_, x, y)| (x, y)).collect();
+        let mut grown = vec![0.0];
+        for pair in points.windows(2) {
+            grown.push(grown.last().unwrap() + (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1));
+        }
+        let total = *grown.last().unwrap();
+        if total > 0.0 {
+            grown.iter_mut().for_each(|g| *g /= total);
+        }
+        Some(Track { satellite, times, points, grown })
+    }
+
+    fn born(&self) -> f64 {
+        self.times[0]
+    }
+
+    fn died(&self) -> f64 {
+        *self.times.last().unwrap()
+    }
+}
+
+// Converts a keyframe export or highlight clip into an animated SVG, or into Lottie JSON if the output
+// path ends in .json, with each satellite a moving circle trailed by its path as it grows, and planets
+// moving as they were sampled. The animation plays in simulation time and loops.
+pub fn convert(input: &str, output: &str) -> Result<String, String> {
+    let text = fs::read_to_string(input).map_err(|e| format!("Could not read keyframes {}: {}", input, e))?;
+    let recording: Recording = serde_json::from_str(&text).map_err(|e| format!("Could not read keyframes {}: {}", input, e))?;
+    let times = recording.satellites.iter().flat_map(|sat| sat.keys.iter().map(|key| key.0)).filter(|t| t.is_finite());
+    let (start, end) = times.fold((f64::INFINITY, f64::NEG_INFINITY), |(start, end), t| (start.min(t), end.max(t)));
+    if end <= start {
+        return Err(format!("Keyframes {} do not cover any length of time to animate", input));
+    }
+    let length = end - start;
+    let tracks: Vec<Track> = recording.satellites.iter().filter_map(|sat| Track::new(sat, start, length)).collect();
+    let lottie = Path::new(output).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let document = match lottie {
+        true => serde_json::to_string(&lottie_document(&recording, &tracks, start, length)).unwrap(),
+        false => svg_document(&recording, &tracks, start, length),
+    };
+    fs::write(output, document).map_err(|e| format!("Could not write animation {}: {}", output, e))?;
+    Ok(format!("{} satellites over {:.1} s", tracks.len(), length))
+}
+
+// Values of an SVG animation at each of its key times, padded out to start at 0 and end at 1 as SVG
+// requires, by holding the first and last values
+fn keyed<T: Copy>(times: &[f64], values: &[T]) -> (Vec<f64>, Vec<T>) {
+    let (mut times, mut values) = (times.to_vec(), values.to_vec());
+    if times[0] > 0.0 {
+        times.insert(0, 0.0);
+        values.insert(0, values[0]);
+    }
+    if *times.last().unwrap() < 1.0 {
+        times.push(1.0);
+        values.push(*values.last().unwrap());
+    }
+    (times, values)
+}
+
+fn joined<T, F: Fn(&T) -> String>(values: &[T], format: F) -> String {
+    values.iter().map(format).collect::<Vec<String>>().join(";")
+}
+
+fn svg_document(recording: &Recording, tracks: &[Track], start: f64, length: f64) -> String {
+    let (width, height) = (recording.width, recording.height);
+    let mut out = String::new();
+    writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">", width, height, width, height).unwrap();
+    writeln!(out, "<rect width=\"{}\" height=\"{}\" {}/>", width, height, paint("fill", BACKGROUND)).unwrap();
+    let timing = format!("dur=\"{:.3}s\" repeatCount=\"indefinite\"", length);
+    for planet in recording.planets.iter() {
+        match planet.motion(start, length) {
+            Some((times, points)) => {
+                let (times, points) = keyed(&times, &points);
+                let key_times = joined(&times, |t| format!("{:.5}", t));
+                writeln!(
+                    out,
+                    "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" {}><animate attributeName=\"cx\" values=\"{}\" keyTimes=\"{}\" {}/><animate attributeName=\"cy\" values=\"{}\" keyTimes=\"{}\" {}/></circle>",
+                    planet.x, planet.y, planet.radius, paint("fill", planet.color), joined(&points, |p| format!("{:.2}", p.0)), key_times, timing, joined(&points, |p| format!("{:.2}", p.1)), key_times, timing,
+                ).unwrap();
+            },
+            None => {
+                writeln!(out, "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" {}/>", planet.x, planet.y, planet.radius, paint("fill", planet.color)).unwrap();
+            },
+        }
+    }
+    for track in tracks {
+        let (times, points) = keyed(&track.times, &track.points);
+        let (_, grown) = keyed(&track.times, &track.grown);
+        let key_times = joined(&times, |t| format!("{:.5}", t));
+        // Shown only from the first keyframe to the last
+        let shown = format!(
+            "<animate attributeName=\"visibility\" values=\"hidden;visible;hidden\" keyTimes=\"0;{:.5};{:.5}\" calcMode=\"discrete\" {}/>",
+            track.born(), track.died(), timing,
+        );
+        let [r, g, b, a] = track.satellite.color;
+        let path: Vec<String> = track.points.iter().map(|(x, y)| format!("{:.2} {:.2}", x, y)).collect();
+        writeln!(out, "<g visibility=\"hidden\">{}", shown).unwrap();
+        // Trails are drawn as a dash the length of the whole path, slid along to reveal more of it
+        writeln!(
+            out,
+            "<path d=\"M{}\" pathLength=\"1\" stroke-dasharray=\"1 1\" stroke-dashoffset=\"1\" fill=\"none\" stroke-width=\"{}\" stroke-linejoin=\"round\" {}><animate attributeName=\"stroke-dashoffset\" values=\"{}\" keyTimes=\"{}\" {}/></path>",
+            path.join(" L"), TRAIL_WIDTH, paint("stroke", [r, g, b, a * TRAIL_ALPHA]), joined(&grown, |g| format!("{:.5}", 1.0 - g)), key_times, timing,
+        ).unwrap();
+        writeln!(
+            out,
+            "<circle r=\"{:.2}\" {}><animate attributeName=\"cx\" values=\"{}\" keyTimes=\"{}\" {}/><animate attributeName=\"cy\" values=\"{}\" keyTimes=\"{}\" {}/></circle>",
+            track.satellite.radius, paint("fill", track.satellite.color), joined(&points, |p| format!("{:.2}", p.0)), key_times, timing, joined(&points, |p| format!("{:.2}", p.1)), key_times, timing,
+        ).unwrap();
+        writeln!(out, "</g>").unwrap();
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+// Lottie colour, as RGBA components from 0 to 1
+fn lottie_color([r, g, b, a]: [f32; 4]) -> Value {
+    json!([r, g, b, a])
+}
+
+// Static Lottie property
+fn fixed(value: Value) -> Value {
+    json!({"a": 0, "k": value})
+}
+
+// Lottie property animated in straight lines between keyframes at the given frames
+fn animated(frames: &[f64], values: Vec<Value>) -> Value {
+    let keys: Vec<Value> = frames.iter().zip(values).map(|(t, s)| {
+        json!({"t": t, "s": s, "i": {"x": [1], "y": [1]}, "o": {"x": [0], "y": [0]}})
+    }).collect();
+    json!({"a": 1, "k": keys})
+}
+
+// Lottie shape layer showing the given shapes from frame `ip` to `op`, with the given position
+fn layer(index: usize, name: &str, ip: f64, op: f64, position: Value, shapes: Vec<Value>) -> Value {
+    json!({
+        "ddd": 0, "ind": index, "ty": 4, "nm": name, "sr": 1, "ao": 0, "bm": 0, "st": 0, "ip": ip, "op": op,
+        "ks": {
+            "o": fixed(json!(100)), "r": fixed(json!(0)), "p": position,
+            "a": fixed(json!([0, 0, 0])), "s": fixed(json!([100, 100, 100])),
+        },
+        "shapes": shapes,
+    })
+}
+
+// Filled Lottie circle of the given radius about the layer's position
+fn disc(radius: f64, color: [f32; 4]) -> Vec<Value> {
+    vec![
+        json!({"ty": "el", "d": 1, "p": fixed(json!([0, 0])), "s": fixed(json!([2.0 * radius, 2.0 * radius]))}),
+        json!({"ty": "fl", "c": fixed(lottie_color(color)), "o": fixed(json!(100.0 * color[3])), "r": 1}),
+    ]
+}
+
+fn lottie_document(recording: &Recording, tracks: &[Track], start: f64, length: f64) -> Value {
+    let frames = (length * LOTTIE_FPS).ceil().max(1.0);
+    let mut layers = Vec::new();
+    // Layers are listed from the top down, so satellites go over trails and trails over the planets
+    for (i, track) in tracks.iter().enumerate() {
+        let key_frames: Vec<f64> = track.times.iter().map(|t| t * frames).collect();
+        let (ip, op) = (key_frames[0], *key_frames.last().unwrap() + 1.0);
+        let position = animated(&key_frames, track.points.iter().map(|(x, y)| json!([x, y, 0])).collect());
+        layers.push(layer(layers.len() + 1, &format!("satellite {}", i), ip, op, position, disc(track.satellite.radius, track.satellite.color)));
+    }
+    for (i, track) in tracks.iter().enumerate() {
+        let key_frames: Vec<f64> = track.times.iter().map(|t| t * frames).collect();
+        let (ip, op) = (key_frames[0], *key_frames.last().unwrap() + 1.0);
+        let vertices: Vec<Value> = track.points.iter().map(|(x, y)| json!([x, y])).collect();
+        let tangents = vec![json!([0, 0]); vertices.len()];
+        let [r, g, b, a] = track.satellite.color;
+        let shapes = vec![
+            json!({"ty": "sh", "ks": fixed(json!({"c": false, "v": vertices, "i": tangents, "o": tangents}))}),
+            json!({
+                "ty": "st", "c": fixed(lottie_color([r, g, b, 1.0])), "o": fixed(json!(100.0 * a * TRAIL_ALPHA)),
+                "w": fixed(json!(TRAIL_WIDTH)), "lc": 2, "lj": 2,
+            }),
+            // Trails grow by trimming the path to the part laid down so far
+            json!({
+                "ty": "tm", "s": fixed(json!(0)), "o": fixed(json!(0)), "m": 1,
+                "e": animated(&key_frames, track.grown.iter().map(|g| json!([100.0 * g])).collect()),
+            }),
+        ];
+        layers.push(layer(layers.len() + 1, &format!("trail {}", i), ip, op, fixed(json!([0, 0, 0])), shapes));
+    }
+    for (i, planet) in recording.planets.iter().enumerate() {
+        let position = match planet.motion(start, length) {
+            Some((times, points)) => {
+                let key_frames: Vec<f64> = times.iter().map(|t| t * frames).collect();
+                animated(&key_frames, points.iter().map(|(x, y)| json!([x, y, 0])).collect())
+            },
+            None => fixed(json!([planet.x, planet.y, 0])),
+        };
+        layers.push(layer(layers.len() + 1, &format!("planet {}", i), 0.0, frames, position, disc(planet.radius, planet.color)));
+    }
+    let background = vec![
+        json!({"ty": "rc", "d": 1, "p": fixed(json!([recording.width / 2.0, recording.height / 2.0])), "s": fixed(json!([recording.width, recording.height])), "r": fixed(json!(0))}),
+        json!({"ty": "fl", "c": fixed(lottie_color(BACKGROUND)), "o": fixed(json!(100)), "r": 1}),
+    ];
+    layers.push(layer(layers.len() + 1, "background", 0.0, frames, fixed(json!([0, 0, 0])), background));
+    json!({
+        "v": "5.7.4", "nm": "orbits", "ddd": 0, "fr": LOTTIE_FPS, "ip": 0, "op": frames,
+        "w": recording.width.round(), "h": recording.height.round(), "assets": [], "layers": layers,
+    })
+}
@@ -0,0 +1,129 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use graphics::math::Matrix2d;
+
+use crate::draw::DrawList;
+
+// Colour arrows and labels are drawn in, to stand out against the simulation
+const INK: [f32; 4] = [1.0, 0.9, 0.2, 1.0];
+
+// Shortest drag (in px) that draws an arrow; anything shorter is a click, which starts a label
+const MIN_ARROW: f64 = 10.0;
+
+// Length and half-width (in px) of arrowheads
+const HEAD_LENGTH: f64 = 12.0;
+const HEAD_WIDTH: f64 = 5.0;
+
+// Size labels are drawn at, as a multiple of the font's own size
+const LABEL_SCALE: f64 = 2.0;
+
+// Something placed on a frozen frame, in world coordinates
+pub enum Mark {
+    Arrow { from: [f64; 2], to: [f64; 2] },
+    Label { at: [f64; 2], text: String },
+}
+
+// Arrows and labels placed with the mouse over a frozen frame, for making teaching materials. Dragging
+// draws an arrow, and clicking starts a label that takes whatever is typed until Enter.
+#[derive(Default)]
+pub struct Annotations {
+    pub marks: Vec<Mark>,
+    start: Option<[f64; 2]>, // Where the arrow being dragged out starts
+    pub typing: bool,        // Whether the last mark is a label still being typed
+    pub export: bool,        // Whether to save the next frame drawn, once it is drawn
+}
+
+impl Annotations {
+    pub fn press(&mut self, pos: [f64; 2]) {
+        self.finish();
+        self.start = Some(pos);
+    }
+
+    // Ends a drag, as an arrow if it went far enough or otherwise as a new label
+    pub fn release(&mut self, pos: [f64; 2]) {
+        let from = match self.start.take() {
+            Some(from) => from,
+            None => return,
+        };
+        if (pos[0] - from[0]).hypot(pos[1] - from[1]) >= MIN_ARROW {
+            self.marks.push(Mark::Arrow { from, to: pos });
+        } else {
+            self.marks.push(Mark::Label { at: from, text: String::new() });
+            self.typing = true;
+        }
+    }
+
+    // Adds typed text to the label being typed, if any
+    pub fn type_text(&mut self, text: &str) {
+        if let (true, Some(Mark::Label { text: label, .. })) = (self.typing, self.marks.last_mut()) {
+            label.extend(text.chars().filter(|c| !c.is_control()));
+        }
+    }
+
+    // Deletes the last character typed, or the last mark once there is nothing being typed
+    pub fn erase(&mut self) {
+        match (self.typing, self.marks.last_mut()) {
+            (true, Some(Mark::Label { text, .. })) => {
+                text.pop();
+            },
+            _ => {
+                self.marks.pop();
+            },
+        }
+    }
+
+    // Stops typing the current label, dropping it if nothing was typed
+    pub fn finish(&mut self) {
+        if self.typing {
+            self.typing = false;
+            if let Some(Mark::Label { text, .. }) = self.marks.last() {
+                if text.is_empty() {
+                    self.marks.pop();
+                }
+            }
+        }
+    }
+
+    // Adds the marks to a draw list, along with the arrow being dragged out to the cursor and a caret
+    // on the label being typed unless the frame is being saved
+    pub fn draw(&self, cursor: [f64; 2], transform: Matrix2d, list: &mut DrawList) {
+        let dragged = self.start.map(|from| Mark::Arrow { from, to: cursor });
+        for mark in self.marks.iter().chain(dragged.iter()) {
+            match mark {
+                Mark::Arrow { from, to } => {
+                    list.line(INK, 1.0, [from[0], from[1], to[0], to[1]], transform);
+                    let length = (to[0] - from[0]).hypot(to[1] - from[1]).max(f64::EPSILON);
+                    let (d_x, d_y) = ((to[0] - from[0]) / length, (to[1] - from[1]) / length);
+                    let (back_x, back_y) = (to[0] - d_x * HEAD_LENGTH, to[1] - d_y * HEAD_LENGTH);
+                    let head = [
+                        [to[0], to[1]],
+                        [back_x - d_y * HEAD_WIDTH, back_y + d_x * HEAD_WIDTH],
+                        [back_x + d_y * HEAD_WIDTH, back_y - d_x * HEAD_WIDTH],
+                    ];
+                    list.polygon(INK, &head, transform);
+                },
+                Mark::Label { at, text } => list.text(text, INK, at[0], at[1], LABEL_SCALE, transform),
+            }
+        }
+        if let (true, false, Some(Mark::Label { at, text })) = (self.typing, self.export, self.marks.last()) {
+            let x = at[0] + crate::text::width(text, LABEL_SCALE);
+            list.text("_", INK, x, at[1], LABEL_SCALE, transform);
+        }
+    }
+}
+
+// First pair of paths of the form `annotatedN.png` and `annotatedN.svg` in the given directory that
+// are both free, so earlier exports are never overwritten
+pub fn next_paths(dir: &str) -> io::Result<(PathBuf, PathBuf)> {
+    std::fs::create_dir_all(dir)?;
+    let dir = Path::new(dir);
+    let mut n = 0;
+    loop {
+        let (png, svg) = (dir.join(format!("annotated{}.png", n)), dir.join(format!("annotated{}.svg", n)));
+        if !png.exists() && !svg.exists() {
+            return Ok((png, svg));
+        }
+        n += 1;
+    }
+}
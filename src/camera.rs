@@ -0,0 +1,57 @@
+use graphics::math::Matrix2d;
+use graphics::Transformed;
+
+use crate::simulation::Planet;
+
+// Zoom used when following a planet, unless given
+const FOLLOW_ZOOM: f64 = 3.0;
+
+// What part of the world a window shows
+#[derive(Clone, Copy, PartialEq)]
+pub enum Camera {
+    Overview,                            // The whole world, as in the main window
+    Follow { planet: usize, zoom: f64 }, // Zoomed in on a planet, keeping it centred
+    Director,                            // Wherever the director is framing, for unattended displays
+    Shot { x: f64, y: f64, zoom: f64 },  // Zoomed in on a point, keeping it centred, as the director frames it
+}
+
+impl Camera {
+    // Reads a camera written as "overview", "director", or "follow:planetN" with an optional ":zoom"
+    pub fn parse(s: &str) -> Result<Camera, String> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let planet = |part: &str| part.strip_prefix("planet").and_then(|n| n.parse::<usize>().ok());
+        match parts[..] {
+            ["overview"] => Ok(Camera::Overview),
+            ["director"] => Ok(Camera::Director),
+            ["follow", target] => match planet(target) {
+                Some(planet) => Ok(Camera::Follow { planet, zoom: FOLLOW_ZOOM }),
+                None => Err(format!("Camera must follow a planet such as planet0: {}", s)),
+            },
+            ["follow", target, zoom] => match (planet(target), zoom.parse::<f64>()) {
+                (Some(planet), Ok(zoom)) if zoom > 0.0 => Ok(Camera::Follow { planet, zoom }),
+                _ => Err(format!("Camera must follow a planet such as planet0, with a positive zoom: {}", s)),
+            },
+            _ => Err(format!("Camera must be overview, director or follow:planetN[:zoom]: {}", s)),
+        }
+    }
+
+    // How much larger than in the main window the world is shown
+    pub fn zoom(&self) -> f64 {
+        match *self {
+            Camera::Follow { zoom, .. } | Camera::Shot { zoom, .. } => zoom,
+            Camera::Overview | Camera::Director => 1.0,
+        }
+    }
+
+    // Transform from the world to a window of the given size, starting from the window's own transform
+    pub fn transform(&self, base: Matrix2d, width: f64, height: f64, planets: &[Planet]) -> Matrix2d {
+        match *self {
+            Camera::Follow { planet, zoom } => match planets.get(planet) {
+                Some(planet) => base.trans(width / 2.0, height / 2.0).zoom(zoom).trans(-planet.x, -planet.y),
+                None => base,
+            },
+            Camera::Shot { x, y, zoom } => base.trans(width / 2.0, height / 2.0).zoom(zoom).trans(-x, -y),
+            Camera::Overview | Camera::Director => base,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::events::Event;
+
+// Events screenshots can be taken on: those from the event bus, plus a satellite captured by a third
+// planet in turn and the number of satellites reaching a new high
+pub const TRIGGERS: [&str; 8] = ["near_miss", "merge", "capture", "resonance", "breakup", "disruption", "slingshot", "peak"];
+
+// Least simulation time (in seconds) between screenshots of a new peak, so that a population
+// climbing steadily does not save one every frame
+const PEAK_INTERVAL: f64 = 2.0;
+
+// Saves the window as a numbered sequence of PNG frames, sampled at a steady rate of simulation
+// time rather than once per render, so the frames play back at the intended speed however fast
+// the window happened to be drawing
+pub struct FrameCapture {
+    dir: PathBuf,
+    rate: f64,        // Frames per second of simulation time
+    next_sample: f64, // Simulation time the next frame is due
+    frames: usize,    // Frames saved so far
+}
+
+impl FrameCapture {
+    pub fn create(dir: &str, rate: f64) -> io::Result<FrameCapture> {
+        std::fs::create_dir_all(dir)?;
+        Ok(FrameCapture {
+            dir: PathBuf::from(dir),
+            rate,
+            next_sample: 0.0,
+            frames: 0,
+        })
+    }
+
+    // Saves what was just drawn to the window, once for every frame that has fallen due by the given
+    // simulation time. Renders further apart than a frame repeat, and renders closer together are skipped.
+    pub fn capture(&mut self, time: f64, width: u32, height: u32) -> io::Result<()> {
+        if time < self.next_sample {
+            return Ok(());
+        }
+        let image = read_window(width, height);
+        while self.next_sample <= time {
+            let path = self.dir.join(format!("frame{:06}.png", self.frames));
+            image.save(&path).map_err(io::Error::other)?;
+            self.frames += 1;
+            self.next_sample += 1.0 / self.rate;
+        }
+        Ok(())
+    }
+}
+
+// Takes a screenshot by itself when chosen events happen, so rare moments are kept even with nobody
+// watching. Each kind of event is shot at most a set number of times, except a peak in the number of
+// satellites, which keeps one screenshot overwritten with each new high.
+pub struct EventShots {
+    dir: PathBuf,
+    triggers: Vec<String>,                 // Kinds of event to take screenshots on
+    limit: usize,                          // Most screenshots taken of each kind
+    taken: HashMap<&'static str, usize>,   // Screenshots taken of each kind so far
+    captors: HashMap<u64, HashSet<usize>>, // Planets each satellite has been bound to through captures
+    peak: usize,                           // Most live satellites seen at once
+    peak_shot: Option<f64>,                // Simulation time of the last screenshot of a peak
+    due: Vec<String>,                      // Names of the screenshots to take at the next render
+}
+
+impl EventShots {
+    pub fn create(dir: &str, triggers: Vec<String>, limit: usize) -> io::Result<EventShots> {
+        std::fs::create_dir_all(dir)?;
+        Ok(EventShots {
+            dir: PathBuf::from(dir),
+            triggers,
+            limit,
+            taken: HashMap::new(),
+            captors: HashMap::new(),
+            peak: 0,
+            peak_shot: None,
+            due: Vec::new(),
+        })
+    }
+
+    // Queues a screenshot of the kind, unless it is not wanted or has been shot enough already
+    fn queue(&mut self, kind: &'static str, time: f64) {
+        if !self.triggers.iter().any(|trigger| trigger == kind) {
+            return;
+        }
+        let taken = self.taken.entry(kind).or_insert(0);
+        if *taken < self.limit {
+            *taken += 1;
+            self.due.push(format!("{:08.1}s-{}.png", time, kind));
+        }
+    }
+
+    // Looks through an update's events and the number of live satellites for moments worth a screenshot
+    pub fn notice(&mut self, time: f64, events: &[Event], live: usize) {
+        for event in events {
+            self.queue(event.name(), time);
+            if let Event::Capture { satellite, from, planet, .. } = *event {
+                let captors = self.captors.entry(satellite).or_default();
+                captors.insert(from);
+                captors.insert(planet);
+                if captors.len() == 3 {
+                    self.queue("slingshot", time);
+                }
+            }
+        }
+        if live > self.peak {
+            self.peak = live;
+            let wanted = self.triggers.iter().any(|trigger| trigger == "peak");
+            if wanted && self.peak_shot.is_none_or(|shot| time - shot >= PEAK_INTERVAL) {
+                self.peak_shot = Some(time);
+                self.due.push("peak.png".to_string());
+            }
+        }
+    }
+
+    // Saves what was just drawn to the window as each screenshot that has fallen due
+    pub fn save(&mut self, width: u32, height: u32) -> io::Result<()> {
+        if self.due.is_empty() {
+            return Ok(());
+        }
+        let image = read_window(width, height);
+        for name in self.due.drain(..) {
+            let path = self.dir.join(name);
+            image.save(&path).map_err(io::Error::other)?;
+            println!("Saved screenshot {}", path.display());
+        }
+        Ok(())
+    }
+}
+
+// Saves what was just drawn to the window as a single PNG
+pub fn save_window(path: &Path, width: u32, height: u32) -> io::Result<()> {
+    read_window(width, height).save(path).map_err(io::Error::other)
+}
+
+// Reads back the window's pixels, turned the right way up and without alpha
+fn read_window(width: u32, height: u32) -> image::RgbImage {
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    unsafe {
+        gl::ReadPixels(0, 0, width as i32, height as i32, gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut _);
+    }
+    // OpenGL rows start from the bottom
+    image::RgbImage::from_fn(width, height, |x, y| {
+        let i = (((height - 1 - y) * width + x) * 4) as usize;
+        image::Rgb([pixels[i], pixels[i + 1], pixels[i + 2]])
+    })
+}
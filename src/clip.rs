@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+use std::io;
+use std::path::PathBuf;
+
+use crate::events::Event;
+use crate::export;
+use crate::simulation::{Planet, Satellite};
+
+// Position of a single satellite in a frame
+pub struct SatelliteSample {
+    pub id: u64,
+    pub color: [f32; 4],
+    pub radius: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+// State of the simulation at one instant
+pub struct Frame {
+    pub time: f64,
+    pub satellites: Vec<SatelliteSample>,
+    pub planets: Vec<(f64, f64)>, // Position of each planet
+}
+
+// A clip waiting for the frames after its event
+struct PendingClip {
+    event: Event,
+    time: f64, // Simulation time of the event
+    frames: Vec<Frame>,
+}
+
+// Keeps a rolling buffer of recent frames, and when an event happens saves a clip of the frames
+// around it (half before, half after) to a directory, in the same format as the keyframe export.
+pub struct ClipRecorder {
+    dir: PathBuf,
+    length: f64,                   // Length of each clip in seconds
+    rate: f64,                     // Frames sampled per second of simulation time
+    three_d: bool,                 // Whether satellites move out of the plane, so clips keep their heights
+    next_sample: f64,              // Simulation time the next frame is due
+    history: VecDeque<Frame>,      // Frames from the last half clip
+    pending: Option<PendingClip>,  // Clip still collecting frames after its event
+}
+
+impl ClipRecorder {
+    pub fn create(dir: &str, length: f64, rate: f64, three_d: bool) -> io::Result<ClipRecorder> {
+        std::fs::create_dir_all(dir)?;
+        Ok(ClipRecorder {
+            dir: PathBuf::from(dir),
+            length,
+            rate,
+            three_d,
+            next_sample: 0.0,
+            history: VecDeque::new(),
+            pending: None,
+        })
+    }
+
+    // Samples a frame if one is due, and saves the pending clip once it has all its frames
+    pub fn record(&mut self, time: f64, satellites: &[Satellite], planets: &[Planet], width: f64, height: f64) -> io::Result<()> {
+        if time < self.next_sample {
+            return Ok(());
+        }
+        self.next_sample += 1.0 / self.rate;
+
+        let frame = Frame {
+            time,
+            satellites: satellites.iter().filter(|sat| sat.fate.is_none()).map(|sat| SatelliteSample {
+                id: sat.id,
+                color: sat.color,
+                radius: sat.radius,
+                x: sat.x,
+                y: sat.y,
+                z: sat.z,
+            }).collect(),
+            planets: planets.iter().map(|planet| (planet.x, planet.y)).collect(),
+        };
+
+        match self.pending.as_mut() {
+            Some(pending) => {
+                pending.frames.push(frame);
+                if time - pending.time >= self.length / 2.0 {
+                    let pending = self.pending.take().unwrap();
+                    let name = format!("{:08.1}s-{}.json", pending.time, pending.event.name());
+                    let path = self.dir.join(name);
+                    export::write_clip(&path, width, height, planets, self.rate, self.three_d, &pending.frames)?;
+                    println!("Saved highlight {}", path.display());
+                }
+            },
+            None => {
+                self.history.push_back(frame);
+                while self.history.front().is_some_and(|first| time - first.time > self.length / 2.0) {
+                    self.history.pop_front();
+                }
+            },
+        }
+        Ok(())
+    }
+
+    // Starts a clip around the event, unless one is already being recorded
+    pub fn trigger(&mut self, event: Event, time: f64) {
+        if self.pending.is_some() {
+            return;
+        }
+        self.pending = Some(PendingClip {
+            event,
+            time,
+            frames: self.history.drain(..).collect(),
+        });
+    }
+}
@@ -0,0 +1,116 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+
+// Most satellites a single paste may add, so a stray paste of something huge cannot flood the screen
+const MAX_PASTED: usize = 100;
+
+// Programs that print the system clipboard, tried in turn until one is installed
+const PASTE_COMMANDS: [&[&str]; 5] = [
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-o"],
+    &["xsel", "--clipboard", "--output"],
+    &["pbpaste"],
+    &["powershell", "-NoProfile", "-Command", "Get-Clipboard"],
+];
+
+// Programs that set the system clipboard from what they read, in the same order
+const COPY_COMMANDS: [&[&str]; 5] = [
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard", "-i"],
+    &["xsel", "--clipboard", "--input"],
+    &["pbcopy"],
+    &["clip"],
+];
+
+// A satellite as written in a pasted snippet, such as {"x": 400, "y": 200, "v_x": 50, "v_y": 0},
+// with an optional `color` of three or four components from 0 to 1
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Body {
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub v_x: f64,
+    #[serde(default)]
+    pub v_y: f64,
+    #[serde(default)]
+    color: Option<Vec<f32>>,
+}
+
+impl Body {
+    // The body's colour, or None to pick one as for any other new satellite
+    pub fn color(&self) -> Option<[f32; 4]> {
+        match self.color.as_deref() {
+            Some(&[r, g, b]) => Some([r, g, b, 1.0]),
+            Some(&[r, g, b, a]) => Some([r, g, b, a]),
+            _ => None,
+        }
+    }
+}
+
+// Reads the satellites described by a JSON snippet of one body or a list of them
+pub fn parse(s: &str) -> Result<Vec<Body>, String> {
+    let s = s.trim();
+    let bodies = match s.starts_with('[') {
+        true => serde_json::from_str(s),
+        false => serde_json::from_str(s).map(|body| vec![body]),
+    };
+    let bodies: Vec<Body> = bodies.map_err(|e| format!("Pasted satellites must be JSON like {{\"x\": 400, \"y\": 200, \"v_x\": 50, \"v_y\": 0}}: {}", e))?;
+    if bodies.len() > MAX_PASTED {
+        return Err(format!("Cannot paste more than {} satellites at once", MAX_PASTED));
+    }
+    for body in bodies.iter() {
+        if ![body.x, body.y, body.v_x, body.v_y].iter().all(|value| value.is_finite()) {
+            return Err("Pasted satellites must have finite positions and velocities".to_string());
+        }
+        if let Some(color) = body.color.as_ref() {
+            if !(3..=4).contains(&color.len()) || color.iter().any(|c| !(0.0..=1.0).contains(c)) {
+                return Err("Pasted colours must have three or four components from 0 to 1".to_string());
+            }
+        }
+    }
+    Ok(bodies)
+}
+
+// Text on the system clipboard, read through whichever clipboard program is installed
+pub fn paste() -> io::Result<String> {
+    let mut found = false;
+    for command in PASTE_COMMANDS.iter() {
+        match Command::new(command[0]).args(&command[1..]).output() {
+            Ok(output) if output.status.success() => return Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+            // Installed but unable to reach a clipboard, such as wl-paste outside Wayland
+            Ok(_) => found = true,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+            Err(e) => return Err(e),
+        }
+    }
+    match found {
+        true => Err(io::Error::other("Could not read the clipboard, or it is empty")),
+        false => Err(io::Error::other("No clipboard program found (install wl-clipboard, xclip or xsel)")),
+    }
+}
+
+// Puts text on the system clipboard, through whichever clipboard program is installed
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut found = false;
+    for command in COPY_COMMANDS.iter() {
+        let mut child = match Command::new(command[0]).args(&command[1..]).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn() {
+            Ok(child) => child,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        found = true;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+    match found {
+        true => Err(io::Error::other("Could not set the clipboard")),
+        false => Err(io::Error::other("No clipboard program found (install wl-clipboard, xclip or xsel)")),
+    }
+}
@@ -0,0 +1,126 @@
+use serde::Deserialize;
+
+// Calendar the simulation runs on, as given in the `clock` section of a scene file
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ClockConfig {
+    pub epoch: String,   // Date at the start of the simulation, as YYYY-MM-DD with an optional THH:MM[:SS] (UTC)
+    #[serde(default = "default_time_scale")]
+    pub time_scale: f64, // Real seconds that pass for every second of simulation time
+}
+
+fn default_time_scale() -> f64 { 86400.0 }
+
+// Simulation time, and for scenes with a calendar the date it corresponds to, formatted the same way
+// wherever it is shown or recorded
+pub struct Clock {
+    epoch: Option<i64>, // Unix time (in seconds) at the start of the simulation, for scenes with a calendar
+    time_scale: f64,    // Real seconds that pass for every second of simulation time
+}
+
+impl Clock {
+    pub fn new(config: Option<&ClockConfig>) -> Result<Clock, String> {
+        match config {
+            Some(config) => Ok(Clock {
+                epoch: Some(parse_date(&config.epoch)?),
+                time_scale: config.time_scale,
+            }),
+            None => Ok(Clock {
+                epoch: None,
+                time_scale: 1.0,
+            }),
+        }
+    }
+
+    // Moves the epoch to the given Unix time and runs at the pace of the wall clock from then on,
+    // returning the simulation seconds that would have passed since the old epoch
+    pub fn rebase(&mut self, now: i64) -> Option<f64> {
+        let elapsed = (now - self.epoch?) as f64 / self.time_scale;
+        self.epoch = Some(now);
+        self.time_scale = 1.0;
+        Some(elapsed)
+    }
+
+    // Unix time (in seconds) at the given simulation time, for scenes with a calendar
+    pub fn unix_time(&self, time: f64) -> Option<f64> {
+        Some(self.epoch? as f64 + time * self.time_scale)
+    }
+
+    // Date and time (UTC) at the given simulation time, for scenes with a calendar
+    pub fn date(&self, time: f64) -> Option<String> {
+        Some(format_date(self.unix_time(time)?.floor() as i64))
+    }
+
+    // Elapsed simulation time, followed by the date for scenes with a calendar
+    pub fn stamp(&self, time: f64) -> String {
+        match self.date(time) {
+            Some(date) => format!("{:.1}s, {}", time, date),
+            None => format!("{:.1}s", time),
+        }
+    }
+
+    // Extra header fields for exports, giving the epoch and time scale for scenes with a calendar
+    pub fn header(&self) -> String {
+        match self.epoch {
+            Some(epoch) => format!(",\"epoch\":\"{}\",\"time_scale\":{}", format_date(epoch), self.time_scale),
+            None => String::new(),
+        }
+    }
+}
+
+// Days from 1970-01-01 to the given date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// Date (year, month, day) the given number of days after 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// Reads YYYY-MM-DD with an optional THH:MM[:SS] as Unix time in seconds
+pub fn parse_date(s: &str) -> Result<i64, String> {
+    let error = || format!("Date must be written as YYYY-MM-DD or YYYY-MM-DDTHH:MM[:SS]: {}", s);
+    let (date, time) = match s.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (s, None),
+    };
+    let date: Vec<i64> = date.split('-').map(|part| part.parse().map_err(|_| error())).collect::<Result<_, _>>()?;
+    let time: Vec<i64> = match time {
+        Some(time) => time.split(':').map(|part| part.parse().map_err(|_| error())).collect::<Result<_, _>>()?,
+        None => vec![0, 0],
+    };
+    match (&date[..], &time[..]) {
+        (&[year, month, day], &[hour, minute]) | (&[year, month, day], &[hour, minute, _])
+            if (1..=12).contains(&month) && (1..=31).contains(&day) && (0..24).contains(&hour) && (0..60).contains(&minute) =>
+        {
+            let second = time.get(2).copied().unwrap_or(0);
+            if !(0..60).contains(&second) {
+                return Err(error());
+            }
+            Ok(days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second)
+        },
+        _ => Err(error()),
+    }
+}
+
+// Writes Unix time in seconds as YYYY-MM-DD HH:MM:SS
+pub fn format_date(unix_time: i64) -> String {
+    let (year, month, day) = civil_from_days(unix_time.div_euclid(86400));
+    let seconds = unix_time.rem_euclid(86400);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
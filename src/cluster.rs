@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+// A group of satellites found close together
+pub struct Cluster {
+    pub hull: Vec<(f64, f64)>, // Convex hull of the members, in order around the outline
+    pub size: usize,           // Number of members
+}
+
+// Finds clusters of points using DBSCAN: points with at least `min_points` neighbours within `eps`
+// are core points, and clusters are the connected groups of core points plus their neighbours.
+// Neighbour queries use a grid of `eps`-sized cells so each only looks at nearby points.
+pub fn find_clusters(points: &[(f64, f64)], eps: f64, min_points: usize) -> Vec<Cluster> {
+    let cell = |p: (f64, f64)| ((p.0 / eps).floor() as i64, (p.1 / eps).floor() as i64);
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, p) in points.iter().enumerate() {
+        grid.entry(cell(*p)).or_default().push(i);
+    }
+
+    let eps_sq = eps * eps;
+    let neighbours = |i: usize| {
+        let (cx, cy) = cell(points[i]);
+        let mut found = Vec::new();
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if let Some(members) = grid.get(&(cx + dx, cy + dy)) {
+                    for &j in members {
+                        let distance_x = points[i].0 - points[j].0;
+                        let distance_y = points[i].1 - points[j].1;
+                        if (distance_x * distance_x) + (distance_y * distance_y) <= eps_sq {
+                            found.push(j);
+                        }
+                    }
+                }
+            }
+        }
+        found
+    };
+
+    let mut labels: Vec<Option<usize>> = vec![None; points.len()];
+    let mut visited = vec![false; points.len()];
+    let mut clusters: Vec<Vec<usize>> = Vec::new();
+    for i in 0..points.len() {
+        if visited[i] {
+            continue;
+        }
+        visited[i] = true;
+        let mut queue = neighbours(i);
+        if queue.len() < min_points {
+            continue;
+        }
+
+        // Grow a new cluster out from this core point
+        let id = clusters.len();
+        let mut members = vec![i];
+        labels[i] = Some(id);
+        while let Some(j) = queue.pop() {
+            if labels[j].is_none() {
+                labels[j] = Some(id);
+                members.push(j);
+            }
+            if !visited[j] {
+                visited[j] = true;
+                let more = neighbours(j);
+                if more.len() >= min_points {
+                    queue.extend(more);
+                }
+            }
+        }
+        clusters.push(members);
+    }
+
+    clusters.into_iter().map(|members| {
+        let member_points: Vec<(f64, f64)> = members.iter().map(|&i| points[i]).collect();
+        Cluster {
+            hull: convex_hull(member_points),
+            size: members.len(),
+        }
+    }).collect()
+}
+
+// Computes the convex hull of a set of points using Andrew's monotone chain algorithm
+pub fn convex_hull(mut points: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<(f64, f64)> = Vec::with_capacity(points.len() * 2);
+    // Lower hull, then upper hull
+    for pass in 0..2 {
+        let start = hull.len();
+        let ordered: Box<dyn Iterator<Item = &(f64, f64)>> = if pass == 0 {
+            Box::new(points.iter())
+        } else {
+            Box::new(points.iter().rev())
+        };
+        for &p in ordered {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // The last point of each half is the first point of the next
+        hull.pop();
+    }
+    hull
+}
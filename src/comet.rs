@@ -0,0 +1,38 @@
+use crate::simulation::{Planet, Satellite};
+
+// Length (in px) of a tail right by a planet's surface, shrinking further out
+const MAX_LENGTH: f64 = 40.0;
+
+// Particles drawn along each tail
+const PARTICLES: usize = 12;
+
+// Most a particle strays from the line of the tail by its end, in radians either side
+const FAN: f64 = 0.3;
+
+// Spread in [-1, 1] for a tail particle, fixed for each satellite so tails don't flicker
+fn jitter(id: u64, k: usize) -> f64 {
+    let mut h = id.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ (k as u64).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h ^= h >> 31;
+    h = h.wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^= h >> 29;
+    (h % 2001) as f64 / 1000.0 - 1.0
+}
+
+// Particles of a comet tail streaming away from the nearest planet as if it were a star, longer and
+// brighter the closer the satellite is, as (x, y, radius, alpha)
+pub fn tail(sat: &Satellite, planets: &[Planet]) -> Vec<(f64, f64, f64, f32)> {
+    let surface = |planet: &Planet| (sat.x - planet.x).hypot(sat.y - planet.y) - planet.radius;
+    let star = match planets.iter().min_by(|a, b| surface(a).partial_cmp(&surface(b)).unwrap()) {
+        Some(star) => star,
+        None => return Vec::new(),
+    };
+    let strength = star.radius / (surface(star).max(0.0) + star.radius);
+    let length = MAX_LENGTH * strength;
+    let away = (sat.y - star.y).atan2(sat.x - star.x);
+    (1..=PARTICLES).map(|k| {
+        let t = k as f64 / PARTICLES as f64;
+        let (sin, cos) = (away + jitter(sat.id, k) * FAN * t).sin_cos();
+        let radius = sat.radius * (1.0 - 0.6 * t);
+        (sat.x + length * t * cos, sat.y + length * t * sin, radius, (strength * (1.0 - t) * 0.8) as f32)
+    }).collect()
+}
@@ -0,0 +1,76 @@
+// Values sampled on a regular grid starting from the origin
+pub struct Grid {
+    pub cols: usize,
+    pub rows: usize,
+    pub spacing: f64,     // Distance between neighbouring samples
+    pub values: Vec<f64>, // Row by row
+}
+
+impl Grid {
+    // Samples a function at every point of a grid covering the given area
+    pub fn sample<F: Fn(f64, f64) -> f64>(width: f64, height: f64, spacing: f64, f: F) -> Grid {
+        let cols = (width / spacing).ceil() as usize + 1;
+        let rows = (height / spacing).ceil() as usize + 1;
+        let mut values = Vec::with_capacity(cols * rows);
+        for row in 0..rows {
+            for col in 0..cols {
+                values.push(f(col as f64 * spacing, row as f64 * spacing));
+            }
+        }
+        Grid {
+            cols,
+            rows,
+            spacing,
+            values,
+        }
+    }
+
+    pub fn get(&self, col: usize, row: usize) -> f64 {
+        self.values[row * self.cols + col]
+    }
+
+    // Line segments tracing where the sampled values cross `level`, found by marching squares
+    // with linear interpolation along each cell edge
+    pub fn contour(&self, level: f64) -> Vec<[f64; 4]> {
+        let mut segments = Vec::new();
+        for row in 0..self.rows - 1 {
+            for col in 0..self.cols - 1 {
+                // Corners clockwise from the top left
+                let values = [
+                    self.get(col, row),
+                    self.get(col + 1, row),
+                    self.get(col + 1, row + 1),
+                    self.get(col, row + 1),
+                ];
+                let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+                let above: Vec<bool> = values.iter().map(|v| *v > level).collect();
+
+                // Where the contour crosses each edge that has corners on either side of it, in edge order
+                let mut crossings = Vec::with_capacity(4);
+                for edge in 0..4 {
+                    let (a, b) = (edge, (edge + 1) % 4);
+                    if above[a] != above[b] {
+                        let t = (level - values[a]) / (values[b] - values[a]);
+                        let x = corners[a].0 + (corners[b].0 - corners[a].0) * t;
+                        let y = corners[a].1 + (corners[b].1 - corners[a].1) * t;
+                        crossings.push(((col as f64 + x) * self.spacing, (row as f64 + y) * self.spacing));
+                    }
+                }
+
+                match crossings.len() {
+                    2 => segments.push([crossings[0].0, crossings[0].1, crossings[1].0, crossings[1].1]),
+                    4 => {
+                        // A saddle: pair up the crossings so the centre joins the corners it agrees with
+                        let centre = values.iter().sum::<f64>() / 4.0 > level;
+                        let (first, second) = if centre == above[0] { ((0, 1), (2, 3)) } else { ((3, 0), (1, 2)) };
+                        for (i, j) in [first, second].iter() {
+                            segments.push([crossings[*i].0, crossings[*i].1, crossings[*j].0, crossings[*j].1]);
+                        }
+                    },
+                    _ => {},
+                }
+            }
+        }
+        segments
+    }
+}
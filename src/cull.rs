@@ -0,0 +1,28 @@
+use graphics::math::{transform_pos, transform_vec, Matrix2d};
+
+// What a camera can see, so that bodies and trails outside it can be skipped. Works in normalised
+// device coordinates, where the window runs from -1 to 1 both ways.
+pub struct Frustum {
+    transform: Matrix2d, // Transform from the world to normalised device coordinates
+}
+
+impl Frustum {
+    pub fn new(transform: Matrix2d) -> Frustum {
+        Frustum { transform }
+    }
+
+    // Whether any of a disc could be on screen
+    pub fn sees(&self, x: f64, y: f64, radius: f64) -> bool {
+        let [x, y] = transform_pos(self.transform, [x, y]);
+        let [a_x, a_y] = transform_vec(self.transform, [radius, 0.0]);
+        let [b_x, b_y] = transform_vec(self.transform, [0.0, radius]);
+        x.abs() <= 1.0 + a_x.abs() + b_x.abs() && y.abs() <= 1.0 + a_y.abs() + b_y.abs()
+    }
+
+    // Whether a line could cross the screen, which it can't if both ends are off past the same edge
+    pub fn sees_line(&self, a: (f64, f64), b: (f64, f64)) -> bool {
+        let [a_x, a_y] = transform_pos(self.transform, [a.0, a.1]);
+        let [b_x, b_y] = transform_pos(self.transform, [b.0, b.1]);
+        !((a_x < -1.0 && b_x < -1.0) || (a_x > 1.0 && b_x > 1.0) || (a_y < -1.0 && b_y < -1.0) || (a_y > 1.0 && b_y > 1.0))
+    }
+}
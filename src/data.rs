@@ -0,0 +1,12 @@
+use std::env;
+use std::path::PathBuf;
+
+// Directory kept between sessions: $XDG_DATA_HOME/orbits, falling back to ~/.local/share/orbits, or
+// None if neither variable is set
+pub fn dir() -> Option<PathBuf> {
+    let data = match env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".local").join("share"),
+    };
+    Some(data.join("orbits"))
+}
@@ -0,0 +1,208 @@
+use crate::camera::Camera;
+use crate::cluster::Cluster;
+use crate::events::Event;
+use crate::simulation::{Origin, Planet, Satellite};
+
+// Seconds without input before the director takes the main window back from the viewer
+const IDLE_HANDOVER: f64 = 10.0;
+
+// Shortest and longest a shot is held, in seconds, so that cuts come neither too fast to follow nor
+// so slowly the display goes stale
+const MIN_SHOT: f64 = 4.0;
+const MAX_SHOT: f64 = 12.0;
+
+// Seconds an event stays worth cutting to, while waiting out the shot before it
+const EVENT_MEMORY: f64 = 3.0;
+
+// Zoom events are framed at
+const EVENT_ZOOM: f64 = 3.0;
+
+// Closest zoom any shot is framed at
+const MAX_ZOOM: f64 = 4.0;
+
+// Space (in px) left around whatever is framed
+const MARGIN: f64 = 40.0;
+
+// Furthest (in px) from a planet's surface a pass counts as a flyby worth framing
+const FLYBY_RANGE: f64 = 60.0;
+
+// Seconds the camera takes to get most of the way to a new framing
+const EASE: f64 = 1.0;
+
+// What a shot is framing
+#[derive(Clone, Copy, PartialEq)]
+enum Subject {
+    Overview,                                // The whole world
+    Event { x: f64, y: f64 },                // Where something just happened
+    Flyby { satellite: u64, planet: usize }, // A satellite passing close by a planet
+    Cluster { x: f64, y: f64, radius: f64 }, // The largest clump of satellites
+}
+
+// Kinds of shot cut between in turn when nothing has happened: the closest flyby, the largest
+// cluster and the whole world
+const SHOT_KINDS: usize = 3;
+
+// Cinematic camera for unattended displays: frames the most interesting part of the world, cutting
+// to events as they happen and otherwise between the closest flyby, the largest cluster and the
+// whole world, easing smoothly between framings
+pub struct Director {
+    width: f64,                     // Size of the world
+    height: f64,
+    subject: Subject,               // What the current shot frames
+    since_cut: f64,                 // Seconds the current shot has been held
+    next: usize,                    // Kind of shot to try next, counting through SHOT_KINDS
+    event: Option<(f64, f64, f64)>, // Latest event worth cutting to, and how long ago it happened
+    x: f64,                         // Centre and zoom of the camera, as eased towards the shot
+    y: f64,
+    zoom: f64,
+    idle: f64,                      // Seconds since the viewer last touched anything
+    main: bool,                     // Whether the director runs the main window while nobody is using it
+}
+
+impl Director {
+    pub fn new(width: f64, height: f64, main: bool) -> Director {
+        Director {
+            width,
+            height,
+            subject: Subject::Overview,
+            since_cut: 0.0,
+            next: 0,
+            event: None,
+            x: width / 2.0,
+            y: height / 2.0,
+            zoom: 1.0,
+            idle: 0.0,
+            main,
+        }
+    }
+
+    // Hands the main window back to the viewer, starting the director over from the whole world when
+    // it takes over again
+    pub fn interrupt(&mut self) {
+        self.idle = 0.0;
+        if self.main {
+            self.subject = Subject::Overview;
+            self.since_cut = 0.0;
+            self.x = self.width / 2.0;
+            self.y = self.height / 2.0;
+            self.zoom = 1.0;
+        }
+    }
+
+    // Whether the director has the main window, which it takes once nobody has used it for a while
+    pub fn has_main(&self) -> bool {
+        self.main && self.idle >= IDLE_HANDOVER
+    }
+
+    // Camera framing the current shot, as eased so far
+    pub fn camera(&self) -> Camera {
+        Camera::Shot {
+            x: self.x,
+            y: self.y,
+            zoom: self.zoom,
+        }
+    }
+
+    // Moves on by `dt` seconds of real time, cutting to a new shot if one is due and easing towards it
+    pub fn update(&mut self, dt: f64, satellites: &[Satellite], planets: &[Planet], clusters: &[Cluster], events: &[Event]) {
+        self.idle += dt;
+        self.since_cut += dt;
+
+        // Remember the latest event worth a shot of its own
+        if let Some(event) = events.iter().rev().find(|event| !matches!(event, Event::Merge { .. })) {
+            let (x, y) = event.position();
+            self.event = Some((x, y, 0.0));
+        }
+        if let Some((x, y, age)) = self.event {
+            self.event = Some((x, y, age + dt)).filter(|_| age + dt < EVENT_MEMORY);
+        }
+
+        // Cut once the shot has been held long enough and there is an event to show, it has lost its
+        // subject, it was of an event that is over, or it has gone on too long
+        let target = self.target(satellites, planets);
+        let over = matches!(self.subject, Subject::Event { .. });
+        let due = self.since_cut >= MAX_SHOT || target.is_none() || over || self.event.is_some();
+        if self.since_cut >= MIN_SHOT && due {
+            self.cut(satellites, planets, clusters);
+        }
+
+        // Ease towards the shot, zooming evenly in and out
+        if let Some((x, y, zoom)) = self.target(satellites, planets) {
+            let ease = 1.0 - (-dt / EASE).exp();
+            self.x += (x - self.x) * ease;
+            self.y += (y - self.y) * ease;
+            self.zoom *= (zoom / self.zoom).powf(ease);
+        }
+    }
+
+    // Picks the next shot: the latest event if there is one, otherwise the next kind of shot in turn
+    // that has anything to frame. Event shots never follow each other, so that a busy world still
+    // gets a look at the bigger picture in between.
+    fn cut(&mut self, satellites: &[Satellite], planets: &[Planet], clusters: &[Cluster]) {
+        self.since_cut = 0.0;
+        if !matches!(self.subject, Subject::Event { .. }) {
+            if let Some((x, y, _)) = self.event.take() {
+                self.subject = Subject::Event { x, y };
+                return;
+            }
+        }
+        for _ in 0..SHOT_KINDS {
+            let subject = match self.next {
+                0 => self.flyby(satellites, planets),
+                1 => self.cluster(clusters),
+                _ => Some(Subject::Overview),
+            };
+            self.next = (self.next + 1) % SHOT_KINDS;
+            if let Some(subject) = subject {
+                self.subject = subject;
+                return;
+            }
+        }
+    }
+
+    // The live satellite passing closest to a planet's surface, if any is close enough
+    fn flyby(&self, satellites: &[Satellite], planets: &[Planet]) -> Option<Subject> {
+        satellites.iter()
+            .filter(|sat| sat.fate.is_none() && sat.origin != Origin::Belt)
+            .flat_map(|sat| {
+                planets.iter().enumerate().map(move |(i, planet)| {
+                    let gap = (sat.x - planet.x).hypot(sat.y - planet.y) - planet.radius;
+                    (gap, sat.id, i)
+                })
+            })
+            .filter(|(gap, _, _)| *gap < FLYBY_RANGE)
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .map(|(_, satellite, planet)| Subject::Flyby { satellite, planet })
+    }
+
+    // The largest cluster found on the last analysis pass, framed around its outline
+    fn cluster(&self, clusters: &[Cluster]) -> Option<Subject> {
+        let cluster = clusters.iter().filter(|cluster| !cluster.hull.is_empty()).max_by_key(|cluster| cluster.size)?;
+        let n = cluster.hull.len() as f64;
+        let x = cluster.hull.iter().map(|p| p.0).sum::<f64>() / n;
+        let y = cluster.hull.iter().map(|p| p.1).sum::<f64>() / n;
+        let radius = cluster.hull.iter().map(|p| (p.0 - x).hypot(p.1 - y)).fold(0.0, f64::max);
+        Some(Subject::Cluster { x, y, radius })
+    }
+
+    // Zoom that fits a circle of the given radius on screen
+    fn fit(&self, radius: f64) -> f64 {
+        (self.width.min(self.height) / (2.0 * (radius + MARGIN))).clamp(1.0, MAX_ZOOM)
+    }
+
+    // Centre and zoom the current shot calls for, or None once its subject is gone
+    fn target(&self, satellites: &[Satellite], planets: &[Planet]) -> Option<(f64, f64, f64)> {
+        match self.subject {
+            Subject::Overview => Some((self.width / 2.0, self.height / 2.0, 1.0)),
+            Subject::Event { x, y } => Some((x, y, EVENT_ZOOM)),
+            Subject::Flyby { satellite, planet } => {
+                let sat = satellites.iter().find(|sat| sat.id == satellite && sat.fate.is_none())?;
+                let planet = planets.get(planet)?;
+                let (x, y) = ((sat.x + planet.x) / 2.0, (sat.y + planet.y) / 2.0);
+                let radius = (sat.x - planet.x).hypot(sat.y - planet.y) / 2.0 + planet.radius;
+                Some((x, y, self.fit(radius)))
+            },
+            Subject::Cluster { x, y, radius } => Some((x, y, self.fit(radius))),
+        }
+    }
+}
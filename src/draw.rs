@@ -0,0 +1,220 @@
+use graphics::math::Matrix2d;
+use graphics::{clear, Ellipse, Line, Polygon, Rectangle};
+use graphics::Viewport;
+use opengl_graphics::GlGraphics;
+
+use crate::text;
+
+// How shapes are combined with whatever is already drawn under them
+#[derive(Clone, Copy, PartialEq)]
+pub enum Blend {
+    Alpha,    // Painted over it, as far as the shape is opaque
+    Additive, // Added to it, as far as the shape is opaque, so that overlaps brighten
+}
+
+// Something to draw, in the coordinates given by its transform
+pub enum Shape {
+    Clear([f32; 4]), // Fills the whole window, ignoring the transform
+    Blend(Blend),    // Changes how the shapes after it are blended, until the next change; text is always alpha blended
+    Rectangle { color: [f32; 4], rect: [f64; 4] },
+    RectangleBorder { color: [f32; 4], radius: f64, rect: [f64; 4] }, // Outline `radius` wide either side of the edge
+    Ellipse { color: [f32; 4], rect: [f64; 4] },                       // Filling the bounding rectangle
+    EllipseBorder { color: [f32; 4], radius: f64, rect: [f64; 4] },
+    Line { color: [f32; 4], radius: f64, ends: [f64; 4] },             // From (x1, y1) to (x2, y2)
+    Polygon { color: [f32; 4], points: Vec<[f64; 2]> },                 // Filled, with corners in order
+    Text { text: String, color: [f32; 4], x: f64, y: f64, scale: f64 }, // In the built-in bitmap font, from its top-left corner
+}
+
+// Everything to draw for a frame, in order, kept so that any backend can draw it and it can be
+// inspected without a window
+#[derive(Default)]
+pub struct DrawList {
+    pub items: Vec<(Shape, Matrix2d)>, // Each shape with its transform to the window
+}
+
+impl DrawList {
+    pub fn clear(&mut self, color: [f32; 4]) {
+        self.items.push((Shape::Clear(color), [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]));
+    }
+
+    pub fn blend(&mut self, blend: Blend) {
+        self.items.push((Shape::Blend(blend), [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]));
+    }
+
+    pub fn rectangle(&mut self, color: [f32; 4], rect: [f64; 4], transform: Matrix2d) {
+        self.items.push((Shape::Rectangle { color, rect }, transform));
+    }
+
+    pub fn rectangle_border(&mut self, color: [f32; 4], radius: f64, rect: [f64; 4], transform: Matrix2d) {
+        self.items.push((Shape::RectangleBorder { color, radius, rect }, transform));
+    }
+
+    pub fn ellipse(&mut self, color: [f32; 4], rect: [f64; 4], transform: Matrix2d) {
+        self.items.push((Shape::Ellipse { color, rect }, transform));
+    }
+
+    pub fn ellipse_border(&mut self, color: [f32; 4], radius: f64, rect: [f64; 4], transform: Matrix2d) {
+        self.items.push((Shape::EllipseBorder { color, radius, rect }, transform));
+    }
+
+    pub fn line(&mut self, color: [f32; 4], radius: f64, ends: [f64; 4], transform: Matrix2d) {
+        self.items.push((Shape::Line { color, radius, ends }, transform));
+    }
+
+    pub fn polygon(&mut self, color: [f32; 4], points: &[[f64; 2]], transform: Matrix2d) {
+        self.items.push((Shape::Polygon { color, points: points.to_vec() }, transform));
+    }
+
+    pub fn text(&mut self, text: &str, color: [f32; 4], x: f64, y: f64, scale: f64, transform: Matrix2d) {
+        self.items.push((Shape::Text { text: text.to_string(), color, x, y, scale }, transform));
+    }
+}
+
+// Draws draw lists into a window
+pub trait Backend {
+    fn render(&mut self, list: &DrawList, viewport: Viewport);
+}
+
+impl Backend for GlGraphics {
+    fn render(&mut self, list: &DrawList, viewport: Viewport) {
+        self.draw(viewport, |c, gl| {
+            let mut draw_state = c.draw_state;
+            for (shape, transform) in list.items.iter() {
+                let transform = *transform;
+                match shape {
+                    Shape::Clear(color) => clear(*color, gl),
+                    Shape::Blend(blend) => {
+                        draw_state = draw_state.blend(match blend {
+                            Blend::Alpha => graphics::draw_state::Blend::Alpha,
+                            Blend::Additive => graphics::draw_state::Blend::Lighter,
+                        })
+                    },
+                    Shape::Rectangle { color, rect } => Rectangle::new(*color).draw(*rect, &draw_state, transform, gl),
+                    Shape::RectangleBorder { color, radius, rect } => {
+                        Rectangle::new_border(*color, *radius).draw(*rect, &draw_state, transform, gl)
+                    },
+                    Shape::Ellipse { color, rect } => Ellipse::new(*color).draw(*rect, &draw_state, transform, gl),
+                    Shape::EllipseBorder { color, radius, rect } => {
+                        Ellipse::new_border(*color, *radius).draw(*rect, &draw_state, transform, gl)
+                    },
+                    Shape::Line { color, radius, ends } => Line::new(*color, *radius).draw(*ends, &draw_state, transform, gl),
+                    Shape::Polygon { color, points } => Polygon::new(*color).draw(points, &draw_state, transform, gl),
+                    Shape::Text { text, color, x, y, scale } => text::draw(text, *color, *x, *y, *scale, transform, gl),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graphics::math::{abs_transform, identity};
+    use graphics::Viewport;
+
+    use super::{Backend, DrawList, Shape};
+    use crate::clock::Clock;
+    use crate::hud::{Hud, HudData, WidgetConfig};
+    use crate::outcomes::Outcomes;
+    use crate::simulation::quota::Quotas;
+    use crate::simulation::{test_params, Origin, Simulation};
+    use crate::svg::Svg;
+    use crate::thumbnail;
+
+    // A planet with one satellite launched round it, stepped on far enough to leave a trail
+    fn orbiting() -> Simulation {
+        let mut sim = Simulation::new(test_params(1), 1);
+        let planet = &sim.planets[0];
+        let (x, y) = (planet.x + 100.0, planet.y);
+        sim.launch(x, y, 0.0, 200.0, Origin::Script).unwrap();
+        for _ in 0..10 {
+            sim.step(1.0 / 120.0);
+        }
+        sim
+    }
+
+    #[test]
+    fn picture_draws_trails_then_planets_then_satellites() {
+        let sim = orbiting();
+        let view = abs_transform(800.0, 800.0);
+        let list = thumbnail::picture(&sim, 1.0, view);
+        assert!(matches!(list.items[0].0, Shape::Clear(_)));
+        let lines = list.items[1..].iter().take_while(|(shape, _)| matches!(shape, Shape::Line { .. })).count();
+        assert_eq!(lines, sim.satellites[0].trail.len() - 1);
+        assert!(lines > 0);
+        let ellipses: Vec<[f64; 4]> = list.items.iter()
+            .filter_map(|(shape, _)| match shape {
+                Shape::Ellipse { rect, .. } => Some(*rect),
+                _ => None,
+            })
+            .collect();
+        let (planet, sat) = (&sim.planets[0], &sim.satellites[0]);
+        assert_eq!(ellipses, vec![
+            [planet.x - planet.radius, planet.y - planet.radius, 2.0 * planet.radius, 2.0 * planet.radius],
+            [sat.x - sat.radius, sat.y - sat.radius, 2.0 * sat.radius, 2.0 * sat.radius],
+        ]);
+        assert!(list.items[1..].iter().all(|(_, transform)| *transform == view));
+    }
+
+    #[test]
+    fn hud_widgets_draw_text_where_placed() {
+        let sim = orbiting();
+        let outcomes = Outcomes::new(sim.planets.len());
+        let quotas = Quotas::default();
+        let clock = Clock::new(None).unwrap();
+        let data = HudData {
+            fps: 60,
+            time: sim.time,
+            satellites: &sim.satellites,
+            planets: &sim.planets,
+            gravity_constant: sim.params.gravity_constant,
+            events: &[],
+            outcomes: &outcomes,
+            bodies: (2, 0),
+            step_time: 0.0,
+            trails: (0, 1),
+            bound: sim.bound_counts(),
+            quotas: &quotas,
+            clock: &clock,
+        };
+        let configs: Vec<WidgetConfig> = serde_yaml::from_str("[{widget: fps, x: 10, y: 20}, {widget: counts, x: 10, y: 40}]").unwrap();
+        let mut hud = Hud::new(&configs);
+        let transform = identity();
+        let mut list = DrawList::default();
+        hud.draw(&data, transform, &mut list);
+        let texts: Vec<(&str, f64, f64)> = list.items.iter()
+            .filter_map(|(shape, _)| match shape {
+                Shape::Text { text, x, y, .. } => Some((text.as_str(), *x, *y)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts[0], ("60 fps", 10.0, 20.0));
+        assert_eq!(texts[1].0, "1 satellites (1 bound)");
+        assert_eq!(texts[2].0, "1 planets");
+        assert_eq!(texts.len(), 4);
+
+        hud.visible = false;
+        let mut hidden = DrawList::default();
+        hud.draw(&data, transform, &mut hidden);
+        assert!(hidden.items.is_empty());
+    }
+
+    #[test]
+    fn svg_writes_an_element_for_each_shape() {
+        let mut list = DrawList::default();
+        list.clear([0.0, 0.0, 0.0, 1.0]);
+        list.ellipse([1.0, 0.0, 0.0, 1.0], [10.0, 20.0, 30.0, 30.0], abs_transform(100.0, 100.0));
+        list.line([0.0, 1.0, 0.0, 1.0], 1.0, [0.0, 0.0, 50.0, 50.0], abs_transform(100.0, 100.0));
+        let mut svg = Svg::default();
+        svg.render(&list, Viewport {
+            rect: [0, 0, 100, 100],
+            draw_size: [100, 100],
+            window_size: [100.0, 100.0],
+        });
+        let doc = &svg.document;
+        assert!(doc.starts_with("<svg"));
+        assert!(doc.contains("<rect width=\"100\" height=\"100\" fill=\"rgb(0,0,0)\""));
+        assert!(doc.contains("<ellipse cx=\"25.00\" cy=\"35.00\" rx=\"15.00\" ry=\"15.00\" transform=\"matrix(1.000000 -0.000000 0.000000 1.000000 0.000 0.000)\" fill=\"rgb(255,0,0)\""));
+        assert!(doc.contains("<line x1=\"0.00\" y1=\"0.00\" x2=\"50.00\" y2=\"50.00\""));
+        assert!(doc.trim_end().ends_with("</svg>"));
+    }
+}
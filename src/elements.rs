@@ -0,0 +1,94 @@
+use crate::simulation::{Planet, Satellite};
+
+// Osculating two-body orbit of a satellite around a single planet
+#[derive(Clone, Copy)]
+pub struct Elements {
+    pub planet: usize,         // Index of the planet orbited
+    pub semi_major_axis: f64,  // In px
+    pub eccentricity: f64,     // 0 for a circle, approaching 1 as the ellipse stretches out
+    pub period: f64,           // In seconds
+}
+
+// Two-body orbital energy (per unit mass) of a body relative to a planet, negative if bound to it
+fn energy(x: f64, y: f64, v_x: f64, v_y: f64, planet: &Planet, gravity_constant: f64) -> f64 {
+    let r = ((x - planet.x).powi(2) + (y - planet.y).powi(2)).sqrt();
+    let (v_x, v_y) = (v_x - planet.v_x, v_y - planet.v_y);
+    0.5 * (v_x * v_x + v_y * v_y) - gravity_constant * planet.mass / r
+}
+
+// Number of live satellites bound to each planet, taking each planet on its own, so a satellite
+// deep in a binary's well can count as bound to both
+pub fn bound_counts(satellites: &[Satellite], planets: &[Planet], gravity_constant: f64) -> Vec<usize> {
+    planets.iter()
+        .map(|planet| {
+            satellites.iter()
+                .filter(|sat| sat.fate.is_none() && energy(sat.x, sat.y, sat.v_x, sat.v_y, planet, gravity_constant) < 0.0)
+                .count()
+        })
+        .collect()
+}
+
+// Finds the bound orbit of a body around the planet pulling on it hardest, if it has one, moving
+// along with the planet
+pub fn osculating(x: f64, y: f64, v_x: f64, v_y: f64, planets: &[Planet], gravity_constant: f64) -> Option<Elements> {
+    let (planet, dominant) = planets.iter().enumerate().max_by(|(_, a), (_, b)| {
+        let pull = |p: &Planet| p.mass / ((x - p.x).powi(2) + (y - p.y).powi(2));
+        pull(a).partial_cmp(&pull(b)).unwrap()
+    })?;
+
+    let mu = gravity_constant * dominant.mass;
+    let energy = energy(x, y, v_x, v_y, dominant, gravity_constant);
+    if energy >= 0.0 {
+        return None;
+    }
+    let semi_major_axis = -mu / (2.0 * energy);
+    let period = 2.0 * std::f64::consts::PI * (semi_major_axis.powi(3) / mu).sqrt();
+
+    // From the angular momentum h (per unit mass) about the planet, e^2 = 1 + 2 E h^2 / mu^2
+    let h = (x - dominant.x) * (v_y - dominant.v_y) - (y - dominant.y) * (v_x - dominant.v_x);
+    let eccentricity = (1.0 + 2.0 * energy * h * h / (mu * mu)).max(0.0).sqrt();
+    Some(Elements {
+        planet,
+        semi_major_axis,
+        eccentricity,
+        period,
+    })
+}
+
+// Smoothed orbital elements of a satellite, tracked for as long as it stays bound to the same planet
+#[derive(Clone, Copy)]
+pub struct OrbitTrack {
+    pub elements: Elements,      // Exponentially smoothed elements
+    pub since: f64,              // Simulation time the satellite became bound to this planet
+    pub previous: Option<usize>, // Different planet the satellite was last bound to, if any
+}
+
+// Time constant (in seconds) used to smooth out the wobble of osculating elements
+const SMOOTHING: f64 = 2.0;
+
+impl OrbitTrack {
+    // Continues a track with this tick's elements, starting a new one if the satellite is now bound elsewhere.
+    // `last` is the planet the satellite was most recently bound to.
+    pub fn update(track: Option<OrbitTrack>, elements: Option<Elements>, last: Option<usize>, time: f64, dt: f64) -> Option<OrbitTrack> {
+        let elements = elements?;
+        match track {
+            Some(mut track) if track.elements.planet == elements.planet => {
+                let alpha = (dt / SMOOTHING).min(1.0);
+                track.elements.semi_major_axis += alpha * (elements.semi_major_axis - track.elements.semi_major_axis);
+                track.elements.eccentricity += alpha * (elements.eccentricity - track.elements.eccentricity);
+                track.elements.period += alpha * (elements.period - track.elements.period);
+                Some(track)
+            },
+            _ => Some(OrbitTrack {
+                elements,
+                since: time,
+                previous: last.filter(|&planet| planet != elements.planet),
+            }),
+        }
+    }
+
+    // Number of (smoothed) orbits completed since becoming bound
+    pub fn orbits(&self, time: f64) -> f64 {
+        (time - self.since) / self.elements.period
+    }
+}
@@ -0,0 +1,60 @@
+use std::fmt;
+
+// Distance (in px) from a planet's surface within which a surviving pass counts as a near miss
+pub const NEAR_MISS_DISTANCE: f64 = 10.0;
+
+// Something interesting that happened in the simulation
+#[derive(Clone, Copy)]
+pub enum Event {
+    // A satellite skimmed a planet's surface and survived
+    NearMiss { satellite: u64, planet: usize, x: f64, y: f64 },
+    // A satellite crashed into a planet and merged with it
+    Merge { satellite: u64, planet: usize, x: f64, y: f64 },
+    // A satellite bound to one planet was captured by another and completed an orbit around it
+    Capture { satellite: u64, from: usize, planet: usize, x: f64, y: f64 },
+    // Two satellites settled into a resonance with each other
+    Resonance { a: u64, b: u64, p: u32, q: u32, x: f64, y: f64 },
+    // A planet grew too large and broke up into a burst of satellites
+    Breakup { planet: usize, x: f64, y: f64 },
+    // A satellite strayed inside a planet's Roche limit and was torn apart
+    Disruption { satellite: u64, planet: usize, x: f64, y: f64 },
+}
+
+impl Event {
+    // Short name used in file names
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::NearMiss { .. } => "near_miss",
+            Event::Merge { .. } => "merge",
+            Event::Capture { .. } => "capture",
+            Event::Resonance { .. } => "resonance",
+            Event::Breakup { .. } => "breakup",
+            Event::Disruption { .. } => "disruption",
+        }
+    }
+
+    // Where the event happened
+    pub fn position(&self) -> (f64, f64) {
+        match *self {
+            Event::NearMiss { x, y, .. } => (x, y),
+            Event::Merge { x, y, .. } => (x, y),
+            Event::Capture { x, y, .. } => (x, y),
+            Event::Resonance { x, y, .. } => (x, y),
+            Event::Breakup { x, y, .. } => (x, y),
+            Event::Disruption { x, y, .. } => (x, y),
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::NearMiss { satellite, planet, .. } => write!(f, "satellite {} skimmed planet {}", satellite, planet),
+            Event::Merge { satellite, planet, .. } => write!(f, "satellite {} merged with planet {}", satellite, planet),
+            Event::Capture { satellite, from, planet, .. } => write!(f, "satellite {} captured from planet {} by planet {}", satellite, from, planet),
+            Event::Resonance { a, b, p, q, .. } => write!(f, "satellites {} and {} entered {}:{} resonance", a, b, p, q),
+            Event::Breakup { planet, .. } => write!(f, "planet {} broke up", planet),
+            Event::Disruption { satellite, planet, .. } => write!(f, "satellite {} torn apart by planet {}", satellite, planet),
+        }
+    }
+}
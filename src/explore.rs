@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::simulation::{Params, Simulation};
+use crate::thumbnail;
+use crate::tuning::Tuning;
+
+// Steps between looks at the satellites while scoring a run
+const SAMPLE_EVERY: u64 = 10;
+
+// Cells along each side of the grid trail coverage is measured on
+const COVERAGE_CELLS: usize = 32;
+
+// Kinds of event a simulation raises on its own, without the app's analysis passes
+const EVENT_KINDS: [&str; 3] = ["near_miss", "capture", "breakup"];
+
+// Events it takes for a run to get most of the credit for their variety, so that one or two lucky
+// events do not count for much
+const EVENTS_EXPECTED: f64 = 10.0;
+
+// How interesting a short run with one set of tuning turned out, each part from 0 to 1
+pub struct Score {
+    pub capture: f64,   // Share of satellites that were bound to a planet at some point
+    pub coverage: f64,  // Share of the world their trails passed through
+    pub diversity: f64, // How evenly events were spread over the different kinds, and how many there were
+}
+
+impl Score {
+    pub fn total(&self) -> f64 {
+        (self.capture + self.coverage + self.diversity) / 3.0
+    }
+}
+
+// Runs the simulation without a window for the given number of steps and scores how it went
+pub fn score(sim: &mut Simulation, steps: u64, dt: f64) -> Score {
+    let (width, height) = (sim.params.width, sim.params.height);
+    let mut seen: HashSet<u64> = HashSet::new();
+    let mut bound: HashSet<u64> = HashSet::new();
+    let mut visited = vec![false; COVERAGE_CELLS * COVERAGE_CELLS];
+    let mut events = [0usize; EVENT_KINDS.len()];
+
+    for _ in 0..steps {
+        sim.step(dt);
+        for event in sim.events.iter() {
+            if let Some(kind) = EVENT_KINDS.iter().position(|name| *name == event.name()) {
+                events[kind] += 1;
+            }
+        }
+        if !sim.steps.is_multiple_of(SAMPLE_EVERY) {
+            continue;
+        }
+        for sat in sim.satellites.iter().filter(|sat| sat.fate.is_none()) {
+            seen.insert(sat.id);
+            if sat.track.is_some() {
+                bound.insert(sat.id);
+            }
+            if sat.x >= 0.0 && sat.x < width && sat.y >= 0.0 && sat.y < height {
+                let column = (sat.x / width * COVERAGE_CELLS as f64) as usize;
+                let row = (sat.y / height * COVERAGE_CELLS as f64) as usize;
+                visited[row * COVERAGE_CELLS + column] = true;
+            }
+        }
+    }
+
+    // Entropy of the kinds of event, against the most there could be, scaled down for few events
+    let total: usize = events.iter().sum();
+    let entropy: f64 = events.iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / total as f64;
+            -p * p.ln()
+        })
+        .sum::<f64>()
+        .max(0.0);
+    let diversity = entropy / (EVENT_KINDS.len() as f64).ln() * (1.0 - (-(total as f64) / EVENTS_EXPECTED).exp());
+
+    Score {
+        capture: bound.len() as f64 / seen.len().max(1) as f64,
+        coverage: visited.iter().filter(|cell| **cell).count() as f64 / visited.len() as f64,
+        diversity,
+    }
+}
+
+// Tries `samples` lucky dips, each from its own seed, and writes the best `top` of them to `dir` as
+// scene files, best first, each with a thumbnail of how its run ended. `params` gives the parameters each dip's tuning is applied on top of.
+pub fn run(params: impl Fn() -> Params, seed: u64, samples: usize, steps: u64, dt: f64, top: usize, dir: &str) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut results: Vec<(f64, u64, Tuning, Simulation)> = Vec::new();
+    for i in 0..samples {
+        let tuning = Tuning::lucky(&mut rng);
+        let sim_seed: u64 = rng.gen();
+        let mut params = params();
+        tuning.apply(&mut params);
+        let mut sim = Simulation::new(params, sim_seed);
+        let score = score(&mut sim, steps, dt);
+        println!(
+            "{}/{}: score {:.3} (capture {:.2}, coverage {:.2}, events {:.2})",
+            i + 1, samples, score.total(), score.capture, score.coverage, score.diversity,
+        );
+        results.push((score.total(), sim_seed, tuning, sim));
+        // Only the best few runs are kept, to save holding every finished simulation
+        results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        results.truncate(top);
+    }
+
+    fs::create_dir_all(dir).expect("Could not create explore output directory");
+    for (rank, (score, sim_seed, tuning, sim)) in results.iter().enumerate() {
+        let path = Path::new(dir).join(format!("explore_{:02}.yml", rank + 1));
+        let contents = format!(
+            "# Scored {:.3} over {} steps. Run with `orbits --seed {} --scene {}`.\n{}",
+            score, steps, sim_seed, path.display(), tuning.scene(),
+        );
+        fs::write(&path, contents).expect("Could not write explored scene");
+        thumbnail::save(sim, &path.with_extension("png")).expect("Could not write explored scene thumbnail");
+        println!("{} scored {:.3}", path.display(), score);
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::clip::{Frame, SatelliteSample};
+use crate::clock::Clock;
+use crate::simulation::{Fate, Planet, Satellite};
+
+// A planet where it started, and where it went after that if it moved
+#[derive(Serialize)]
+struct PlanetRecord {
+    color: [f32; 4],
+    mass: f64,
+    radius: f64,
+    x: f64,
+    y: f64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    points: Vec<(f64, f64)>,    // Position after every step, in trail exports
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    keys: Vec<(f64, f64, f64)>, // (time, x, y) samples, in keyframe exports and clips
+}
+
+// Orbit a satellite was last tracked in, smoothed over the last few seconds
+#[derive(Serialize)]
+struct OrbitRecord {
+    planet: usize,
+    semi_major_axis: f64, // In px
+    eccentricity: f64,
+    period: f64,          // In seconds
+}
+
+// Positions written with their height out of the plane only when satellites move in three dimensions,
+// so that flat runs export just as they always have
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Points {
+    Flat(Vec<(f64, f64)>),        // (x, y)
+    Raised(Vec<(f64, f64, f64)>), // (x, y, z)
+}
+
+impl Points {
+    fn new(path: &[(f64, f64, f64)], three_d: bool) -> Points {
+        match three_d {
+            true => Points::Raised(path.to_vec()),
+            false => Points::Flat(path.iter().map(|&(x, y, _)| (x, y)).collect()),
+        }
+    }
+}
+
+// Time-stamped samples, likewise with heights only in three dimensions
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Keys {
+    Flat(Vec<(f64, f64, f64)>),        // (time, x, y)
+    Raised(Vec<(f64, f64, f64, f64)>), // (time, x, y, z)
+}
+
+impl Keys {
+    fn new(keys: &[(f64, f64, f64, f64)], three_d: bool) -> Keys {
+        match three_d {
+            true => Keys::Raised(keys.to_vec()),
+            false => Keys::Flat(keys.iter().map(|&(t, x, y, _)| (t, x, y)).collect()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TrailRecord {
+    id: u64,
+    color: [f32; 4],
+    born: f64,             // Simulation time the satellite was spawned
+    died: Option<f64>,     // Simulation time the satellite died, if it did
+    lifetime: f64,         // Seconds the satellite was alive for
+    fate: &'static str,    // One of "crashed", "escaped", "lost", "expired", "walled", "absorbed" or "alive"
+    planet: Option<usize>, // Index of the planet crashed into
+    orbit: Option<OrbitRecord>, // Orbit the satellite was last in, if it was ever bound to a planet
+    points: Points,
+}
+
+#[derive(Serialize)]
+struct KeyframeRecord {
+    id: u64,
+    color: [f32; 4],
+    radius: f64,
+    born: f64,
+    died: Option<f64>,
+    keys: Keys,
+}
+
+// Writes {"width": .., "height": .., <extra header fields>, "satellites": [..], "planets": [..]}
+// with the satellites streamed one at a time, so large runs never need to be held in memory. The
+// planets come last, so that they can carry where they went over the whole run.
+struct SatelliteStream {
+    writer: BufWriter<File>,
+    first: bool,                // Whether no satellite has been written yet
+    planets: Vec<PlanetRecord>, // Planets as they started, with their motion added as it happens
+}
+
+impl SatelliteStream {
+    fn create<P: AsRef<Path>>(path: P, width: f64, height: f64, planets: &[Planet], extra: &str) -> io::Result<SatelliteStream> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let planets: Vec<PlanetRecord> = planets.iter().map(|planet| PlanetRecord {
+            color: planet.color,
+            mass: planet.mass,
+            radius: planet.radius,
+            x: planet.x,
+            y: planet.y,
+            points: Vec::new(),
+            keys: Vec::new(),
+        }).collect();
+        write!(writer, "{{\"width\":{},\"height\":{}{},\"satellites\":[", width, height, extra)?;
+        Ok(SatelliteStream {
+            writer,
+            first: true,
+            planets,
+        })
+    }
+
+    fn write<T: Serialize>(&mut self, record: &T) -> io::Result<()> {
+        if !self.first {
+            write!(self.writer, ",")?;
+        }
+        self.first = false;
+        serde_json::to_writer(&mut self.writer, record)?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        write!(self.writer, "],\"planets\":")?;
+        serde_json::to_writer(&mut self.writer, &self.planets)?;
+        write!(self.writer, "}}")?;
+        self.writer.flush()
+    }
+}
+
+// Streams the full trail of every satellite to a JSON file as a polyline, written as each satellite dies.
+pub struct TrailExporter {
+    stream: SatelliteStream,
+    three_d: bool, // Whether satellites move out of the plane, so their heights are written too
+}
+
+impl TrailExporter {
+    pub fn create(path: &str, width: f64, height: f64, planets: &[Planet], three_d: bool, clock: &Clock) -> io::Result<TrailExporter> {
+        Ok(TrailExporter {
+            stream: SatelliteStream::create(path, width, height, planets, &clock.header())?,
+            three_d,
+        })
+    }
+
+    // Adds where the planets are after a step to their paths, if they have moved
+    pub fn record_planets(&mut self, planets: &[Planet]) {
+        for (record, planet) in self.stream.planets.iter_mut().zip(planets) {
+            // Planets held still would otherwise repeat the same point over and over
+            if record.points.last() != Some(&(planet.x, planet.y)) {
+                record.points.push((planet.x, planet.y));
+            }
+        }
+    }
+
+    // Writes a single satellite's trail; satellites without a fate are recorded as still alive at `time`
+    pub fn write(&mut self, sat: &Satellite, time: f64) -> io::Result<()> {
+        let (fate, planet) = match sat.fate {
+            Some(Fate::Crashed(i)) => ("crashed", Some(i)),
+            Some(Fate::Escaped) => ("escaped", None),
+            Some(Fate::Lost) => ("lost", None),
+            Some(Fate::Expired) => ("expired", None),
+            Some(Fate::Walled) => ("walled", None),
+            Some(Fate::Absorbed) => ("absorbed", None),
+            None => ("alive", None),
+        };
+        self.stream.write(&TrailRecord {
+            id: sat.id,
+            color: sat.color,
+            born: sat.born,
+            died: sat.fate.map(|_| time),
+            lifetime: time - sat.born,
+            fate,
+            planet,
+            orbit: sat.track.map(|track| OrbitRecord {
+                planet: track.elements.planet,
+                semi_major_axis: track.elements.semi_major_axis,
+                eccentricity: track.elements.eccentricity,
+                period: track.elements.period,
+            }),
+            points: Points::new(&sat.path, self.three_d),
+        })
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        self.stream.finish()
+    }
+}
+
+// Streams time-stamped position samples of every satellite to a JSON file, for re-rendering runs in
+// other tools (see scripts/blender_import.py). Samples are taken at a fixed rate of simulation time.
+pub struct KeyframeExporter {
+    stream: SatelliteStream,
+    pub rate: f64,          // Samples per second of simulation time
+    pub next_sample: f64,   // Simulation time the next sample is due
+    three_d: bool,          // Whether satellites move out of the plane, so their heights are written too
+}
+
+impl KeyframeExporter {
+    pub fn create(path: &str, width: f64, height: f64, planets: &[Planet], rate: f64, three_d: bool, clock: &Clock) -> io::Result<KeyframeExporter> {
+        Ok(KeyframeExporter {
+            stream: SatelliteStream::create(path, width, height, planets, &format!(",\"rate\":{}{}", rate, clock.header()))?,
+            rate,
+            next_sample: 0.0,
+            three_d,
+        })
+    }
+
+    // Adds where the planets are at `time` to their samples
+    pub fn sample_planets(&mut self, planets: &[Planet], time: f64) {
+        for (record, planet) in self.stream.planets.iter_mut().zip(planets) {
+            record.keys.push((time, planet.x, planet.y));
+        }
+    }
+
+    // Writes a single satellite's samples; satellites without a fate are recorded as still alive at exit
+    pub fn write(&mut self, sat: &Satellite, time: f64) -> io::Result<()> {
+        self.stream.write(&KeyframeRecord {
+            id: sat.id,
+            color: sat.color,
+            radius: sat.radius,
+            born: sat.born,
+            died: sat.fate.map(|_| time),
+            keys: Keys::new(&sat.keys, self.three_d),
+        })
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        self.stream.finish()
+    }
+}
+
+// Writes a clip of frames in the keyframe export format, with every satellite seen in the clip
+// recorded from the first to the last frame it appears in
+pub fn write_clip(path: &Path, width: f64, height: f64, planets: &[Planet], rate: f64, three_d: bool, frames: &[Frame]) -> io::Result<()> {
+    let mut stream = SatelliteStream::create(path, width, height, planets, &format!(",\"rate\":{}", rate))?;
+    let end = frames.last().map_or(0.0, |frame| frame.time);
+
+    type Samples = Vec<(f64, f64, f64, f64)>;
+    let mut satellites: BTreeMap<u64, (&SatelliteSample, Samples)> = BTreeMap::new();
+    for frame in frames {
+        for sample in frame.satellites.iter() {
+            satellites.entry(sample.id).or_insert_with(|| (sample, Vec::new())).1.push((frame.time, sample.x, sample.y, sample.z));
+        }
+    }
+    for (i, record) in stream.planets.iter_mut().enumerate() {
+        record.keys = frames.iter().filter_map(|frame| frame.planets.get(i).map(|&(x, y)| (frame.time, x, y))).collect();
+        if let Some(&(_, x, y)) = record.keys.first() {
+            record.x = x;
+            record.y = y;
+        }
+    }
+    for (id, (sample, keys)) in satellites.iter() {
+        let last = keys.last().unwrap().0;
+        stream.write(&KeyframeRecord {
+            id: *id,
+            color: sample.color,
+            radius: sample.radius,
+            born: keys[0].0,
+            died: if last < end { Some(last) } else { None },
+            keys: Keys::new(keys, three_d),
+        })?;
+    }
+    stream.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyframeExporter;
+    use crate::clock::Clock;
+    use crate::simulation::{test_params, Origin, Simulation};
+
+    #[test]
+    fn keyframes_follow_moving_planets() {
+        let mut params = test_params(2);
+        params.planet_gravity = true;
+        let mut sim = Simulation::new(params, 3);
+        let (x, y) = (sim.planets[0].x + 60.0, sim.planets[0].y);
+        sim.launch(x, y, 0.0, 250.0, Origin::Script).unwrap();
+        let path = std::env::temp_dir().join(format!("orbits-keyframes-{}.json", std::process::id()));
+        let mut exporter = KeyframeExporter::create(path.to_str().unwrap(), 800.0, 800.0, &sim.planets, 10.0, false, &Clock::new(None).unwrap()).unwrap();
+        for _ in 0..3 {
+            sim.step(0.1);
+            exporter.sample_planets(&sim.planets, sim.time);
+            let sat = &mut sim.satellites[0];
+            sat.keys.push((sim.time, sat.x, sat.y, sat.z));
+        }
+        exporter.write(&sim.satellites[0], sim.time).unwrap();
+        exporter.finish().unwrap();
+
+        let run: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(run["satellites"][0]["keys"].as_array().unwrap().len(), 3);
+        assert_eq!(run["satellites"][0]["keys"][0].as_array().unwrap().len(), 3);
+        let planets = run["planets"].as_array().unwrap();
+        assert_eq!(planets.len(), 2);
+        for (record, planet) in planets.iter().zip(sim.planets.iter()) {
+            let keys = record["keys"].as_array().unwrap();
+            assert_eq!(keys.len(), 3);
+            assert_eq!(keys[2][1].as_f64().unwrap(), planet.x);
+            assert_eq!(keys[2][2].as_f64().unwrap(), planet.y);
+            assert_ne!(keys[0][1], record["x"]);
+        }
+    }
+    #[test]
+    fn keyframes_carry_heights_in_three_d() {
+        let mut sim = Simulation::new(test_params(1), 3);
+        let (x, y) = (sim.planets[0].x + 60.0, sim.planets[0].y);
+        sim.launch(x, y, 0.0, 250.0, Origin::Script).unwrap();
+        let path = std::env::temp_dir().join(format!("orbits-keyframes-3d-{}.json", std::process::id()));
+        let mut exporter = KeyframeExporter::create(path.to_str().unwrap(), 800.0, 800.0, &sim.planets, 10.0, true, &Clock::new(None).unwrap()).unwrap();
+        let sat = &mut sim.satellites[0];
+        sat.z = 12.5;
+        sat.keys.push((sim.time, sat.x, sat.y, sat.z));
+        exporter.write(&sim.satellites[0], sim.time).unwrap();
+        exporter.finish().unwrap();
+
+        let run: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let key = run["satellites"][0]["keys"][0].as_array().unwrap();
+        assert_eq!(key.len(), 4);
+        assert_eq!(key[3].as_f64().unwrap(), 12.5);
+    }
+}
@@ -0,0 +1,85 @@
+use graphics::math::Matrix2d;
+use graphics::Transformed;
+
+// How the world is fitted to a window of a different shape
+#[derive(Clone, Copy, PartialEq)]
+pub enum Aspect {
+    Extend,    // The world grows or shrinks to the window, showing more or less of it
+    Letterbox, // The world keeps its size and is scaled to fit inside the window, with bars either side
+    Crop,      // The world keeps its size and is scaled to fill the window, cutting off what overhangs
+}
+
+// Scaling and offset placing the world in a window
+#[derive(Clone, Copy)]
+pub struct Fit {
+    scale: f64,           // Window pixels per world pixel
+    x: f64,               // Where the world's top left corner sits in the window
+    y: f64,
+    shift: [f64; 2],      // How far the view is moved across the world from where it is fitted
+    world: [f64; 2],      // Size of the world
+    pub window: [f64; 2], // Size of the window
+}
+
+impl Fit {
+    // Fits a world of the given size to a window of the given size. When extending, the world is
+    // expected to be the size of the window already, so it is drawn as it is.
+    pub fn new(aspect: Aspect, world_width: f64, world_height: f64, width: f64, height: f64) -> Fit {
+        let scale = match aspect {
+            Aspect::Extend => 1.0,
+            Aspect::Letterbox => (width / world_width).min(height / world_height),
+            Aspect::Crop => (width / world_width).max(height / world_height),
+        };
+        let (x, y) = match aspect {
+            Aspect::Extend => (0.0, 0.0),
+            _ => ((width - world_width * scale) / 2.0, (height - world_height * scale) / 2.0),
+        };
+        Fit {
+            scale,
+            x,
+            y,
+            shift: [0.0, 0.0],
+            world: [world_width, world_height],
+            window: [width, height],
+        }
+    }
+
+    // The same fit with the view moved across the world by `shift`, so what was at the shift is drawn
+    // where the world's top left corner was. Bars stay at the edges of the window.
+    pub fn shifted(self, shift: [f64; 2]) -> Fit {
+        Fit { shift, ..self }
+    }
+
+    pub fn shift(&self) -> [f64; 2] {
+        self.shift
+    }
+
+    // Transform from the world to the window, starting from the window's own transform
+    pub fn transform(&self, base: Matrix2d) -> Matrix2d {
+        base.trans(self.x, self.y).zoom(self.scale).trans(-self.shift[0], -self.shift[1])
+    }
+
+    // Where a point in the window is in the world
+    pub fn to_world(self, [x, y]: [f64; 2]) -> [f64; 2] {
+        [(x - self.x) / self.scale + self.shift[0], (y - self.y) / self.scale + self.shift[1]]
+    }
+
+    // Where a point in the world is in the window
+    pub fn to_window(self, [x, y]: [f64; 2]) -> [f64; 2] {
+        [self.x + (x - self.shift[0]) * self.scale, self.y + (y - self.shift[1]) * self.scale]
+    }
+
+    // Rectangles of the window outside the world, to be blacked out, if any
+    pub fn bars(&self) -> Vec<[f64; 4]> {
+        let (width, height) = (self.world[0] * self.scale, self.world[1] * self.scale);
+        let mut bars = Vec::new();
+        if self.x > 0.0 {
+            bars.push([0.0, 0.0, self.x, self.window[1]]);
+            bars.push([self.x + width, 0.0, self.window[0] - self.x - width, self.window[1]]);
+        }
+        if self.y > 0.0 {
+            bars.push([0.0, 0.0, self.window[0], self.y]);
+            bars.push([0.0, self.y + height, self.window[0], self.window[1] - self.y - height]);
+        }
+        bars
+    }
+}
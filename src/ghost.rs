@@ -0,0 +1,67 @@
+use crate::simulation::Satellite;
+
+// Longest flight recorded for a ghost, in updates (a minute at 120 updates per second)
+const MAX_LENGTH: usize = 7200;
+
+// A launch being recorded as it flies, one position per update
+struct Recording {
+    id: u64,                // Satellite being recorded
+    score: f64,
+    path: Vec<(f64, f64)>,
+}
+
+// The best launch so far, replayed as a ghost alongside each new launch
+pub struct Ghost {
+    best: Option<(f64, Vec<(f64, f64)>)>, // Score and recorded path of the best launch
+    recording: Option<Recording>,         // Launch being recorded because it beat the best
+    replay: usize,                        // Updates since the latest launch, and so how far the ghost has got
+}
+
+impl Ghost {
+    pub fn new() -> Ghost {
+        Ghost {
+            best: None,
+            recording: None,
+            replay: 0,
+        }
+    }
+
+    // Starts the ghost over alongside a launch from (x, y), recording the launch if it scored better
+    // than the best so far
+    pub fn launched(&mut self, id: u64, x: f64, y: f64, score: Option<f64>) {
+        self.finish();
+        self.replay = 0;
+        if let Some(score) = score {
+            if self.best.as_ref().is_none_or(|(best, _)| score > *best) {
+                self.recording = Some(Recording { id, score, path: vec![(x, y)] });
+            }
+        }
+    }
+
+    // Moves the ghost on by an update and records where the launch being recorded has got to
+    pub fn update(&mut self, satellites: &[Satellite]) {
+        self.replay += 1;
+        if let Some(recording) = self.recording.as_mut() {
+            match satellites.iter().find(|sat| sat.id == recording.id && sat.fate.is_none()) {
+                Some(sat) if recording.path.len() < MAX_LENGTH => recording.path.push((sat.x, sat.y)),
+                _ => self.finish(),
+            }
+        }
+    }
+
+    // Makes the launch being recorded the new best
+    fn finish(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            self.best = Some((recording.score, recording.path));
+        }
+    }
+
+    // The ghost's recent path, ending where it is now, while it is still flying
+    pub fn trail(&self, length: usize) -> Option<&[(f64, f64)]> {
+        let (_, path) = self.best.as_ref()?;
+        if self.replay >= path.len() {
+            return None;
+        }
+        Some(&path[self.replay.saturating_sub(length)..=self.replay])
+    }
+}
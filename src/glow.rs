@@ -0,0 +1,26 @@
+use std::collections::HashMap;
+
+// Size (in px) of the cells trails are counted in to see how many overlap
+const CELL: f64 = 6.0;
+
+// How quickly overlapping trails brighten: a lone trail shows at 1 - e^-EXPOSURE of its full colour
+const EXPOSURE: f32 = 2.0;
+
+// Opacity to draw each trail segment at when trails are added on top of each other, so that where
+// they overlap they brighten like a long exposure without clipping to white. Segments are counted by
+// the cell their midpoint falls in, and the n crossing a cell share out 1 - e^(-EXPOSURE n) between
+// them, as in exponential tone mapping: one trail shows nearly at full colour, and however many pile
+// up they only ever add up to it.
+pub fn tone_map(segments: &[[f64; 4]]) -> Vec<f32> {
+    let cell = |ends: &[f64; 4]| (((ends[0] + ends[2]) / (2.0 * CELL)).floor() as i64, ((ends[1] + ends[3]) / (2.0 * CELL)).floor() as i64);
+    let mut counts: HashMap<(i64, i64), u32> = HashMap::new();
+    for ends in segments.iter() {
+        *counts.entry(cell(ends)).or_insert(0) += 1;
+    }
+    segments.iter()
+        .map(|ends| {
+            let n = counts[&cell(ends)] as f32;
+            (1.0 - (-EXPOSURE * n).exp()) / n
+        })
+        .collect()
+}
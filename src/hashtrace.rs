@@ -0,0 +1,56 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process;
+
+use crate::simulation::Simulation;
+
+// Reads a hash trace written by `run`, one "<step> <hash>" line per step
+fn read(path: &str) -> io::Result<Vec<u64>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut hashes = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let hash = line.split_whitespace().nth(1)
+            .and_then(|hash| u64::from_str_radix(hash, 16).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Bad hash trace line: {}", line)))?;
+        hashes.push(hash);
+    }
+    Ok(hashes)
+}
+
+// Runs the simulation for the given number of steps without a window, optionally writing the
+// state hash after every step to `trace` and checking it against the hashes in `compare`.
+// Exits with an error at the first step whose hash differs from the one expected.
+pub fn run(mut sim: Simulation, steps: u64, dt: f64, trace: Option<&str>, compare: Option<&str>) {
+    let mut writer = trace.map(|path| BufWriter::new(File::create(path).expect("Could not create hash trace")));
+    let expected = compare.map(|path| read(path).expect("Could not read hash trace"));
+
+    println!("seed {}", sim.seed);
+    for _ in 0..steps {
+        sim.step(dt);
+        for id in sim.removed.iter() {
+            eprintln!("Removed satellite {} at step {}, as its position or velocity was no longer a finite number", id, sim.steps);
+        }
+        let hash = sim.state_hash();
+        if let Some(writer) = writer.as_mut() {
+            writeln!(writer, "{} {:016x}", sim.steps, hash).expect("Could not write hash trace");
+        }
+        if let Some(expected) = expected.as_ref() {
+            match expected.get(sim.steps as usize - 1) {
+                Some(e) if *e == hash => {},
+                Some(e) => {
+                    eprintln!("Diverged at step {}: expected {:016x}, got {:016x}", sim.steps, e, hash);
+                    process::exit(1);
+                },
+                None => {
+                    eprintln!("Hash trace ends after {} steps", expected.len());
+                    process::exit(1);
+                },
+            }
+        }
+    }
+    if let Some(mut writer) = writer {
+        writer.flush().expect("Could not write hash trace");
+    }
+    println!("step {} hash {:016x}", sim.steps, sim.state_hash());
+}
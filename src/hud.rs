@@ -0,0 +1,414 @@
+use std::collections::VecDeque;
+
+use graphics::math::Matrix2d;
+use serde::Deserialize;
+
+use crate::clock::Clock;
+use crate::draw::DrawList;
+use crate::events::Event;
+use crate::outcomes::Outcomes;
+use crate::text;
+use crate::simulation::quota::Quotas;
+use crate::simulation::{Origin, Planet, Satellite};
+
+const TEXT: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+const FRAME: [f32; 4] = [1.0, 1.0, 1.0, 0.3];
+
+// Which widget to show
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum WidgetKind {
+    Fps,         // Frames per second
+    Counts,      // Number of bodies and elapsed time
+    Energy,      // Plot of the total energy of all satellites
+    Leaderboard, // Longest-lived satellites
+    Events,      // Feed of recent events
+    Outcomes,    // Bar chart of how satellites have fared
+    Profiler,    // Frames per second, bodies drawn and culled, step times and trail reuse
+    Origins,     // Satellites from each origin, against any quota
+    Clock,       // Simulation time, and the date for scenes with a calendar
+    Axes,        // Histogram of the semi-major axes of satellites' orbits
+}
+
+// A widget and where to put it, as given in the `hud` section of a scene file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WidgetConfig {
+    pub widget: WidgetKind,
+    pub x: f64,
+    pub y: f64,
+    #[serde(default = "default_width")]
+    pub width: f64,
+    #[serde(default = "default_height")]
+    pub height: f64,
+    #[serde(default = "default_scale")]
+    pub scale: f64, // Text scale, 1 being 8px characters
+}
+
+fn default_width() -> f64 { 200.0 }
+fn default_height() -> f64 { 80.0 }
+fn default_scale() -> f64 { 1.0 }
+
+// Everything a widget can show
+pub struct HudData<'a> {
+    pub fps: usize,
+    pub time: f64,
+    pub satellites: &'a [Satellite],
+    pub planets: &'a [Planet],
+    pub gravity_constant: f64,
+    pub events: &'a [Event], // Events from the last update
+    pub outcomes: &'a Outcomes,
+    pub bodies: (usize, usize), // Bodies drawn and culled as out of view in the last frame
+    pub step_time: f64,         // Seconds the simulation took to step in the last update
+    pub trails: (u64, u64),     // Trail buffers reused from the pool and allocated afresh so far
+    pub bound: (usize, usize),  // Live satellites bound to the planets, and those on their way out
+    pub quotas: &'a Quotas,
+    pub clock: &'a Clock,
+}
+
+// Something drawn over the simulation within a rectangle of the screen
+pub trait Widget {
+    // Called after every simulation update
+    fn update(&mut self, _data: &HudData) {}
+
+    fn draw(&self, data: &HudData, rect: [f64; 4], scale: f64, transform: Matrix2d, list: &mut DrawList);
+}
+
+fn line_height(scale: f64) -> f64 {
+    (text::CHAR_SIZE + 2.0) * scale
+}
+
+// Number of text rows that fit in a rectangle
+fn rows(rect: [f64; 4], scale: f64) -> usize {
+    (rect[3] / line_height(scale)).floor().max(1.0) as usize
+}
+
+struct Fps;
+
+impl Widget for Fps {
+    fn draw(&self, data: &HudData, rect: [f64; 4], scale: f64, transform: Matrix2d, list: &mut DrawList) {
+        list.text(&format!("{} fps", data.fps), TEXT, rect[0], rect[1], scale, transform);
+    }
+}
+
+// Updates the profiler times stepping over, about two seconds' worth
+const PROFILE_HISTORY: usize = 240;
+
+#[derive(Default)]
+struct Profiler {
+    step_times: VecDeque<f64>, // Seconds each recent update took to step the simulation, newest last
+}
+
+impl Widget for Profiler {
+    fn update(&mut self, data: &HudData) {
+        self.step_times.push_back(data.step_time);
+        while self.step_times.len() > PROFILE_HISTORY {
+            self.step_times.pop_front();
+        }
+    }
+
+    // The worst step time against the mean shows up jitter, such as from allocation churn
+    fn draw(&self, data: &HudData, rect: [f64; 4], scale: f64, transform: Matrix2d, list: &mut DrawList) {
+        let mean = self.step_times.iter().sum::<f64>() / self.step_times.len().max(1) as f64;
+        let worst = self.step_times.iter().cloned().fold(0.0, f64::max);
+        let (reused, allocated) = data.trails;
+        let lines = [
+            format!("{} fps", data.fps),
+            format!("{} bodies drawn", data.bodies.0),
+            format!("{} culled", data.bodies.1),
+            format!("step {:.2} ms, worst {:.2}", mean * 1000.0, worst * 1000.0),
+            format!("trails {:.0}% reused", reused as f64 * 100.0 / (reused + allocated).max(1) as f64),
+        ];
+        for (i, l) in lines.iter().enumerate() {
+            list.text(l, TEXT, rect[0], rect[1] + i as f64 * line_height(scale), scale, transform);
+        }
+    }
+}
+
+struct Origins;
+
+impl Widget for Origins {
+    fn draw(&self, data: &HudData, rect: [f64; 4], scale: f64, transform: Matrix2d, list: &mut DrawList) {
+        let mut alive = [0; Origin::NAMES.len()];
+        for sat in data.satellites.iter().filter(|sat| sat.fate.is_none()) {
+            alive[sat.origin.index()] += 1;
+        }
+        for (i, name) in Origin::NAMES.iter().enumerate().take(rows(rect, scale)) {
+            let alive = match data.quotas.limits[i] {
+                Some(limit) => format!("{}/{}", alive[i], limit),
+                None => alive[i].to_string(),
+            };
+            let mut row = format!("{:<8} {:>7} alive, {} in", name, alive, data.quotas.spawned[i]);
+            if data.quotas.refused[i] > 0 {
+                row.push_str(&format!(", {} refused", data.quotas.refused[i]));
+            }
+            list.text(&row, TEXT, rect[0], rect[1] + i as f64 * line_height(scale), scale, transform);
+        }
+    }
+}
+
+struct ClockDisplay;
+
+impl Widget for ClockDisplay {
+    fn draw(&self, data: &HudData, rect: [f64; 4], scale: f64, transform: Matrix2d, list: &mut DrawList) {
+        list.text(&format!("t = {:.1}s", data.time), TEXT, rect[0], rect[1], scale, transform);
+        if let Some(date) = data.clock.date(data.time) {
+            list.text(&date, TEXT, rect[0], rect[1] + line_height(scale), scale, transform);
+        }
+    }
+}
+
+struct Counts;
+
+impl Widget for Counts {
+    fn draw(&self, data: &HudData, rect: [f64; 4], scale: f64, transform: Matrix2d, list: &mut DrawList) {
+        let (bound, unbound) = data.bound;
+        let lines = [
+            format!("{} satellites ({} bound)", bound + unbound, bound),
+            format!("{} planets", data.planets.len()),
+            format!("t = {:.0}s", data.time),
+        ];
+        for (i, l) in lines.iter().enumerate() {
+            list.text(l, TEXT, rect[0], rect[1] + i as f64 * line_height(scale), scale, transform);
+        }
+    }
+}
+
+// Seconds between samples of the energy plot
+const ENERGY_INTERVAL: f64 = 0.25;
+
+// Keep the energy history bounded however wide the widget is
+const ENERGY_HISTORY: usize = 4096;
+
+#[derive(Default)]
+struct Energy {
+    history: VecDeque<f64>, // Total energy samples, oldest first
+    last_sample: f64,       // Simulation time of the newest sample
+}
+
+// Total kinetic plus potential energy (per unit mass) of all live satellites
+fn total_energy(data: &HudData) -> f64 {
+    data.satellites.iter().filter(|sat| sat.fate.is_none()).map(|sat| {
+        let potential: f64 = data.planets.iter().map(|planet| {
+            let distance = ((sat.x - planet.x).powi(2) + (sat.y - planet.y).powi(2)).sqrt();
+            -data.gravity_constant * planet.mass / distance
+        }).sum();
+        0.5 * (sat.v_x * sat.v_x + sat.v_y * sat.v_y) + potential
+    }).sum()
+}
+
+impl Widget for Energy {
+    fn update(&mut self, data: &HudData) {
+        if data.time - self.last_sample < ENERGY_INTERVAL && !self.history.is_empty() {
+            return;
+        }
+        self.last_sample = data.time;
+        self.history.push_back(total_energy(data));
+        if self.history.len() > ENERGY_HISTORY {
+            self.history.pop_front();
+        }
+    }
+
+    fn draw(&self, _data: &HudData, rect: [f64; 4], scale: f64, transform: Matrix2d, list: &mut DrawList) {
+        list.rectangle_border(FRAME, 0.5, rect, transform);
+        list.text("energy", TEXT, rect[0] + 2.0, rect[1] + 2.0, scale, transform);
+
+        // One sample per pixel of width
+        let shown = self.history.len().min(rect[2] as usize);
+        if shown < 2 {
+            return;
+        }
+        let samples = self.history.iter().skip(self.history.len() - shown);
+        let min = samples.clone().cloned().fold(f64::INFINITY, f64::min);
+        let max = samples.clone().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let range = if max > min { max - min } else { 1.0 };
+        let top = rect[1] + line_height(scale);
+        let height = rect[3] - line_height(scale);
+        let point = |i: usize, e: f64| (rect[0] + i as f64, top + height * (1.0 - (e - min) / range));
+        let mut previous: Option<(f64, f64)> = None;
+        for (i, e) in samples.enumerate() {
+            let p = point(i, *e);
+            if let Some(q) = previous {
+                list.line(TEXT, 0.5, [q.0, q.1, p.0, p.1], transform);
+            }
+            previous = Some(p);
+        }
+    }
+}
+
+// Bars in the semi-major axis histogram
+const AXIS_BINS: usize = 64;
+
+// Share of satellites left off each end of the semi-major axis histogram, so a few strays far out
+// don't squash the rest into a handful of bars
+const AXIS_TAIL: f64 = 0.01;
+
+// Step (in px) the ends of the semi-major axis histogram are rounded out to, so they hold still
+const AXIS_ROUNDING: f64 = 10.0;
+
+#[derive(Default)]
+struct AxisHistogram {
+    counts: Vec<usize>, // Satellites in each bar
+    range: (f64, f64),  // Semi-major axes (in px) at the left and right ends
+}
+
+impl Widget for AxisHistogram {
+    fn update(&mut self, data: &HudData) {
+        let mut axes: Vec<f64> = data.satellites.iter()
+            .filter(|sat| sat.fate.is_none())
+            .filter_map(|sat| sat.track.map(|track| track.elements.semi_major_axis))
+            .collect();
+        self.counts = vec![0; AXIS_BINS];
+        if axes.is_empty() {
+            return;
+        }
+        axes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let tail = (axes.len() as f64 * AXIS_TAIL) as usize;
+        let low = (axes[tail] / AXIS_ROUNDING).floor() * AXIS_ROUNDING;
+        let high = (axes[axes.len() - 1 - tail] / AXIS_ROUNDING).floor() * AXIS_ROUNDING + AXIS_ROUNDING;
+        self.range = (low, high);
+        for a in axes.iter().filter(|a| (low..high).contains(*a)) {
+            self.counts[((a - low) / (high - low) * AXIS_BINS as f64) as usize] += 1;
+        }
+    }
+
+    fn draw(&self, _data: &HudData, rect: [f64; 4], scale: f64, transform: Matrix2d, list: &mut DrawList) {
+        list.rectangle_border(FRAME, 0.5, rect, transform);
+        list.text("semi-major axis", TEXT, rect[0] + 2.0, rect[1] + 2.0, scale, transform);
+        let most = match self.counts.iter().max() {
+            Some(&most) if most > 0 => most,
+            _ => return,
+        };
+
+        // Bars fill the space between the title and the labels for each end
+        let top = rect[1] + line_height(scale);
+        let bottom = rect[1] + rect[3] - line_height(scale);
+        let width = rect[2] / self.counts.len() as f64;
+        for (i, count) in self.counts.iter().enumerate() {
+            let height = (bottom - top) * *count as f64 / most as f64;
+            list.rectangle(TEXT, [rect[0] + i as f64 * width, bottom - height, width, height], transform);
+        }
+        let high = format!("{:.0}px", self.range.1);
+        list.text(&format!("{:.0}px", self.range.0), TEXT, rect[0] + 2.0, bottom + 2.0, scale, transform);
+        list.text(&high, TEXT, rect[0] + rect[2] - text::width(&high, scale) - 2.0, bottom + 2.0, scale, transform);
+    }
+}
+
+struct Leaderboard;
+
+impl Widget for Leaderboard {
+    fn draw(&self, data: &HudData, rect: [f64; 4], scale: f64, transform: Matrix2d, list: &mut DrawList) {
+        let mut alive: Vec<&Satellite> = data.satellites.iter().filter(|sat| sat.fate.is_none()).collect();
+        alive.sort_by(|a, b| a.born.partial_cmp(&b.born).unwrap());
+        list.text("oldest", TEXT, rect[0], rect[1], scale, transform);
+        for (i, sat) in alive.iter().take(rows(rect, scale) - 1).enumerate() {
+            let y = rect[1] + (i + 1) as f64 * line_height(scale);
+            let size = text::CHAR_SIZE * scale;
+            list.rectangle(sat.color, [rect[0], y, size, size], transform);
+            let label = format!("#{} {:.0}s", sat.id, data.time - sat.born);
+            list.text(&label, TEXT, rect[0] + size * 1.5, y, scale, transform);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Events {
+    log: VecDeque<String>, // Most recent events, newest last
+}
+
+// Events remembered for the feed, however tall the widget is
+const EVENT_HISTORY: usize = 64;
+
+impl Widget for Events {
+    fn update(&mut self, data: &HudData) {
+        for event in data.events {
+            self.log.push_back(format!("{:.0}s {}", data.time, event));
+        }
+        while self.log.len() > EVENT_HISTORY {
+            self.log.pop_front();
+        }
+    }
+
+    fn draw(&self, _data: &HudData, rect: [f64; 4], scale: f64, transform: Matrix2d, list: &mut DrawList) {
+        let shown = self.log.len().min(rows(rect, scale));
+        for (i, entry) in self.log.iter().skip(self.log.len() - shown).enumerate() {
+            list.text(entry, TEXT, rect[0], rect[1] + i as f64 * line_height(scale), scale, transform);
+        }
+    }
+}
+
+struct OutcomeChart;
+
+impl Widget for OutcomeChart {
+    fn draw(&self, data: &HudData, rect: [f64; 4], scale: f64, transform: Matrix2d, list: &mut DrawList) {
+        let alive = data.satellites.iter().filter(|sat| sat.fate.is_none()).count();
+        let fractions = data.outcomes.fractions(alive);
+        let label_width = fractions.iter().map(|(label, _)| text::width(label, scale)).fold(0.0, f64::max) + 4.0 * scale;
+        let bar_width = (rect[2] - label_width - text::width("100%", scale) - 4.0 * scale).max(0.0);
+        let size = text::CHAR_SIZE * scale;
+        for (i, (label, fraction)) in fractions.iter().take(rows(rect, scale) - 1).enumerate() {
+            let y = rect[1] + i as f64 * line_height(scale);
+            list.text(label, TEXT, rect[0], y, scale, transform);
+            list.rectangle(FRAME, [rect[0] + label_width, y, bar_width, size], transform);
+            list.rectangle(TEXT, [rect[0] + label_width, y, bar_width * fraction, size], transform);
+            let percent = format!("{:.0}%", fraction * 100.0);
+            list.text(&percent, TEXT, rect[0] + label_width + bar_width + 4.0 * scale, y, scale, transform);
+        }
+        let lifetime = match data.outcomes.mean_lifetime() {
+            Some(lifetime) => format!("mean lifetime {:.1}s", lifetime),
+            None => "mean lifetime -".to_string(),
+        };
+        let y = rect[1] + fractions.len().min(rows(rect, scale) - 1) as f64 * line_height(scale);
+        list.text(&lifetime, TEXT, rect[0], y, scale, transform);
+    }
+}
+
+// Widgets overlaid on the simulation, laid out as configured
+pub struct Hud {
+    widgets: Vec<(Box<dyn Widget>, [f64; 4], f64)>, // Each widget with its rectangle and text scale
+    pub visible: bool,
+}
+
+impl Hud {
+    pub fn new(configs: &[WidgetConfig]) -> Hud {
+        let mut hud = Hud {
+            widgets: Vec::new(),
+            visible: true,
+        };
+        for config in configs {
+            hud.add(config);
+        }
+        hud
+    }
+
+    pub fn add(&mut self, config: &WidgetConfig) {
+        let widget: Box<dyn Widget> = match config.widget {
+            WidgetKind::Fps => Box::new(Fps),
+            WidgetKind::Counts => Box::new(Counts),
+            WidgetKind::Energy => Box::new(Energy::default()),
+            WidgetKind::Leaderboard => Box::new(Leaderboard),
+            WidgetKind::Events => Box::new(Events::default()),
+            WidgetKind::Outcomes => Box::new(OutcomeChart),
+            WidgetKind::Profiler => Box::new(Profiler::default()),
+            WidgetKind::Origins => Box::new(Origins),
+            WidgetKind::Clock => Box::new(ClockDisplay),
+            WidgetKind::Axes => Box::new(AxisHistogram::default()),
+        };
+        self.widgets.push((widget, [config.x, config.y, config.width, config.height], config.scale));
+    }
+
+    pub fn update(&mut self, data: &HudData) {
+        for (widget, _, _) in self.widgets.iter_mut() {
+            widget.update(data);
+        }
+    }
+
+    pub fn draw(&self, data: &HudData, transform: Matrix2d, list: &mut DrawList) {
+        if !self.visible {
+            return;
+        }
+        for (widget, rect, scale) in self.widgets.iter() {
+            widget.draw(data, *rect, *scale, transform, list);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, Write};
+
+use piston::input::{Button, Event, Key, MouseButton, MouseCursorEvent, PressEvent, ReleaseEvent, ResizeEvent, TextEvent};
+
+// First line of every input recording
+const HEADER: &str = "orbits input 1";
+
+// Reads back text written quoted and escaped by `{:?}`, or None if it is not
+fn unescape(quoted: &str) -> Option<String> {
+    let mut chars = quoted.strip_prefix('"')?.strip_suffix('"')?.chars();
+    let mut text = String::new();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        text.push(match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            'u' => {
+                let rest = chars.as_str().strip_prefix('{')?;
+                let (hex, after) = rest.split_once('}')?;
+                chars = after.chars();
+                char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+            },
+            c @ ('\\' | '"' | '\'') => c,
+            _ => return None,
+        });
+    }
+    Some(text)
+}
+
+// Input from the keyboard, mouse and window, in the form it is recorded and replayed
+#[derive(Clone, PartialEq)]
+pub enum Input {
+    KeyPress(Key),
+    KeyRelease(Key),
+    Text(String),
+    MouseMove([f64; 2]),
+    MousePress(MouseButton),
+    MouseRelease(MouseButton),
+    Resize([f64; 2]), // The main window's new size, which an extended world follows
+}
+
+impl Input {
+    // The input an event carries, if any
+    pub fn from_event(e: &Event) -> Option<Input> {
+        if let Some(button) = e.press_args() {
+            return match button {
+                Button::Keyboard(key) => Some(Input::KeyPress(key)),
+                Button::Mouse(button) => Some(Input::MousePress(button)),
+                _ => None,
+            };
+        }
+        if let Some(button) = e.release_args() {
+            return match button {
+                Button::Keyboard(key) => Some(Input::KeyRelease(key)),
+                Button::Mouse(button) => Some(Input::MouseRelease(button)),
+                _ => None,
+            };
+        }
+        if let Some(text) = e.text_args() {
+            return Some(Input::Text(text));
+        }
+        if let Some(args) = e.resize_args() {
+            return Some(Input::Resize(args.window_size));
+        }
+        e.mouse_cursor_args().map(Input::MouseMove)
+    }
+
+    // Written as a kind followed by its arguments, with text quoted and escaped so that newlines and
+    // other control characters typed into it stay on the one line
+    fn write(&self) -> String {
+        match self {
+            Input::KeyPress(key) => format!("key_press {}", u32::from(*key)),
+            Input::KeyRelease(key) => format!("key_release {}", u32::from(*key)),
+            Input::Text(text) => format!("text {:?}", text),
+            Input::MouseMove(pos) => format!("mouse_move {} {}", pos[0], pos[1]),
+            Input::MousePress(button) => format!("mouse_press {}", u32::from(*button)),
+            Input::MouseRelease(button) => format!("mouse_release {}", u32::from(*button)),
+            Input::Resize(size) => format!("resize {} {}", size[0], size[1]),
+        }
+    }
+
+    fn parse(s: &str) -> Option<Input> {
+        let (kind, rest) = s.split_once(' ')?;
+        let code = || rest.parse::<u32>().ok();
+        match kind {
+            "key_press" => Some(Input::KeyPress(Key::from(code()?))),
+            "key_release" => Some(Input::KeyRelease(Key::from(code()?))),
+            // Recordings from before text was escaped hold it raw
+            "text" => Some(Input::Text(unescape(rest).unwrap_or_else(|| rest.to_string()))),
+            "mouse_move" => {
+                let (x, y) = rest.split_once(' ')?;
+                Some(Input::MouseMove([x.parse().ok()?, y.parse().ok()?]))
+            },
+            "mouse_press" => Some(Input::MousePress(MouseButton::from(code()?))),
+            "mouse_release" => Some(Input::MouseRelease(MouseButton::from(code()?))),
+            "resize" => {
+                let (width, height) = rest.split_once(' ')?;
+                Some(Input::Resize([width.parse().ok()?, height.parse().ok()?]))
+            },
+            _ => None,
+        }
+    }
+}
+
+// Updates between chunks of a recording being saved: ten seconds at 120 updates per second
+const CHUNK_UPDATES: u64 = 1200;
+
+// FNV-1a hash of a chunk's lines, so a chunk torn by a crash part-way through writing it is noticed
+fn checksum(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+// The index kept alongside a recording
+fn index_path(path: &str) -> String {
+    format!("{}.index", path)
+}
+
+// Writes every input of a session to a file, one per line, each tagged with how many updates came
+// before it so it can be replayed at exactly the same point. The file is only ever appended to, a
+// chunk at a time: each chunk ends with a line giving its length, last update and checksum, and is
+// synced to disk before its end is added to the index, so a crash loses at most the chunk in progress.
+pub struct InputRecorder {
+    file: File,       // The recording
+    index: File,      // Byte offset and update at the end of each chunk saved so far
+    length: u64,      // Bytes saved to the recording so far
+    pending: String,  // Lines for the chunk in progress
+    lines: usize,     // Inputs in the chunk in progress
+    last_chunk: u64,  // Update the last chunk was saved at
+}
+
+impl InputRecorder {
+    pub fn create(path: &str, seed: u64) -> io::Result<InputRecorder> {
+        let mut file = File::create(path)?;
+        let header = format!("{}\nseed {}\n", HEADER, seed);
+        file.write_all(header.as_bytes())?;
+        file.sync_data()?;
+        Ok(InputRecorder {
+            file,
+            index: File::create(index_path(path))?,
+            length: header.len() as u64,
+            pending: String::new(),
+            lines: 0,
+            last_chunk: 0,
+        })
+    }
+
+    pub fn record(&mut self, update: u64, input: &Input) {
+        self.pending.push_str(&format!("{} {}\n", update, input.write()));
+        self.lines += 1;
+    }
+
+    // Called after every update, saving a chunk every so often
+    pub fn update(&mut self, update: u64) -> io::Result<()> {
+        if update - self.last_chunk >= CHUNK_UPDATES && self.lines > 0 {
+            self.save_chunk(update)?;
+        }
+        Ok(())
+    }
+
+    fn save_chunk(&mut self, update: u64) -> io::Result<()> {
+        let chunk = format!("{}chunk {} {} {:016x}\n", self.pending, self.lines, update, checksum(&self.pending));
+        self.file.write_all(chunk.as_bytes())?;
+        self.file.sync_data()?;
+        self.length += chunk.len() as u64;
+        self.index.write_all(format!("{} {}\n", self.length, update).as_bytes())?;
+        self.index.sync_data()?;
+        self.pending.clear();
+        self.lines = 0;
+        self.last_chunk = update;
+        Ok(())
+    }
+
+    pub fn finish(mut self, update: u64) -> io::Result<()> {
+        if self.lines > 0 {
+            self.save_chunk(update)?;
+        }
+        Ok(())
+    }
+}
+
+// What could be read back from a recording, up to the end of its last whole chunk
+struct Scan {
+    seed: u64,
+    inputs: VecDeque<(u64, Input)>,
+    chunks: Vec<(usize, u64)>, // Byte offset and update at the end of each whole chunk
+    length: usize,             // Bytes in the whole chunks, along with the header
+    torn: usize,               // Bytes after them, from a chunk that was never finished
+}
+
+// Reads a recording chunk by chunk, stopping at the first chunk that is incomplete or damaged
+fn scan(path: &str) -> Result<Scan, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Could not read input recording {}: {}", path, e))?;
+    let mut lines = contents.split_inclusive('\n');
+    if lines.next() != Some(&format!("{}\n", HEADER)) {
+        return Err(format!("Not an input recording: {}", path));
+    }
+    let seed_line = lines.next().unwrap_or("");
+    let seed = seed_line.trim_end()
+        .strip_prefix("seed ")
+        .and_then(|seed| seed.parse().ok())
+        .ok_or_else(|| format!("Input recording has no seed: {}", path))?;
+
+    let mut scan = Scan {
+        seed,
+        inputs: VecDeque::new(),
+        chunks: Vec::new(),
+        length: HEADER.len() + 1 + seed_line.len(),
+        torn: 0,
+    };
+    let (mut offset, mut start) = (scan.length, scan.length);
+    let mut chunk = Vec::new();
+    for line in lines {
+        offset += line.len();
+        if !line.ends_with('\n') {
+            break;
+        }
+        if let Some(trailer) = line.strip_prefix("chunk ") {
+            let fields: Vec<&str> = trailer.trim_end().split(' ').collect();
+            let whole = match fields[..] {
+                [count, update, sum] => {
+                    count.parse() == Ok(chunk.len())
+                        && u64::from_str_radix(sum, 16) == Ok(checksum(&contents[start..offset - line.len()]))
+                        && update.parse::<u64>().is_ok()
+                },
+                _ => false,
+            };
+            if !whole {
+                break;
+            }
+            scan.inputs.extend(chunk.drain(..));
+            scan.chunks.push((offset, fields[1].parse().unwrap()));
+            scan.length = offset;
+            start = offset;
+            continue;
+        }
+        match line.trim_end_matches('\n').split_once(' ').and_then(|(update, input)| Some((update.parse().ok()?, Input::parse(input)?))) {
+            Some(input) => chunk.push(input),
+            None => break,
+        }
+    }
+    scan.torn = contents.len() - scan.length;
+    Ok(scan)
+}
+
+// Cuts a recording back to its last whole chunk and rewrites its index to match, returning a summary
+// of what was kept
+pub fn repair(path: &str) -> Result<String, String> {
+    let scan = scan(path)?;
+    let file = fs::OpenOptions::new().write(true).open(path).map_err(|e| format!("Could not open input recording {}: {}", path, e))?;
+    file.set_len(scan.length as u64).map_err(|e| format!("Could not repair input recording {}: {}", path, e))?;
+    let index: String = scan.chunks.iter().map(|(end, update)| format!("{} {}\n", end, update)).collect();
+    fs::write(index_path(path), index).map_err(|e| format!("Could not write index for input recording {}: {}", path, e))?;
+    Ok(format!("kept {} inputs in {} chunks, cut {} bytes from an unfinished chunk", scan.inputs.len(), scan.chunks.len(), scan.torn))
+}
+
+// Input read back from a recording, handed out at the updates it first arrived after
+pub struct InputReplay {
+    pub seed: u64,                  // Seed the recorded session started from
+    inputs: VecDeque<(u64, Input)>, // Inputs still to come, in order, each with its update
+}
+
+impl InputReplay {
+    // Reads a recording as far as its last whole chunk, warning if anything after it was lost or its
+    // index does not match (as after a crash), which --repair_input tidies up
+    pub fn load(path: &str) -> Result<InputReplay, String> {
+        let scan = scan(path)?;
+        if scan.torn > 0 {
+            eprintln!("Input recording {} ends with {} bytes of an unfinished chunk, which will be skipped", path, scan.torn);
+        }
+        let indexed = fs::read_to_string(index_path(path)).unwrap_or_default().lines().count();
+        if indexed != scan.chunks.len() {
+            eprintln!("Input recording {} has {} chunks but its index lists {}", path, scan.chunks.len(), indexed);
+        }
+        Ok(InputReplay {
+            seed: scan.seed,
+            inputs: scan.inputs,
+        })
+    }
+
+    // Inputs that arrived after the given number of updates
+    pub fn due(&mut self, update: u64) -> Vec<Input> {
+        let mut due = Vec::new();
+        while self.inputs.front().is_some_and(|(at, _)| *at <= update) {
+            due.push(self.inputs.pop_front().unwrap().1);
+        }
+        due
+    }
+
+    pub fn finished(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{repair, Input, InputRecorder, InputReplay, CHUNK_UPDATES};
+    use piston::input::{Key, MouseButton};
+    use std::fs;
+
+    fn inputs() -> Vec<Input> {
+        vec![
+            Input::KeyPress(Key::A),
+            Input::KeyRelease(Key::LShift),
+            Input::Text("plain".to_string()),
+            Input::Text("two\nlines, \"quoted\" \\ tabbed\t\r\u{1b}[0m é ✓".to_string()),
+            Input::Text(String::new()),
+            Input::MouseMove([12.5, -3.0]),
+            Input::MousePress(MouseButton::Left),
+            Input::MouseRelease(MouseButton::Right),
+            Input::Resize([1024.0, 768.0]),
+        ]
+    }
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("orbits-{}-{}.txt", name, std::process::id())).to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn inputs_survive_being_written_and_read() {
+        for input in inputs() {
+            let line = input.write();
+            assert!(!line.contains('\n'), "{} spans more than one line", line);
+            assert!(Input::parse(&line) == Some(input), "{} did not read back", line);
+        }
+        assert!(Input::parse("text raw from an old recording") == Some(Input::Text("raw from an old recording".to_string())));
+    }
+
+    #[test]
+    fn repair_keeps_whole_chunks_of_a_torn_recording() {
+        let path = temp_path("input");
+        let mut recorder = InputRecorder::create(&path, 42).unwrap();
+        for (i, input) in inputs().iter().enumerate() {
+            recorder.record(i as u64, input);
+        }
+        recorder.update(CHUNK_UPDATES).unwrap();
+        recorder.record(CHUNK_UPDATES + 1, &Input::Text("lost\nin the crash".to_string()));
+        recorder.finish(CHUNK_UPDATES + 2).unwrap();
+
+        // Cut the file part-way through its second chunk, as a crash while writing it would
+        let whole = fs::metadata(&path).unwrap().len();
+        fs::OpenOptions::new().write(true).open(&path).unwrap().set_len(whole - 10).unwrap();
+        let summary = repair(&path).unwrap();
+        assert!(summary.starts_with(&format!("kept {} inputs in 1 chunks", inputs().len())), "{}", summary);
+        assert_eq!(fs::read_to_string(format!("{}.index", path)).unwrap().lines().count(), 1);
+
+        let mut replay = InputReplay::load(&path).unwrap();
+        assert_eq!(replay.seed, 42);
+        assert!(replay.due(u64::MAX) == inputs());
+        assert!(replay.finished());
+        fs::remove_file(format!("{}.index", path)).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+}
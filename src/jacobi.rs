@@ -0,0 +1,84 @@
+use crate::contour::Grid;
+use crate::simulation::{Planet, Satellite};
+
+// Distance (in px) between samples of the potential when drawing zero-velocity curves
+const GRID_SPACING: f64 = 8.0;
+
+// Frame turning with the planets of a circular binary, about their barycentre
+#[derive(Clone, Copy)]
+pub struct RotatingFrame {
+    pub x: f64,     // Barycentre
+    pub y: f64,
+    pub omega: f64, // Angular velocity in rad/s, positive turning from +x towards +y
+}
+
+// Effective potential (per unit mass) in the rotating frame: gravity plus the centrifugal term
+pub fn effective_potential(x: f64, y: f64, planets: &[Planet], gravity_constant: f64, frame: RotatingFrame) -> f64 {
+    let gravity: f64 = planets.iter().map(|planet| {
+        gravity_constant * planet.mass / ((x - planet.x).powi(2) + (y - planet.y).powi(2)).sqrt()
+    }).sum();
+    gravity + 0.5 * frame.omega * frame.omega * ((x - frame.x).powi(2) + (y - frame.y).powi(2))
+}
+
+// Jacobi constant of a satellite, the one quantity conserved in the restricted three-body problem.
+// Higher values mean less energy in the rotating frame, and so more of the plane out of reach.
+pub fn constant(sat: &Satellite, planets: &[Planet], gravity_constant: f64, frame: RotatingFrame) -> f64 {
+    let gravity: f64 = planets.iter().map(|planet| {
+        gravity_constant * planet.mass / ((sat.x - planet.x).powi(2) + (sat.y - planet.y).powi(2)).sqrt()
+    }).sum();
+    let angular_momentum = (sat.x - frame.x) * sat.v_y - (sat.y - frame.y) * sat.v_x;
+    2.0 * gravity - (sat.v_x * sat.v_x + sat.v_y * sat.v_y) + 2.0 * frame.omega * angular_momentum
+}
+
+// Twice the effective potential sampled over the window, so that points below a given Jacobi constant
+// are out of reach of satellites with that constant. `to_world` maps window points to the world, to
+// allow viewing from the rotating frame.
+pub fn potential_grid<F: Fn(f64, f64) -> (f64, f64)>(width: f64, height: f64, planets: &[Planet], gravity_constant: f64, frame: RotatingFrame, to_world: F) -> Grid {
+    Grid::sample(width, height, GRID_SPACING, |x, y| {
+        let (x, y) = to_world(x, y);
+        2.0 * effective_potential(x, y, planets, gravity_constant, frame)
+    })
+}
+
+// Jacobi constant at the L1 point between two planets, found as the lowest effective potential on
+// the line joining them. Satellites above this value are trapped around whichever planet they started at.
+pub fn l1(planets: &[Planet], gravity_constant: f64, frame: RotatingFrame) -> Option<f64> {
+    if planets.len() != 2 {
+        return None;
+    }
+    let (a, b) = (&planets[0], &planets[1]);
+    let potential = |t: f64| effective_potential(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t, planets, gravity_constant, frame);
+
+    // Ternary search, as the potential falls from one planet to L1 and rises again to the other
+    let (mut low, mut high) = (0.01, 0.99);
+    for _ in 0..100 {
+        let m1 = low + (high - low) / 3.0;
+        let m2 = high - (high - low) / 3.0;
+        if potential(m1) < potential(m2) {
+            high = m2;
+        } else {
+            low = m1;
+        }
+    }
+    Some(2.0 * potential((low + high) / 2.0))
+}
+
+// Colour for a Jacobi constant relative to the value at L1: red for satellites free to wander
+// anywhere, through yellow at the L1 value, to blue for those trapped around one planet
+pub fn color(constant: f64, l1: f64) -> [f32; 4] {
+    const FREE: [f32; 3] = [1.0, 0.2, 0.1];
+    const CRITICAL: [f32; 3] = [1.0, 0.9, 0.2];
+    const TRAPPED: [f32; 3] = [0.3, 0.6, 1.0];
+    let ratio = (constant / l1).clamp(-0.5, 1.5) as f32;
+    let (from, to, t) = if ratio < 1.0 {
+        (FREE, CRITICAL, (ratio + 0.5) / 1.5)
+    } else {
+        (CRITICAL, TRAPPED, (ratio - 1.0) * 2.0)
+    };
+    [
+        from[0] + (to[0] - from[0]) * t,
+        from[1] + (to[1] - from[1]) * t,
+        from[2] + (to[2] - from[2]) * t,
+        1.0,
+    ]
+}
@@ -0,0 +1,43 @@
+use crate::simulation::Planet;
+
+// Lagrange points L1 to L5 of a pair of planets, where a satellite co-rotating with them feels no net
+// force, or None unless there are exactly two. L1 to L3 lie on the line through the planets, placed by
+// the usual approximations for a light secondary, which get rougher as the masses even up; L4 and L5
+// lead and trail the secondary at the corners of equilateral triangles, which holds for any masses.
+pub fn points(planets: &[Planet]) -> Option<[(f64, f64); 5]> {
+    if planets.len() != 2 {
+        return None;
+    }
+    let (primary, secondary) = if planets[0].mass >= planets[1].mass {
+        (&planets[0], &planets[1])
+    } else {
+        (&planets[1], &planets[0])
+    };
+    let mass = primary.mass + secondary.mass;
+    if mass <= 0.0 || secondary.mass <= 0.0 {
+        return None;
+    }
+    let mu = secondary.mass / mass;
+    let (d_x, d_y) = (secondary.x - primary.x, secondary.y - primary.y);
+    let separation = d_x.hypot(d_y);
+    if separation == 0.0 {
+        return None;
+    }
+    let (u_x, u_y) = (d_x / separation, d_y / separation);
+
+    // Distances along the line from the primary, towards the secondary
+    let hill = (mu / 3.0).cbrt();
+    let along = |distance: f64| (primary.x + u_x * distance, primary.y + u_y * distance);
+    let l1 = along(separation * (1.0 - hill));
+    let l2 = along(separation * (1.0 + hill));
+    let l3 = along(-separation * (1.0 + 5.0 * mu / 12.0));
+
+    // The secondary's position turned 60 degrees either way about the primary, L4 ahead of it in the
+    // direction it is going round and L5 behind
+    let turning = (secondary.v_x - primary.v_x) * -d_y + (secondary.v_y - primary.v_y) * d_x;
+    let (sin, cos) = std::f64::consts::FRAC_PI_3.sin_cos();
+    let sin = if turning < 0.0 { -sin } else { sin };
+    let turned = |sin: f64| (primary.x + d_x * cos - d_y * sin, primary.y + d_x * sin + d_y * cos);
+    let (l4, l5) = (turned(sin), turned(-sin));
+    Some([l1, l2, l3, l4, l5])
+}
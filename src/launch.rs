@@ -0,0 +1,98 @@
+use crate::simulation::Planet;
+
+// Launch speed in px/s for every px the aim is dragged out from the launch site
+pub const DRAG_SPEED: f64 = 2.0;
+
+// How near horizontal (in degrees) and how near the right speed (as a fraction of it) a launch must be
+// for the snap assist to take over
+const SNAP_ANGLE: f64 = 15.0;
+const SNAP_SPEED: f64 = 0.2;
+
+// Launch site and aim for firing satellites by hand from a planet's surface, as in Newton's cannonball
+#[derive(Clone, Copy)]
+pub struct Launcher {
+    pub planet: usize, // Planet launched from
+    pub bearing: f64,  // Direction of the launch site from the planet's centre, in radians
+    pub altitude: f64, // Height of the launch site above the surface, in px
+    pub angle: f64,    // Launch angle in degrees above the horizon, 0 to 180 going over the top
+    pub speed: f64,    // Launch speed in px/s
+}
+
+impl Launcher {
+    // Launch site just clear of the surface of the planet closest to the given point, facing that
+    // point, with a horizontal launch at half the speed of a circular orbit skimming the surface.
+    // Repulsors are passed over, as nothing can orbit them.
+    pub fn pick(x: f64, y: f64, planets: &[Planet], gravity_constant: f64, sat_radius: f64) -> Option<Launcher> {
+        let distance = |planet: &Planet| (x - planet.x).powi(2) + (y - planet.y).powi(2);
+        let (index, planet) = planets.iter().enumerate().filter(|(_, planet)| !planet.repulsor()).min_by(|(_, a), (_, b)| distance(a).partial_cmp(&distance(b)).unwrap())?;
+        Some(Launcher {
+            planet: index,
+            bearing: (y - planet.y).atan2(x - planet.x),
+            altitude: sat_radius * 2.0,
+            angle: 0.0,
+            speed: 0.5 * (gravity_constant * planet.mass / planet.radius).sqrt(),
+        })
+    }
+
+    // Point satellites are launched from
+    pub fn site(&self, planets: &[Planet]) -> Option<(f64, f64)> {
+        let planet = planets.get(self.planet)?;
+        let distance = planet.radius + self.altitude;
+        Some((planet.x + distance * self.bearing.cos(), planet.y + distance * self.bearing.sin()))
+    }
+
+    // Launch velocity, turned from the local horizontal towards straight up by the launch angle
+    pub fn velocity(&self) -> (f64, f64) {
+        let (normal_x, normal_y) = (self.bearing.cos(), self.bearing.sin());
+        let (tangent_x, tangent_y) = (-normal_y, normal_x);
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        (self.speed * (cos * tangent_x + sin * normal_x), self.speed * (cos * tangent_y + sin * normal_y))
+    }
+
+    // The same launch adjusted to go into an orbit of the given eccentricity with the launch site as
+    // its closest point (circular for 0), if it is already close enough to one, going round the way it
+    // was aimed. Only the launch planet's pull is considered.
+    pub fn snapped(&self, planets: &[Planet], gravity_constant: f64, eccentricity: f64) -> Option<Launcher> {
+        let planet = planets.get(self.planet)?;
+        let speed = (gravity_constant * planet.mass * (1.0 + eccentricity) / (planet.radius + self.altitude)).sqrt();
+        let horizon = if self.angle > 90.0 { 180.0 } else { 0.0 };
+        if (self.angle - horizon).abs() <= SNAP_ANGLE && (self.speed - speed).abs() <= SNAP_SPEED * speed {
+            Some(Launcher { angle: horizon, speed, ..*self })
+        } else {
+            None
+        }
+    }
+
+    // Launch speed needed to reach the same highest point as this launch the cheapest way, by firing
+    // along the horizon as in the first burn of a Hohmann transfer, or None if this launch escapes.
+    // Only the launch planet's pull is considered.
+    pub fn minimum_speed(&self, planets: &[Planet], gravity_constant: f64) -> Option<f64> {
+        let planet = planets.get(self.planet)?;
+        let mu = gravity_constant * planet.mass;
+        let r = planet.radius + self.altitude;
+        let energy = self.speed * self.speed / 2.0 - mu / r;
+        if energy >= 0.0 {
+            return None;
+        }
+        let semi_major = -mu / (2.0 * energy);
+        let momentum = r * self.speed * self.angle.to_radians().cos();
+        let eccentricity = (1.0 + 2.0 * energy * momentum * momentum / (mu * mu)).max(0.0).sqrt();
+        let apoapsis = semi_major * (1.0 + eccentricity);
+        Some((2.0 * mu * apoapsis / (r * (r + apoapsis))).sqrt())
+    }
+
+    // Aims at a point dragged out from the launch site: the launch goes that way (or along the horizon
+    // for points below it), faster the further out
+    pub fn aim(&mut self, x: f64, y: f64, planets: &[Planet]) {
+        let (site_x, site_y) = match self.site(planets) {
+            Some(site) => site,
+            None => return,
+        };
+        let (d_x, d_y) = (x - site_x, y - site_y);
+        let (normal_x, normal_y) = (self.bearing.cos(), self.bearing.sin());
+        let up = d_x * normal_x + d_y * normal_y;
+        let along = -d_x * normal_y + d_y * normal_x;
+        self.angle = up.max(0.0).atan2(along).to_degrees();
+        self.speed = (d_x * d_x + d_y * d_y).sqrt() * DRAG_SPEED;
+    }
+}
@@ -0,0 +1,26 @@
+// Coarsest detail trails are drawn at, keeping one point in this many
+const MAX_STRIDE: usize = 16;
+
+// Level of detail trails are drawn at, so that zooming out doesn't cost more to draw: each halving of
+// the zoom below 1 keeps every other point of the level above
+#[derive(Clone, Copy)]
+pub struct TrailLod {
+    stride: usize, // One point in this many is drawn
+}
+
+impl TrailLod {
+    pub fn for_zoom(zoom: f64) -> TrailLod {
+        let level = if zoom < 1.0 { (1.0 / zoom).log2().floor() as u32 } else { 0 };
+        TrailLod {
+            stride: 2usize.saturating_pow(level).min(MAX_STRIDE),
+        }
+    }
+
+    // Indices of the points to draw of a trail of the given length, newest first, always keeping the
+    // newest and oldest so the trail stays as long
+    pub fn indices(&self, len: usize) -> impl Iterator<Item = usize> {
+        let stride = self.stride;
+        let oldest = Some(0).filter(|_| len > 0 && !(len - 1).is_multiple_of(stride));
+        (0..len).rev().step_by(stride).chain(oldest)
+    }
+}
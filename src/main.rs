@@ -8,195 +8,2012 @@ extern crate opengl_graphics;
 extern crate piston;
 extern crate fps_counter;
 
-use std::collections::VecDeque;
+mod animate;
+mod annotate;
+mod camera;
+mod capture;
+mod clip;
+mod clipboard;
+mod clock;
+mod cluster;
+mod comet;
+mod contour;
+mod cull;
+mod data;
+mod director;
+mod draw;
+mod elements;
+mod events;
+mod explore;
+mod export;
+mod fit;
+mod ghost;
+mod glow;
+mod hashtrace;
+mod hud;
+mod input;
+mod jacobi;
+mod lagrange;
+mod launch;
+mod lod;
+mod mass;
+mod moods;
+mod outcomes;
+mod pick;
+mod raster;
+mod resonance;
+mod scene;
+mod scores;
+mod share;
+mod simulation;
+mod slowmo;
+mod stability;
+mod svg;
+mod supervise;
+mod swarm;
+mod text;
+mod thumbnail;
+mod tilt;
+mod timeline;
+mod tuning;
+mod tutorial;
+
+use std::collections::HashSet;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use glutin_window::GlutinWindow as Window;
-use window::AdvancedWindow;
+use window::{AdvancedWindow, OpenGLWindow};
+use graphics::math;
+use graphics::Transformed;
 use opengl_graphics::{GlGraphics, OpenGL};
 use piston::event_loop::{EventSettings, Events};
-use piston::input::{RenderArgs, RenderEvent, UpdateArgs, UpdateEvent};
+use piston::input::{Key, MouseButton, RenderArgs, RenderEvent, UpdateArgs, UpdateEvent};
+use piston::window::Window as PistonWindow;
 use piston::window::WindowSettings;
 
-use rand::Rng;
-use rand::prelude::ThreadRng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use fps_counter::FPSCounter;
+
+use annotate::Annotations;
+use camera::Camera;
+use capture::{EventShots, FrameCapture};
+use clip::ClipRecorder;
+use clock::Clock;
+use cluster::Cluster;
+use cull::Frustum;
+use director::Director;
+use draw::{Backend, Blend, DrawList};
+use elements::OrbitTrack;
+use events::Event;
+use export::{KeyframeExporter, TrailExporter};
+use fit::{Aspect, Fit};
+use ghost::Ghost;
+use hud::{Hud, HudData, WidgetConfig, WidgetKind};
+use input::{Input, InputRecorder, InputReplay};
+use launch::Launcher;
+use lod::TrailLod;
+use mass::MassCue;
+use moods::{Moods, Timing};
+use outcomes::Outcomes;
+use resonance::Resonance;
+use scene::Scene;
+use share::ShareCode;
+use scores::Scores;
+use simulation::anisotropy::Anisotropy;
+use simulation::belt::Belt;
+use simulation::density::Density;
+use simulation::ecosystem::Ecosystem;
+use simulation::formation::{Formation, Shape};
+use simulation::integrator::Integrator;
+use simulation::oblateness::Oblateness;
+use simulation::collision::Collisions;
+use simulation::corotation::Pose;
+use simulation::wall::{self, Wall, WallKind};
+use simulation::emitter::Emitter;
+use simulation::periodic::{self, Orbit};
+use simulation::quota::Quotas;
+use simulation::region::Region;
+use simulation::autosave::Autosave;
+use simulation::{Boundary, Mode, Origin, Params, Planet, Satellite, Simulation};
+use slowmo::SlowMotion;
+use svg::Svg;
+use supervise::Heartbeat;
+use swarm::Swarm;
+use tilt::Tilt;
+use timeline::Timeline;
+use tuning::Tuning;
+use tutorial::{Action, Tutorial};
+
+
+struct Args {
+    title: String,          // Window title
+    overlay: bool,          // Draw over the desktop with a transparent background
+    kiosk: bool,            // Lock down input for unattended installations
+    kiosk_quit: KioskQuit,  // How a kiosk may be quit
+    kiosk_reset: f64,       // Seconds between automatic resets in kiosk mode (0 disables)
+    cluster_eps: f64,       // Neighbourhood radius (in px) used to detect clusters
+    cluster_min: usize,     // Minimum neighbours for a satellite to be at the core of a cluster
+    stats_log: f64,         // Seconds between printed summaries of outcomes (0 disables)
+    snap_eccentricity: f64, // Eccentricity of the orbits the snap assist steers launches into
+    time_warp: usize,       // Simulation steps taken per update
+    fragments: usize,       // Pieces each satellite splits into when set off as a firework
+    aspect: Aspect,         // How the world is fitted to a window of a different shape
+    ui_scale: Option<f64>,  // Physical pixels each pixel is drawn at, in place of the monitor's scale factor
+    annotations: String,    // Directory annotated frames are saved to
+    autosave: Option<String>, // File the bodies are saved to every so often, if any
+    mass_cue: MassCue,      // How planets show their mass
+}
+
+// Ways of quitting while in kiosk mode
+#[derive(Clone, Copy, PartialEq)]
+enum KioskQuit {
+    Combo,  // Ctrl+Alt+Q quits
+    Signal, // Only an external signal (e.g. SIGTERM) quits
+}
+
+// What satellites are coloured by
+#[derive(Clone, Copy, PartialEq)]
+enum ColorMode {
+    Random,    // Each satellite's own random colour
+    Stability, // How regular the satellite's orbit is
+    Jacobi,    // Jacobi constant, in binary mode
+}
+
+impl ColorMode {
+    fn next(self) -> ColorMode {
+        match self {
+            ColorMode::Random => ColorMode::Stability,
+            ColorMode::Stability => ColorMode::Jacobi,
+            ColorMode::Jacobi => ColorMode::Random,
+        }
+    }
+}
+
+// Modifier keys currently held down
+#[derive(Default)]
+struct Modifiers {
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+}
+
+impl Modifiers {
+    // Updates the held state for a modifier key, ignoring any other key
+    fn set(&mut self, key: Key, held: bool) {
+        match key {
+            Key::LCtrl | Key::RCtrl => self.ctrl = held,
+            Key::LAlt | Key::RAlt => self.alt = held,
+            Key::LShift | Key::RShift => self.shift = held,
+            _ => {}
+        }
+    }
+}
+
+// Range of the Jacobi slider, relative to the value at L1. Below the lower end nothing is out of reach.
+const JACOBI_LEVEL_MIN: f64 = 0.6;
+const JACOBI_LEVEL_MAX: f64 = 1.3;
+
+// Jacobi slider as [x, y, length] of its track, in the bottom-right corner of the window
+fn slider_track(width: f64, height: f64) -> [f64; 3] {
+    [width - 210.0, height - 16.0, 200.0]
+}
+
+// How many logical pixels of a window each pixel is drawn at, given the window's logical and physical
+// width: 1 to draw at the monitor's own scale factor, or whatever draws at the --ui_scale given instead
+fn ui_zoom(ui_scale: Option<f64>, window_width: f64, draw_width: f64) -> f64 {
+    match ui_scale {
+        Some(scale) if draw_width > 0.0 => scale * window_width / draw_width,
+        _ => 1.0,
+    }
+}
+
+// Transform viewing the world from the frame turning with a pair of planets, from the transform of the
+// world: the barycentre is kept at `origin` and the line between the planets lies level
+fn co_rotated(base: math::Matrix2d, origin: (f64, f64), pose: Pose) -> math::Matrix2d {
+    base.trans(origin.0, origin.1).rot_rad(-pose.angle).trans(-pose.x, -pose.y)
+}
+
+// Step size and number of steps used to predict the path of a launch
+const PREDICTION_DT: f64 = 1.0 / 120.0;
+const PREDICTION_STEPS: usize = 1200;
+
+// Whether a satellite at (x, y) is touching a planet
+fn hits_planet(x: f64, y: f64, planets: &[Planet], sat_radius: f64) -> bool {
+    planets.iter().any(|planet| planet.touches(x, y, sat_radius))
+}
+
+// Longest step taken to keep up with the wall clock in real-time mode, so a stall (e.g. while the
+// window is dragged) can't make the physics blow up
+const REALTIME_MAX_STEP: f64 = 0.1;
+
+// How far (in px) outside a planet the mouse can be and still count as over it
+const PICK_MARGIN: f64 = 4.0;
+
+// How close (in px) a click must be to an emitter to switch it on or off
+const EMITTER_CLICK_RADIUS: f64 = 10.0;
+
+// Step size used without a window, matching the default of 120 updates per second
+const HEADLESS_DT: f64 = 1.0 / 120.0;
+
+// Mass of the outer planet in the Kirkwood demo relative to the inner one: twenty times Jupiter's
+// share of the Sun, so the gaps open within minutes rather than over millions of years
+const KIRKWOOD_RATIO: f64 = 0.02;
+
+// Steps the Kirkwood demo takes per update unless told otherwise
+const KIRKWOOD_WARP: usize = 10;
+
+// Shortest wall (in px) the wall tool draws, so a click without a drag adds nothing
+const MIN_WALL_LENGTH: f64 = 5.0;
+
+// Fraction of normal speed time slows to in slow motion
+const SLOW_MOTION_SPEED: f64 = 0.25;
+
+// Seconds a lucky dip's tuning stays on screen
+const LUCKY_SHOWN: f64 = 10.0;
+
+// Seconds between autosaves of the bodies
+const AUTOSAVE_EVERY: f64 = 30.0;
+
+// Acceleration (in px/s^2) of the engine of a satellite piloted with Shift and the arrow keys
+const PILOT_THRUST: f64 = 40.0;
+
+// Gap (in px) between a planet's surface and the ring counting the satellites bound to it
+const BOUND_RING_GAP: f64 = 6.0;
+
+// Colours portals are drawn in, one per pair, so it is clear which mouths are linked
+const PORTAL_COLORS: [[f32; 4]; 4] = [
+    [0.6, 0.3, 1.0, 0.8],
+    [1.0, 0.6, 0.1, 0.8],
+    [0.1, 0.9, 0.9, 0.8],
+    [1.0, 0.3, 0.7, 0.8],
+];
+
+
+// Where the simulation is against the wall clock in real-time mode
+struct Realtime {
+    started: Instant, // When the simulation started, at simulation time 0
+    offset: f64,      // Seconds from the scene's epoch to the start
+}
+
+// A second window showing the same simulation through its own camera
+struct ExtraView {
+    window: Window,
+    gl: GlGraphics,
+    camera: Camera,
+}
+
+pub struct App {
+    fps_counter: FPSCounter,     // FPS counter
+    sim: Simulation,             // Planets and satellites
+    trail_export: Option<TrailExporter>, // Writes out trails of dead satellites
+    keyframe_export: Option<KeyframeExporter>, // Writes out sampled positions of dead satellites
+    clusters: Vec<Cluster>,      // Clusters of satellites found on the last pass
+    show_clusters: bool,         // Whether to detect and highlight clusters
+    since_analysis: f64,         // Seconds since the once-a-second analysis passes last ran
+    resonances: Vec<Resonance>,  // Resonant pairs of satellites found on the last pass
+    show_resonances: bool,       // Whether to detect and annotate resonances
+    color_mode: ColorMode,       // What satellites are coloured by
+    events: Vec<Event>,          // Events detected during the last update, including analysis
+    known_resonances: HashSet<(u64, u64)>, // Resonant pairs already reported
+    clip_recorder: Option<ClipRecorder>, // Saves clips around events
+    frame_capture: Option<FrameCapture>, // Saves the window as image frames
+    event_shots: Option<EventShots>, // Saves screenshots when chosen events happen
+    hud: Hud,                    // Widgets overlaid on the simulation
+    fps: usize,                  // Frames per second at the last render
+    bodies: (usize, usize),      // Bodies drawn and culled as out of view in the last frame, over all windows
+    step_time: f64,              // Seconds the simulation took to step in the last update
+    modifiers: Modifiers,        // Modifier keys currently held
+    show_zero_velocity: bool,    // Whether to draw zero-velocity curves
+    show_lagrange: bool,         // Whether to mark the Lagrange points of a pair of planets
+    jacobi_level: f64,           // Jacobi constant of the zero-velocity curves, relative to its value at L1
+    rotating_view: bool,         // Whether to view a pair of planets from the frame turning with them
+    barycentric: bool,           // Whether to keep the barycentre of the system at the centre of the main window
+    tilt: Option<Tilt>,          // Direction a world whose bodies move in three dimensions is viewed from
+    cursor: [f64; 2],            // Last known mouse position, in the world
+    cursor_moved: bool,          // Whether the mouse has moved since the satellite under it was last looked for
+    hover: Option<u64>,          // Id of the live satellite under the mouse, if any
+    fit: Fit,                    // How the world is placed in the main window
+    ui_zoom: f64,                // Logical pixels of the main window each pixel is drawn at
+    dragging: bool,              // Whether the Jacobi slider is being dragged
+    launcher: Option<Launcher>,  // Launch site and aim, while the launch tool is in use
+    piloted: Option<u64>,        // Last satellite launched by hand, which Shift and the arrow keys steer
+    engine: HashSet<Key>,        // Arrow keys held down to fire the piloted satellite's engine
+    timeline: Option<Timeline>,  // Captions and launches played back over time
+    tutorial: Option<Tutorial>,  // Walkthrough of the controls, until finished or skipped
+    outcomes: Outcomes,          // How satellites have fared over the session
+    clock: Clock,                // Simulation time and date, as shown and recorded
+    realtime: Option<Realtime>,  // Keeps the simulation in step with the wall clock, if set
+    slow_motion: Option<SlowMotion>, // Slows time down around near misses and merges, if set
+    moods: Option<Moods>,        // Moves the spawn palette round a day of colour themes, if set
+    director: Option<Director>,  // Frames the most interesting part of the world for unattended displays, if asked to
+    since_stats: f64,            // Seconds since outcomes were last printed
+    since_autosave: f64,         // Seconds since the bodies were last autosaved
+    aiming: bool,                // Whether a launch is being aimed with the mouse
+    snap: bool,                  // Whether launches close to a nice orbit are snapped onto it
+    score: Option<f64>,          // Efficiency of the last launch that stayed up, against the cheapest reaching as high
+    scores: Scores,              // High scores, saved between sessions
+    naming: Option<(f64, String)>, // High score waiting for a name, and the name typed so far
+    ghost: Ghost,                // Best launch so far, replayed alongside each new launch
+    show_origin: Option<&'static str>, // Only satellites with this kind of origin are drawn, if set
+    comets: bool,                // Whether satellites are drawn as comets, with tails streaming away from the nearest planet
+    glow: bool,                  // Whether trails are added on top of each other, brightening where they overlap
+    show_swarm: bool,            // Whether to mark the centre of mass and spread of the satellites shown
+    show_bound: bool,            // Whether to ring each planet with how many satellites are bound to it
+    bound: Vec<usize>,           // Satellites bound to each planet on the last pass
+    drawing_walls: bool,         // Whether the wall tool is in use
+    wall_start: Option<[f64; 2]>, // Where the wall being drawn starts, while dragging one out
+    annotations: Option<Annotations>, // Arrows and labels placed over the frozen frame, while annotating
+    since_reset: f64,            // Seconds since the simulation was last reset
+    lucky: Option<(Tuning, f64)>, // Tuning picked by the last lucky dip, and how many more seconds to show it
+    updates: u64,                // Updates run so far, which recorded input is tagged with
+    input_recorder: Option<InputRecorder>, // Writes out input so the session can be replayed
+    args: Args,                  // Any other useful arguments
+}
+
+impl App {
+    fn render(&mut self, args: &RenderArgs, window: &mut Window, gl: &mut GlGraphics) {
+        let fps = self.fps_counter.tick();
+        self.fps = fps;
+        window.set_title(format!("{} ({} fps)", self.args.title, fps));
+
+        // The director takes the main window over while nobody is using it
+        let camera = match self.director.as_ref() {
+            Some(director) if director.has_main() => Camera::Director,
+            _ => Camera::Overview,
+        };
+        let mut list = DrawList::default();
+        self.bodies = self.draw(args, camera, &mut list);
+        gl.render(&list, args.viewport());
+
+        // Save the annotated frame just drawn, both as an image and as a drawing that scales cleanly
+        if let Some(annotations) = self.annotations.as_mut().filter(|annotations| annotations.export) {
+            annotations.export = false;
+            let saved = annotate::next_paths(&self.args.annotations).and_then(|(png, svg)| {
+                capture::save_window(&png, args.draw_size[0], args.draw_size[1])?;
+                let mut drawing = Svg::default();
+                drawing.render(&list, args.viewport());
+                std::fs::write(&svg, drawing.document)?;
+                Ok((png, svg))
+            });
+            match saved {
+                Ok((png, svg)) => println!("Saved annotated frame as {} and {}", png.display(), svg.display()),
+                Err(e) => eprintln!("Could not save annotated frame: {}", e),
+            }
+        }
+
+        if let Some(capture) = self.frame_capture.as_mut() {
+            capture.capture(self.sim.time, args.draw_size[0], args.draw_size[1]).expect("Could not save captured frame");
+        }
+        if let Some(shots) = self.event_shots.as_mut() {
+            shots.save(args.draw_size[0], args.draw_size[1]).expect("Could not save screenshot");
+        }
+    }
+
+    // Draws the extra window in step with the main one. Its input is ignored, beyond closing it.
+    fn render_extra(&mut self, view: &mut ExtraView) {
+        while view.window.poll_event().is_some() {}
+        view.window.make_current();
+        let size = view.window.size();
+        let draw_size = view.window.draw_size();
+        let args = RenderArgs {
+            ext_dt: 0.0,
+            window_size: [size.width, size.height],
+            draw_size: [draw_size.width as u32, draw_size.height as u32],
+        };
+        let mut list = DrawList::default();
+        let (drawn, culled) = self.draw(&args, view.camera, &mut list);
+        view.gl.render(&list, args.viewport());
+        self.bodies = (self.bodies.0 + drawn, self.bodies.1 + culled);
+        view.window.swap_buffers();
+    }
+
+    // Adds the simulation as seen by a camera to a draw list, returning how many bodies were drawn and how
+    // many were skipped as out of view. Overlays tied to the screen, such as the HUD, are only drawn for the
+    // overview and the director.
+    fn draw(&self, args: &RenderArgs, camera: Camera, list: &mut DrawList) -> (usize, usize) {
+        use graphics::{rectangle, Context};
+
+        const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+        const TRANSPARENT: [f32; 4] = [0.0, 0.0, 0.0, 0.0];
+
+        let background = if self.args.overlay { TRANSPARENT } else { BLACK };
+        let planets_iter = self.sim.planets.iter();
+        let roche = self.sim.params.roche;
+        let mass_cue = self.args.mass_cue;
+        let show_origin = self.show_origin;
+        let comets = self.comets;
+        let glow = self.glow;
+        let satellites_iter = self.sim.satellites.iter().filter(|sat| show_origin.is_none_or(|name| sat.origin.name() == name));
+        let swarm = if self.show_swarm { Swarm::measure(satellites_iter.clone()) } else { None };
+        let bound = &self.bound;
+        let color_mode = self.color_mode;
+        let planets = &self.sim.planets;
+        let gravity_constant = self.sim.params.gravity_constant;
+        let sat_radius = self.sim.params.sat_radius;
+        let score = self.score;
+        let naming = self.naming.as_ref();
+        let cursor = self.cursor;
+        let annotations = self.annotations.as_ref();
+        let hovered = self.hovered_planet();
+        let hover = self.hover;
+        let hovered_satellite = if hovered.is_none() { hover.and_then(|id| self.sim.satellites.iter().find(|sat| sat.id == id)) } else { None };
+        let ghost = self.ghost.trail(self.sim.params.trail_length);
+        let frame = self.sim.rotating_frame();
+        let l1 = frame.and_then(|frame| jacobi::l1(planets, gravity_constant, frame));
+        let zoom = ui_zoom(self.args.ui_scale, args.window_size[0], args.draw_size[0] as f64);
+        let width = args.window_size[0] / zoom;
+        let height = args.window_size[1] / zoom;
+        // Overviews follow the barycentre along with the main window, while cameras framing something
+        // of their own do not
+        let fit = match camera {
+            Camera::Overview => Fit::new(self.args.aspect, self.sim.params.width, self.sim.params.height, width, height).shifted(self.fit.shift()),
+            _ => Fit::new(self.args.aspect, self.sim.params.width, self.sim.params.height, width, height),
+        };
+        let co_rotation = &self.sim.co_rotation;
+        let pose = co_rotation.now().filter(|_| self.rotating_view);
+        let show_zero_velocity = self.show_zero_velocity;
+        let lagrange = if self.show_lagrange { lagrange::points(planets) } else { None };
+        let jacobi_level = self.jacobi_level;
+        let show_clusters = self.show_clusters;
+        let clusters_iter = self.clusters.iter().filter(|_| show_clusters);
+        let show_resonances = self.show_resonances;
+        let resonances_iter = self.resonances.iter().filter(|_| show_resonances);
+        let ripples_iter = self.sim.ripples.iter();
+        let emitters_iter = self.sim.emitters.iter();
+        let walls = &self.sim.walls;
+        let fields = &self.sim.fields;
+        let portals = &self.sim.portals;
+        let new_wall = self.wall_start.map(|start| [start[0], start[1], self.cursor[0], self.cursor[1]]);
+        let spawning = self.sim.spawning;
+        let time = self.sim.time;
+        let satellites = &self.sim.satellites;
+        let launch = self.aimed().and_then(|(launcher, snapped)| {
+            let (x, y) = launcher.site(planets)?;
+            let (v_x, v_y) = launcher.velocity();
+            Some((launcher, snapped, x, y, v_x, v_y, self.sim.predict(x, y, v_x, v_y, PREDICTION_STEPS, PREDICTION_DT)))
+        });
+        let tutorial = self.tutorial.is_some();
+        let caption = match self.tutorial.as_ref() {
+            Some(tutorial) => Some(tutorial.caption()),
+            None => self.timeline.as_ref().and_then(|timeline| timeline.caption.as_deref()),
+        };
+        let lucky = self.lucky.as_ref().map(|(tuning, _)| tuning.lines());
+        let hud = &self.hud;
+        let overview = matches!(camera, Camera::Overview | Camera::Director);
+        let camera = match (camera, self.director.as_ref()) {
+            (Camera::Director, Some(director)) => director.camera(),
+            (Camera::Director, None) => Camera::Overview,
+            (camera, _) => camera,
+        };
+        let unmoved = camera == Camera::Overview;
+        let lod = TrailLod::for_zoom(camera.zoom());
+        let hud_data = HudData {
+            fps: self.fps,
+            time: self.sim.time,
+            satellites: &self.sim.satellites,
+            planets: &self.sim.planets,
+            gravity_constant: self.sim.params.gravity_constant,
+            events: &self.events,
+            outcomes: &self.outcomes,
+            bodies: self.bodies,
+            step_time: self.step_time,
+            trails: (self.sim.trails.reused, self.sim.trails.allocated),
+            bound: self.sim.bound_counts(),
+            clock: &self.clock,
+            quotas: &self.sim.quotas,
+        };
+
+        let c = Context::new_viewport(args.viewport()).zoom(zoom);
+
+        // Clear the screen.
+        list.clear(background);
+
+        // Viewed from the rotating frame, everything is turned back by the angle the planets have
+        // turned through, so that they stay still
+        let fitted = fit.transform(c.transform);
+        let world = camera.transform(fitted, self.sim.params.width, self.sim.params.height, planets);
+        // Bodies moving in three dimensions are seen from the direction the view is turned and tipped to
+        let tilt = self.tilt;
+        let world = match tilt {
+            Some(tilt) => tilt.transform(world, self.sim.params.width / 2.0, self.sim.params.height / 2.0),
+            None => world,
+        };
+        let view = match pose {
+            Some(pose) => co_rotated(world, co_rotation.origin(), pose),
+            None => world,
+        };
+        let turned = pose.map_or(0.0, |pose| -pose.angle);
+        let frustum = Frustum::new(view);
+        let (mut drawn, mut culled) = (0, 0);
+
+        // Shade where satellites with the chosen Jacobi constant can never go, and outline it
+        if let (true, true, Some(frame), Some(l1)) = (unmoved, show_zero_velocity, frame, l1) {
+            let (centre, origin, (sin, cos)) = match pose {
+                Some(pose) => ((pose.x, pose.y), co_rotation.origin(), pose.angle.sin_cos()),
+                None => ((frame.x, frame.y), (frame.x, frame.y), (0.0, 1.0)),
+            };
+            let to_world = |x: f64, y: f64| {
+                (centre.0 + (x - origin.0) * cos - (y - origin.1) * sin, centre.1 + (x - origin.0) * sin + (y - origin.1) * cos)
+            };
+            let grid = jacobi::potential_grid(self.sim.params.width, self.sim.params.height, planets, gravity_constant, frame, to_world);
+            let level = jacobi_level * l1;
+            for row in 0..grid.rows {
+                for col in 0..grid.cols {
+                    if grid.get(col, row) < level {
+                        let rect = rectangle::centered_square(col as f64 * grid.spacing, row as f64 * grid.spacing, grid.spacing / 2.0);
+                        list.rectangle([0.5, 0.5, 0.5, 0.25], rect, fitted);
+                    }
+                }
+            }
+            for segment in grid.contour(level) {
+                list.line([1.0, 1.0, 1.0, 0.6], 0.5, segment, fitted);
+            }
+        }
+
+        // Draw planets, with a faint haze for any atmosphere and a glow around a star, skipping any out of view
+        for planet in planets_iter {
+            let radius = mass_cue.radius(planet);
+            let reach = if planet.star { radius * 1.75 } else { radius };
+            let reach = mass_cue.ring(planet).map_or(reach, |ring| reach.max(ring));
+            if !frustum.sees(planet.x, planet.y, reach.max(planet.radius + planet.atmosphere_height)) {
+                culled += 1;
+                continue;
+            }
+            drawn += 1;
+            if planet.atmosphere_height > 0.0 {
+                let [r, g, b, _] = planet.color;
+                let haze = rectangle::centered_square(planet.x, planet.y, planet.radius + planet.atmosphere_height);
+                list.ellipse([r, g, b, 0.15], haze, view);
+            }
+            if planet.star {
+                for ring in 1..=3 {
+                    let glow = rectangle::centered_square(planet.x, planet.y, radius * (1.0 + 0.25 * ring as f64));
+                    list.ellipse([1.0, 0.9, 0.6, 0.08], glow, view);
+                }
+            }
+            match (planet.outline.as_ref(), tilt) {
+                // Asteroids are flat slabs lying in the plane, drawn at their true shape and size
+                (Some(outline), _) => {
+                    let corner = |i: usize| [planet.x + outline.corners[i].0, planet.y + outline.corners[i].1];
+                    for &[a, b, c] in outline.triangles.iter() {
+                        list.polygon(planet.color, &[corner(a), corner(b), corner(c)], view);
+                    }
+                },
+                (None, Some(tilt)) => list.ellipse(planet.color, rectangle::centered_square(0.0, 0.0, radius), tilt.upright(view, planet.x, planet.y, turned)),
+                (None, None) => list.ellipse(planet.color, rectangle::centered_square(planet.x, planet.y, radius), view),
+            }
+            // Mark repulsors with a minus sign, for their negative mass
+            if planet.repulsor() {
+                let half = radius / 2.0;
+                list.line([0.1, 0.1, 0.15, 1.0], radius / 8.0, [planet.x - half, planet.y, planet.x + half, planet.y], view);
+            }
+            // Circle the planet at a radius standing for its mass, brighter than the planet so that it
+            // shows inside it too
+            if let Some(ring) = mass_cue.ring(planet) {
+                let [r, g, b, _] = planet.color;
+                let lit = [(r + 1.0) / 2.0, (g + 1.0) / 2.0, (b + 1.0) / 2.0, 0.8];
+                list.ellipse_border(lit, 1.0, rectangle::centered_square(planet.x, planet.y, ring), view);
+            }
+            if roche > 0.0 && !planet.repulsor() {
+                let [r, g, b, _] = planet.color;
+                list.ellipse_border([r, g, b, 0.2], 0.5, rectangle::centered_square(planet.x, planet.y, roche * planet.radius), view);
+            }
+            // Mark the spin axis of a tipped-over oblate planet where it pokes out of each pole
+            if let Some((n_x, n_y)) = planet.oblateness.map(|oblateness| oblateness.axis()).filter(|(n_x, n_y)| n_x.hypot(*n_y) > 0.01) {
+                let (r_x, r_y) = (n_x * planet.radius * 1.5, n_y * planet.radius * 1.5);
+                list.line([1.0, 1.0, 1.0, 0.4], 0.5, [planet.x - r_x, planet.y - r_y, planet.x + r_x, planet.y + r_y], view);
+            }
+        }
+
+        // Mark where satellites can be parked to go round with a pair of planets
+        if let Some(points) = lagrange {
+            const LAGRANGE: [f32; 4] = [0.6, 0.9, 1.0, 0.7];
+            for (i, &(x, y)) in points.iter().enumerate() {
+                list.line(LAGRANGE, 0.5, [x - 4.0, y, x + 4.0, y], view);
+                list.line(LAGRANGE, 0.5, [x, y - 4.0, x, y + 4.0], view);
+                list.text(&format!("L{}", i + 1), LAGRANGE, x + 5.0, y - 12.0, 1.0, view);
+            }
+        }
+
+        // Ring each planet with a faint count of the satellites bound to it
+        for (planet, count) in planets.iter().zip(bound.iter()).filter(|(planet, _)| !planet.repulsor()) {
+            let [r, g, b, _] = planet.color;
+            let ring = mass_cue.radius(planet) + BOUND_RING_GAP;
+            list.ellipse_border([r, g, b, 0.3], 0.5, rectangle::centered_square(planet.x, planet.y, ring), view);
+            let label = count.to_string();
+            list.text(&label, [r, g, b, 0.6], planet.x - crate::text::width(&label, 1.0) / 2.0, planet.y - ring - 12.0, 1.0, view);
+        }
+
+        // Trail points are carried along with the planets as they have turned and moved since the
+        // points were recorded, so that trails in the rotating frame show the path taken in that frame
+        let unturn = |pos: (f64, f64), age: usize| -> (f64, f64) {
+            match pose {
+                Some(_) => co_rotation.carry(pos, age),
+                None => pos,
+            }
+        };
+
+        // Mark emitters with their launch direction, dimmed while switched off
+        for emitter in emitters_iter {
+            let alpha = if emitter.enabled && spawning { 0.8 } else { 0.25 };
+            let color = [1.0, 1.0, 1.0, alpha];
+            let (sin, cos) = emitter.angle.to_radians().sin_cos();
+            list.ellipse(color, rectangle::centered_square(emitter.x, emitter.y, 4.0), view);
+            list.line(color, 1.0, [emitter.x, emitter.y, emitter.x + 15.0 * cos, emitter.y + 15.0 * sin], view);
+        }
+
+        // Tint fields by their effect
+        for field in fields.iter() {
+            match &field.region {
+                Region::Circle { x, y, radius } => list.ellipse(field.color(), rectangle::centered_square(*x, *y, *radius), view),
+                Region::Rect { x, y, width, height } => list.rectangle(field.color(), [*x, *y, *width, *height], view),
+                Region::Planets => {
+                    for planet in planets.iter() {
+                        list.ellipse(field.color(), rectangle::centered_square(planet.x, planet.y, planet.radius), view);
+                    }
+                },
+                Region::Polygon { points } => list.polygon(field.color(), points, view),
+            }
+        }
+
+        // Draw portals, each pair of mouths in a colour of its own joined by a faint line
+        for (i, portal) in portals.iter().enumerate() {
+            let color = PORTAL_COLORS[i % PORTAL_COLORS.len()];
+            for mouth in [portal.a, portal.b].iter() {
+                list.ellipse_border(color, 1.5, rectangle::centered_square(mouth.x, mouth.y, mouth.radius), view);
+            }
+            list.line([color[0], color[1], color[2], 0.15], 1.0, [portal.a.x, portal.a.y, portal.b.x, portal.b.y], view);
+        }
+
+        // Draw walls, absorbing ones in red, and the one being dragged out
+        for wall in walls.iter() {
+            let color = match wall.kind {
+                WallKind::Bounce => [1.0, 1.0, 1.0, 0.8],
+                WallKind::Absorb => [1.0, 0.3, 0.3, 0.8],
+            };
+            list.line(color, 1.5, [wall.x1, wall.y1, wall.x2, wall.y2], view);
+        }
+        if let Some(ends) = new_wall {
+            list.line([1.0, 1.0, 1.0, 0.4], 1.5, ends, view);
+        }
+
+        let satellite_color = |satellite: &Satellite| match color_mode {
+            ColorMode::Random => satellite.color,
+            ColorMode::Stability => stability::color(satellite.stability.estimate()),
+            ColorMode::Jacobi => match (frame, l1) {
+                (Some(frame), Some(l1)) => jacobi::color(jacobi::constant(satellite, planets, gravity_constant, frame), l1),
+                _ => [0.5, 0.5, 0.5, 1.0],
+            },
+        };
+
+        // Segments of a satellite's trail in view, at less detail the further out the camera is,
+        // leaving a gap wherever the satellite jumped through a portal or wrapped round an edge (marked
+        // by a point that is not a number)
+        // Where a point at the given height, if any, is drawn in the plane
+        let lift = |(x, y): (f64, f64), z: Option<f64>| match (tilt, z) {
+            (Some(tilt), Some(z)) => tilt.lift(x, y, z, turned),
+            _ => (x, y),
+        };
+
+        let trail_segments = |satellite: &Satellite| {
+            let trail = &satellite.trail;
+            let mut segments = Vec::new();
+            if trail.len() > 1 {
+                let newest = trail.len() - 1;
+                let mut points = lod.indices(trail.len()).map(|i| (i, lift(unturn(trail[i], newest - i), satellite.heights.get(i).copied())));
+                let (mut i_old, mut pos_old) = points.next().unwrap();
+                for (i, pos) in points {
+                    let gap = trail.range(i..=i_old).any(|pos| pos.0.is_nan());
+                    if !gap && frustum.sees_line(pos, pos_old) {
+                        segments.push([pos.0, pos.1, pos_old.0, pos_old.1]);
+                    }
+                    i_old = i;
+                    pos_old = pos;
+                }
+            }
+            segments
+        };
+
+        // Glowing trails are all added on top of each other before any satellite is drawn, toned
+        // down where they pile up so that they brighten without clipping
+        if glow {
+            let (mut colors, mut segments) = (Vec::new(), Vec::new());
+            for satellite in satellites_iter.clone() {
+                let color = satellite_color(satellite);
+                for ends in trail_segments(satellite) {
+                    colors.push(color);
+                    segments.push(ends);
+                }
+            }
+            list.blend(Blend::Additive);
+            for ((color, ends), alpha) in colors.iter().zip(segments.iter()).zip(glow::tone_map(&segments)) {
+                list.line([color[0], color[1], color[2], color[3] * alpha], 1.0, *ends, view);
+            }
+            list.blend(Blend::Alpha);
+        }
+
+        // Draw satellites
+        for satellite in satellites_iter {
+            let color = satellite_color(satellite);
+
+            // Draw trail
+            if !glow {
+                for ends in trail_segments(satellite) {
+                    list.line(color, 1.0, ends, view);
+                }
+            }
+
+            // Draw satellite, unless it is out of view
+            if satellite.fate.is_none() {
+                let (x, y) = lift((satellite.x, satellite.y), Some(satellite.z));
+                if !frustum.sees(x, y, satellite.radius) {
+                    culled += 1;
+                    continue;
+                }
+                drawn += 1;
+                // Belts hold thousands of bodies, so each is just a dot
+                if satellite.origin == Origin::Belt {
+                    list.rectangle(color, [x - 0.5, y - 0.5, 1.0, 1.0], view);
+                    continue;
+                }
+                let rect = rectangle::rectangle_by_corners(x - satellite.radius, y - satellite.radius, x + satellite.radius, y + satellite.radius);
+                // Show a firing engine as a flame out the back, longer the harder it pushes
+                if time < satellite.burn_until {
+                    let (a_x, a_y) = satellite.thrust;
+                    let length = a_x.hypot(a_y);
+                    if length > 0.0 {
+                        let flame = satellite.radius + length.sqrt() * 2.0;
+                        let ends = [x, y, x - a_x / length * flame, y - a_y / length * flame];
+                        list.line([1.0, 0.6, 0.1, 0.9], satellite.radius / 3.0, ends, view);
+                    }
+                }
+                list.ellipse(color, rect, view);
+                if comets {
+                    for (tail_x, tail_y, radius, alpha) in comet::tail(satellite, planets) {
+                        let (tail_x, tail_y) = (tail_x + x - satellite.x, tail_y + y - satellite.y);
+                        list.ellipse([color[0], color[1], color[2], alpha], rectangle::centered_square(tail_x, tail_y, radius), view);
+                    }
+                }
+            }
+        }
+
+        // Light up the trail of the satellite under the mouse, over the rest
+        if let (true, Some(satellite)) = (unmoved, hovered_satellite) {
+            let color = satellite_color(satellite);
+            let lit = [(color[0] + 1.0) / 2.0, (color[1] + 1.0) / 2.0, (color[2] + 1.0) / 2.0, 1.0];
+            for ends in trail_segments(satellite) {
+                list.line(lit, 1.5, ends, view);
+            }
+        }
+
+        // Mark the centre of mass with a cross and outline the 1-sigma dispersion ellipse around it
+        if let Some(swarm) = swarm.as_ref() {
+            const SWARM: [f32; 4] = [0.0, 1.0, 1.0, 0.7];
+            let transform = view.trans(swarm.x, swarm.y).rot_rad(swarm.angle);
+            let rect = [-swarm.major, -swarm.minor, 2.0 * swarm.major, 2.0 * swarm.minor];
+            list.ellipse_border(SWARM, 1.0, rect, transform);
+            list.line(SWARM, 1.0, [-6.0, 0.0, 6.0, 0.0], transform);
+            list.line(SWARM, 1.0, [0.0, -6.0, 0.0, 6.0], transform);
+        }
+
+        // Outline and label clusters
+        const CLUSTER: [f32; 4] = [1.0, 1.0, 1.0, 0.6];
+        for cluster in clusters_iter {
+            for (i, a) in cluster.hull.iter().enumerate() {
+                let b = cluster.hull[(i + 1) % cluster.hull.len()];
+                list.line(CLUSTER, 1.0, [a.0, a.1, b.0, b.1], view);
+            }
+            let top = cluster.hull.iter().fold(cluster.hull[0], |top, p| if p.1 < top.1 { *p } else { top });
+            let label = format!("{} sats", cluster.size);
+            list.text(&label, CLUSTER, top.0 - crate::text::width(&label, 1.0) / 2.0, top.1 - 12.0, 1.0, view);
+        }
+
+        // Link resonant pairs and label them with their period ratio
+        const RESONANCE: [f32; 4] = [1.0, 1.0, 0.0, 0.6];
+        for resonance in resonances_iter {
+            let a = satellites.iter().find(|sat| sat.id == resonance.a && sat.fate.is_none());
+            let b = satellites.iter().find(|sat| sat.id == resonance.b && sat.fate.is_none());
+            if let (Some(a), Some(b)) = (a, b) {
+                list.line(RESONANCE, 0.5, [a.x, a.y, b.x, b.y], view);
+                let label = format!("{}:{}", resonance.p, resonance.q);
+                list.text(&label, RESONANCE, (a.x + b.x) / 2.0, (a.y + b.y) / 2.0, 1.0, view);
+            }
+        }
+
+        // Draw ripples as a few rings spreading and fading behind each other
+        for ripple in ripples_iter {
+            for ring in 0..3 {
+                let radius = ripple.radius(time) - ring as f64 * 8.0;
+                if radius <= 0.0 {
+                    continue;
+                }
+                let alpha = (ripple.remaining(time) * ripple.strength.min(2.0) * 0.3 / (ring + 1) as f64) as f32;
+                let rect = rectangle::centered_square(ripple.x, ripple.y, radius);
+                list.ellipse_border([1.0, 1.0, 1.0, alpha], 1.0, rect, view);
+            }
+        }
+
+        // Replay the best launch so far as a ghost
+        if let Some(ghost) = ghost {
+            const GHOST: [f32; 4] = [1.0, 1.0, 1.0, 0.3];
+            for pair in ghost.windows(2) {
+                list.line(GHOST, 1.0, [pair[0].0, pair[0].1, pair[1].0, pair[1].1], view);
+            }
+            let &(x, y) = ghost.last().unwrap();
+            list.ellipse(GHOST, rectangle::centered_square(x, y, sat_radius), view);
+        }
+
+        // Show where a launch would go: the aim as an arrow out from the launch site, then the
+        // predicted path as a dashed line
+        if let Some((launcher, snapped, x, y, v_x, v_y, path)) = launch.as_ref() {
+            const LAUNCH: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+            for (i, pair) in path.windows(2).enumerate() {
+                if i % 8 < 4 && !pair[0].0.is_nan() && !pair[1].0.is_nan() {
+                    list.line([1.0, 1.0, 1.0, 0.4], 1.0, [pair[0].0, pair[0].1, pair[1].0, pair[1].1], view);
+                }
+            }
+            let (tip_x, tip_y) = (x + v_x / launch::DRAG_SPEED, y + v_y / launch::DRAG_SPEED);
+            list.line(LAUNCH, 1.0, [*x, *y, tip_x, tip_y], view);
+            list.ellipse(LAUNCH, rectangle::centered_square(*x, *y, 3.0), view);
+            let label = format!("{:.0} deg, {:.0} px/s{}", launcher.angle, launcher.speed, if *snapped { " (snapped)" } else { "" });
+            list.text(&label, LAUNCH, tip_x + 6.0, tip_y - 4.0, 1.0, view);
+            if let Some(score) = score {
+                let label = format!("last launch {:.0}% efficient", score * 100.0);
+                list.text(&label, LAUNCH, tip_x + 6.0, tip_y + 8.0, 1.0, view);
+            }
+
+            // Warn when the path comes back down, marking where with the time to impact, and
+            // suggest burning along the horizon (the way the launch was going) to stay up
+            let &(end_x, end_y) = path.last().unwrap();
+            if hits_planet(end_x, end_y, planets, sat_radius) {
+                const WARNING: [f32; 4] = [1.0, 0.3, 0.2, 0.9];
+                list.line(WARNING, 1.0, [end_x - 5.0, end_y - 5.0, end_x + 5.0, end_y + 5.0], view);
+                list.line(WARNING, 1.0, [end_x - 5.0, end_y + 5.0, end_x + 5.0, end_y - 5.0], view);
+                let label = format!("impact in {:.1}s", (path.len() - 1) as f64 * PREDICTION_DT);
+                list.text(&label, WARNING, end_x + 8.0, end_y - 4.0, 1.0, view);
+                let sense = if launcher.angle > 90.0 { -1.0 } else { 1.0 };
+                let (burn_x, burn_y) = (-launcher.bearing.sin() * sense, launcher.bearing.cos() * sense);
+                list.line(WARNING, 1.0, [*x, *y, x + 20.0 * burn_x, y + 20.0 * burn_y], view);
+                list.ellipse(WARNING, rectangle::centered_square(x + 20.0 * burn_x, y + 20.0 * burn_y, 2.0), view);
+            }
+        }
+
+        // Black out whatever overhangs the world when letterboxing
+        for bar in fit.bars() {
+            list.rectangle(BLACK, bar, c.transform);
+        }
+
+        // Mark up the frozen frame, explaining how unless the frame is being saved
+        if let (true, Some(annotations)) = (unmoved, annotations) {
+            annotations.draw(cursor, fitted, list);
+            if !annotations.export {
+                let hint = "frozen: drag for an arrow, click to label, Backspace to undo, E to save, A to resume";
+                list.text(hint, [1.0, 1.0, 1.0, 0.8], 10.0, height - 10.0, 1.0, c.transform);
+            }
+        }
+
+        // Explain the Jacobi colours with a scale from free to trapped
+        if overview && color_mode == ColorMode::Jacobi {
+            const LEGEND: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+            if l1.is_some() {
+                for i in 0..100 {
+                    let ratio = -0.5 + i as f64 / 50.0;
+                    list.rectangle(jacobi::color(ratio, 1.0), [10.0 + i as f64, height - 30.0, 1.0, 8.0], c.transform);
+                }
+                list.text("free", LEGEND, 10.0, height - 18.0, 1.0, c.transform);
+                list.text("L1", LEGEND, 77.0, height - 18.0, 1.0, c.transform);
+                list.text("trapped", LEGEND, 115.0, height - 30.0, 1.0, c.transform);
+                list.text("Jacobi constant", LEGEND, 10.0, height - 42.0, 1.0, c.transform);
+            } else {
+                list.text("Jacobi constant needs --mode binary", LEGEND, 10.0, height - 18.0, 1.0, c.transform);
+            }
+        }
+
+        // Slider setting the Jacobi constant of the zero-velocity curves
+        if overview && show_zero_velocity {
+            const SLIDER: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+            if l1.is_some() {
+                let track = slider_track(width, height);
+                list.line(SLIDER, 0.5, [track[0], track[1], track[0] + track[2], track[1]], c.transform);
+                let knob = track[0] + track[2] * (jacobi_level - JACOBI_LEVEL_MIN) / (JACOBI_LEVEL_MAX - JACOBI_LEVEL_MIN);
+                list.rectangle(SLIDER, rectangle::centered([knob, track[1], 3.0, 6.0]), c.transform);
+                let label = format!("C = {:.2} x L1", jacobi_level);
+                list.text(&label, SLIDER, track[0], track[1] - 20.0, 1.0, c.transform);
+            } else {
+                list.text("Zero-velocity curves need --mode binary", SLIDER, width - 330.0, height - 18.0, 1.0, c.transform);
+            }
+        }
+
+        // Caption from the tutorial or the timeline, centred along the top
+        if let (true, Some(caption)) = (overview, caption) {
+            list.text(caption, [1.0, 1.0, 1.0, 0.9], (width - crate::text::width(caption, 2.0)) / 2.0, 20.0, 2.0, c.transform);
+        }
+        if overview && tutorial {
+            let hint = "Tab skips the tutorial";
+            list.text(hint, [1.0, 1.0, 1.0, 0.6], (width - crate::text::width(hint, 1.0)) / 2.0, 44.0, 1.0, c.transform);
+        }
+
+        // Tuning picked by the last lucky dip, in the top right
+        if let (true, Some(lines)) = (overview, lucky) {
+            const LUCKY: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+            let box_width = lines.iter().map(|row| crate::text::width(row, 1.0)).fold(0.0, f64::max) + 8.0;
+            let x = width - box_width - 10.0;
+            list.rectangle([0.0, 0.0, 0.0, 0.8], [x, 10.0, box_width, 4.0 + 12.0 * lines.len() as f64], c.transform);
+            for (j, row) in lines.iter().enumerate() {
+                list.text(row, LUCKY, x + 4.0, 14.0 + 12.0 * j as f64, 1.0, c.transform);
+            }
+        }
+
+        // Describe the planet or satellite under the cursor
+        let tooltip = match (hovered, hovered_satellite) {
+            (Some(i), _) => {
+                let planet = &planets[i];
+                let bound = satellites.iter()
+                    .filter(|sat| sat.fate.is_none() && sat.track.as_ref().map(|track| track.elements.planet) == Some(i))
+                    .count();
+                Some(vec![
+                    format!("planet {}", i),
+                    format!("mass {:.0}", planet.mass),
+                    format!("{} satellites in orbit", bound),
+                    format!("accreted {:.0}", planet.accreted),
+                ])
+            },
+            (None, Some(sat)) => {
+                let altitude = planets.iter()
+                    .map(|planet| (sat.x - planet.x).hypot(sat.y - planet.y) - planet.radius)
+                    .fold(f64::INFINITY, f64::min);
+                let mut lines = vec![
+                    format!("satellite {}", sat.id),
+                    format!("speed {:.0} px/s", sat.v_x.hypot(sat.v_y)),
+                    format!("age {:.1} s", time - sat.born),
+                ];
+                if altitude.is_finite() {
+                    lines.push(format!("altitude {:.0} px", altitude));
+                }
+                match &sat.track {
+                    Some(track) => lines.extend([
+                        format!("orbiting planet {}", track.elements.planet),
+                        format!("a {:.0} px, e {:.2}", track.elements.semi_major_axis, track.elements.eccentricity),
+                        format!("period {:.1} s", track.elements.period),
+                        format!("{:.0} orbits", track.orbits(time).floor()),
+                    ]),
+                    None => lines.push("not in orbit".to_string()),
+                }
+                Some(lines)
+            },
+            (None, None) => None,
+        };
+        if let (true, Some(lines)) = (unmoved, tooltip) {
+            const TOOLTIP: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+            let [x, y] = fit.to_window(cursor);
+            let (x, y) = (x + 12.0, y + 12.0);
+            let box_width = lines.iter().map(|row| crate::text::width(row, 1.0)).fold(0.0, f64::max) + 8.0;
+            list.rectangle([0.0, 0.0, 0.0, 0.8], [x, y, box_width, 4.0 + 12.0 * lines.len() as f64], c.transform);
+            for (j, row) in lines.iter().enumerate() {
+                list.text(row, TOOLTIP, x + 4.0, y + 4.0 + 12.0 * j as f64, 1.0, c.transform);
+            }
+        }
+
+        // Ask for a name for a new high score
+        if let (true, Some((score, name))) = (overview, naming) {
+            const ENTRY: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+            let lines = [
+                format!("New high score: {:.0}% efficient", score * 100.0),
+                format!("Name: {}_", name),
+                "Press Enter to save".to_string(),
+            ];
+            list.rectangle([0.0, 0.0, 0.0, 0.8], rectangle::centered([width / 2.0, height / 2.0, 170.0, 40.0]), c.transform);
+            for (i, row) in lines.iter().enumerate() {
+                let x = (width - crate::text::width(row, 1.0)) / 2.0;
+                list.text(row, ENTRY, x, height / 2.0 - 25.0 + 20.0 * i as f64, 1.0, c.transform);
+            }
+        }
+
+        if overview && !spawning {
+            let label = "spawning paused";
+            list.text(label, [1.0, 1.0, 1.0, 0.8], width - crate::text::width(label, 1.0) - 10.0, 10.0, 1.0, c.transform);
+        }
+
+        if let (true, Some(name)) = (overview, show_origin) {
+            let label = format!("showing {} satellites", name);
+            list.text(&label, [1.0, 1.0, 1.0, 0.8], width - crate::text::width(&label, 1.0) - 10.0, 20.0, 1.0, c.transform);
+        }
+
+        if overview {
+            hud.draw(&hud_data, c.transform, list);
+        }
+
+        (drawn, culled)
+    }
+
+    fn update(&mut self, args: &UpdateArgs) {
+        // Hold the frame still while it is being annotated
+        if self.annotations.is_some() {
+            return;
+        }
+
+        // Periodically start afresh when running unattended
+        self.since_reset += args.dt;
+        if self.args.kiosk && self.args.kiosk_reset > 0.0 && self.since_reset >= self.args.kiosk_reset {
+            self.reset();
+        }
+
+        // Shift the colours new satellites are given with the time of day
+        if let Some(moods) = self.moods.as_ref() {
+            self.sim.params.palette = Some(moods.palette(self.sim.time));
+        }
+
+        // Move the tutorial on once the planet it asks about is hovered over, and end it after its
+        // closing caption
+        if self.hovered_planet().is_some() {
+            self.tutorial_done(Action::Inspect);
+        }
+        if self.tutorial.as_mut().is_some_and(|tutorial| tutorial.update(args.dt)) {
+            self.end_tutorial();
+        }
+
+        // Ease slow motion in or out
+        let scale = match self.slow_motion.as_mut() {
+            Some(slow_motion) => {
+                slow_motion.update(args.dt);
+                slow_motion.scale()
+            },
+            None => 1.0,
+        };
+
+        // Take as many steps as time is sped up by, handling each as it happens
+        self.events.clear();
+        self.step_time = 0.0;
+        for _ in 0..self.args.time_warp {
+            // In real time, step by however long has really passed since the last step
+            let dt = match self.realtime.as_ref() {
+                Some(realtime) => (realtime.started.elapsed().as_secs_f64() - self.sim.time).clamp(0.0, REALTIME_MAX_STEP),
+                None => args.dt * scale,
+            };
+            let started = Instant::now();
+            self.sim.step(dt);
+            self.step_time += started.elapsed().as_secs_f64();
+            self.ghost.update(&self.sim.satellites);
+            let time = self.sim.time;
+
+            // Fire any launches and splits due on the timeline
+            if let Some(timeline) = self.timeline.as_mut() {
+                for cue in timeline.update(time) {
+                    if let Some(launch) = cue.launch {
+                        if let Some((x, y)) = launch.launcher().site(&self.sim.planets) {
+                            let (v_x, v_y) = launch.launcher().velocity();
+                            let id = self.sim.launch(x, y, v_x, v_y, Origin::Script);
+                            if let (Some(id), Some(thrust)) = (id, launch.thrust) {
+                                self.sim.thrust(id, (thrust.x, thrust.y), thrust.duration);
+                            }
+                        }
+                    }
+                    if let Some(count) = cue.split {
+                        self.sim.split(count);
+                    }
+                }
+            }
+
+            self.events.extend(self.sim.events.iter().cloned());
+            for id in self.sim.removed.iter() {
+                eprintln!("Removed satellite {} at {:.2} s, as its position or velocity was no longer a finite number", id, time);
+            }
+
+            // Tally and write out satellites that died during this step
+            for sat in self.sim.satellites.iter_mut().filter(|sat| sat.fate.is_some() && sat.died == time) {
+                self.outcomes.record(sat);
+                if let Some(exporter) = self.trail_export.as_mut() {
+                    exporter.write(sat, time).expect("Could not write trail export");
+                    sat.path = Vec::new();
+                }
+                if let Some(exporter) = self.keyframe_export.as_mut() {
+                    sat.keys.push((time, sat.x, sat.y, sat.z));
+                    exporter.write(sat, time).expect("Could not write keyframe export");
+                    sat.keys = Vec::new();
+                }
+            }
+
+            // Follow the planets for the trail export, and sample positions for the keyframe export
+            if let Some(exporter) = self.trail_export.as_mut() {
+                exporter.record_planets(&self.sim.planets);
+            }
+            if let Some(exporter) = self.keyframe_export.as_mut() {
+                if time >= exporter.next_sample {
+                    for sat in self.sim.satellites.iter_mut().filter(|sat| sat.fate.is_none()) {
+                        sat.keys.push((time, sat.x, sat.y, sat.z));
+                    }
+                    exporter.sample_planets(&self.sim.planets, time);
+                    exporter.next_sample += 1.0 / exporter.rate;
+                }
+            }
+        }
+        let time = self.sim.time;
+        self.recentre();
+
+        // Keep track of the satellite under the mouse, to describe it and light up its trail
+        self.update_hover();
+
+        if let Some((_, shown)) = self.lucky.as_mut() {
+            *shown -= args.dt;
+            if *shown <= 0.0 {
+                self.lucky = None;
+            }
+        }
+
+        // Save what has been recorded so far every so often, in case of a crash
+        if let Some(recorder) = self.input_recorder.as_mut() {
+            recorder.update(self.updates).expect("Could not write input recording");
+        }
+
+        // Look for clusters and resonances once a second
+        self.since_analysis += args.dt;
+        let analyse = self.since_analysis >= 1.0;
+        if analyse {
+            self.since_analysis = 0.0;
+        }
+
+        if (self.show_clusters || self.director.is_some()) && analyse {
+            let points: Vec<(f64, f64)> = self.sim.satellites.iter()
+                .filter(|sat| sat.fate.is_none())
+                .map(|sat| (sat.x, sat.y))
+                .collect();
+            self.clusters = cluster::find_clusters(&points, self.args.cluster_eps, self.args.cluster_min);
+        }
+
+        if self.show_bound && analyse {
+            self.bound = elements::bound_counts(&self.sim.satellites, &self.sim.planets, self.sim.params.gravity_constant);
+        }
+
+        if (self.show_resonances || self.clip_recorder.is_some()) && analyse {
+            let tracks: Vec<(u64, OrbitTrack)> = self.sim.satellites.iter()
+                .filter(|sat| sat.fate.is_none())
+                .filter_map(|sat| sat.track.map(|track| (sat.id, track)))
+                .collect();
+            self.resonances = resonance::find_resonances(&tracks, time);
+
+            for resonance in self.resonances.iter() {
+                if self.known_resonances.insert((resonance.a, resonance.b)) {
+                    let a = self.sim.satellites.iter().find(|sat| sat.id == resonance.a).unwrap();
+                    self.events.push(Event::Resonance {
+                        a: resonance.a,
+                        b: resonance.b,
+                        p: resonance.p,
+                        q: resonance.q,
+                        x: a.x,
+                        y: a.y,
+                    });
+                }
+            }
+        }
+
+        // Summarise outcomes every so often
+        self.since_stats += args.dt;
+        if self.args.stats_log > 0.0 && self.since_stats >= self.args.stats_log {
+            self.since_stats = 0.0;
+            let alive = self.sim.satellites.iter().filter(|sat| sat.fate.is_none()).count();
+            println!("[{}] {}", self.clock.stamp(time), self.outcomes.summary(alive));
+        }
+
+        // Save the bodies every so often, to pick up from after a crash
+        self.since_autosave += args.dt;
+        if let (Some(path), true) = (self.args.autosave.as_ref(), self.since_autosave >= AUTOSAVE_EVERY) {
+            self.since_autosave = 0.0;
+            if let Err(e) = Autosave::capture(&self.sim).save(path) {
+                eprintln!("{}", e);
+            }
+        }
+
+        if let Some(slow_motion) = self.slow_motion.as_mut() {
+            slow_motion.notice(&self.events);
+        }
+        if let Some(director) = self.director.as_mut() {
+            director.update(args.dt, &self.sim.satellites, &self.sim.planets, &self.clusters, &self.events);
+        }
+
+        if let Some(shots) = self.event_shots.as_mut() {
+            shots.notice(time, &self.events, self.sim.satellites.iter().filter(|sat| sat.fate.is_none()).count());
+        }
+
+        // Save highlight clips around interesting events, though not the many merges
+        if let Some(recorder) = self.clip_recorder.as_mut() {
+            for event in self.events.iter().filter(|event| !matches!(event, Event::Merge { .. })) {
+                let (x, y) = event.position();
+                println!("[{:.1}s] {} at ({:.0}, {:.0})", time, event, x, y);
+                recorder.trigger(*event, time);
+            }
+            recorder.record(time, &self.sim.satellites, &self.sim.planets, self.sim.params.width, self.sim.params.height).expect("Could not save highlight");
+        }
+
+        self.hud.update(&HudData {
+            fps: self.fps,
+            time,
+            satellites: &self.sim.satellites,
+            planets: &self.sim.planets,
+            gravity_constant: self.sim.params.gravity_constant,
+            events: &self.events,
+            outcomes: &self.outcomes,
+            bodies: self.bodies,
+            step_time: self.step_time,
+            trails: (self.sim.trails.reused, self.sim.trails.allocated),
+            bound: self.sim.bound_counts(),
+            clock: &self.clock,
+            quotas: &self.sim.quotas,
+        });
+    }
+
+    // Exports the trails and samples of all satellites that are still alive
+    fn export_live(&mut self) {
+        if let Some(exporter) = self.trail_export.as_mut() {
+            for sat in self.sim.satellites.iter().filter(|sat| sat.fate.is_none()) {
+                exporter.write(sat, self.sim.time).expect("Could not write trail export");
+            }
+        }
+        if let Some(exporter) = self.keyframe_export.as_mut() {
+            for sat in self.sim.satellites.iter().filter(|sat| sat.fate.is_none()) {
+                exporter.write(sat, self.sim.time).expect("Could not write keyframe export");
+            }
+        }
+    }
+
+    // Writes out anything still pending before exit
+    fn finish(&mut self) {
+        self.export_live();
+        let (bound, unbound) = self.sim.bound_counts();
+        println!("{}", self.outcomes.totals(bound, unbound));
+        if let Some(recorder) = self.input_recorder.take() {
+            recorder.finish(self.updates).expect("Could not write input recording");
+        }
+        if let Some(exporter) = self.trail_export.take() {
+            exporter.finish().expect("Could not write trail export");
+        }
+        if let Some(exporter) = self.keyframe_export.take() {
+            exporter.finish().expect("Could not write keyframe export");
+        }
+    }
+
+    fn tutorial_done(&mut self, action: Action) {
+        if let Some(tutorial) = self.tutorial.as_mut() {
+            tutorial.done(action);
+        }
+    }
+
+    // Finishes or skips the tutorial, remembering that it has been seen
+    fn end_tutorial(&mut self) {
+        if self.tutorial.take().is_some() {
+            if let Err(e) = Tutorial::mark_seen() {
+                eprintln!("Could not remember the tutorial has been seen: {}", e);
+            }
+        }
+    }
+
+    // Clears all satellites and creates a fresh set of planets
+    fn reset(&mut self) {
+        self.export_live();
+        self.sim.reset();
+        if let Some(realtime) = self.realtime.as_ref() {
+            self.sim.pass_time(realtime.offset + self.sim.time);
+        }
+        self.since_reset = 0.0;
+        if let Some(timeline) = self.timeline.as_mut() {
+            timeline.restart(self.sim.time);
+        }
+    }
+
+    // Handles input from the keyboard and mouse, live or replayed, recording it if asked to
+    fn input(&mut self, input: &Input, window: &mut Window) {
+        if let Some(recorder) = self.input_recorder.as_mut() {
+            recorder.record(self.updates, input);
+        }
+        if let (false, Some(director)) = (self.args.kiosk, self.director.as_mut()) {
+            director.interrupt();
+        }
+        match input {
+            Input::KeyPress(key) => self.key_press(*key, window),
+            Input::KeyRelease(key) => self.key_release(*key),
+            Input::Text(text) => self.text(text),
+            Input::MouseMove(pos) => self.mouse_move(*pos),
+            Input::MousePress(button) => self.mouse_press(*button),
+            Input::MouseRelease(button) => self.mouse_release(*button),
+            Input::Resize(size) => self.resize(*size),
+        }
+    }
+
+    // Fits the world to the main window's new size. An extended world takes the window's size, moving
+    // the edges satellites escape past while the planets keep their places; any other keeps its own
+    // size and is only scaled to the window.
+    fn resize(&mut self, size: [f64; 2]) {
+        let (width, height) = (size[0] / self.ui_zoom, size[1] / self.ui_zoom);
+        // Minimised windows shrink to nothing
+        if width < 1.0 || height < 1.0 {
+            return;
+        }
+        if self.args.aspect == Aspect::Extend {
+            self.sim.params.width = width;
+            self.sim.params.height = height;
+            if self.sim.density.is_some() {
+                self.sim.density = Some(Density::new(width, height));
+            }
+        }
+        self.fit = Fit::new(self.args.aspect, self.sim.params.width, self.sim.params.height, width, height);
+        self.recentre();
+    }
+
+    // Moves the view of the main window to keep the barycentre of the system at the centre of the
+    // world, when following it. The mouse stays where it is in the window, so the point of the world
+    // under it moves along with the view.
+    fn recentre(&mut self) {
+        // The rotating frame already holds the barycentre of its pair of planets still
+        let turning = self.rotating_view && self.sim.co_rotation.now().is_some();
+        let shift = match (self.barycentric && !turning, self.sim.barycentre()) {
+            (true, Some((x, y))) => [x - self.sim.params.width / 2.0, y - self.sim.params.height / 2.0],
+            _ => [0.0, 0.0],
+        };
+        let fit = self.fit.shifted(shift);
+        self.cursor = fit.to_world(self.fit.to_window(self.cursor));
+        self.fit = fit;
+    }
+
+    fn key_press(&mut self, key: Key, window: &mut Window) {
+        self.modifiers.set(key, true);
+
+        if self.args.kiosk {
+            // Only the quit combination is honoured in kiosk mode
+            if self.args.kiosk_quit == KioskQuit::Combo && key == Key::Q && self.modifiers.ctrl && self.modifiers.alt {
+                window.set_should_close(true);
+            }
+            return;
+        }
+
+        // While a high score is being named, keys only edit the name
+        if let Some((score, name)) = self.naming.as_mut() {
+            match key {
+                Key::Return => {
+                    let name = if name.is_empty() { "anonymous" } else { name.as_str() };
+                    self.scores.add(scores::LAUNCH, name, *score);
+                    if let Err(e) = self.scores.save() {
+                        eprintln!("Could not save high scores: {}", e);
+                    }
+                    self.naming = None;
+                },
+                Key::Backspace => {
+                    name.pop();
+                },
+                _ => {}
+            }
+            return;
+        }
+
+        // While annotating, Enter finishes a label and Backspace undoes, and any other key goes into
+        // the label being typed, if there is one
+        if let Some(annotations) = self.annotations.as_mut() {
+            match key {
+                Key::Return => {
+                    annotations.finish();
+                    return;
+                },
+                Key::Backspace => {
+                    annotations.erase();
+                    return;
+                },
+                _ if annotations.typing => return,
+                Key::E => {
+                    annotations.export = true;
+                    return;
+                },
+                _ => {}
+            }
+        }
+
+        match key {
+            Key::A => {
+                // Freeze the frame to annotate it, or throw the annotations away and carry on
+                self.annotations = match self.annotations {
+                    Some(_) => None,
+                    None => Some(Annotations::default()),
+                };
+            },
+            Key::C if self.modifiers.ctrl => {
+                // Share the planets and tuning as a code
+                self.share();
+            },
+            Key::C => {
+                // Toggle cluster highlighting
+                self.show_clusters = !self.show_clusters;
+                self.clusters.clear();
+                self.since_analysis = 1.0;
+            },
+            Key::H => {
+                // Toggle the HUD
+                self.hud.visible = !self.hud.visible;
+            },
+            Key::V if self.modifiers.ctrl => {
+                // Add the satellites described on the clipboard
+                self.paste();
+            },
+            Key::V => {
+                // Cycle what satellites are coloured by
+                self.color_mode = self.color_mode.next();
+            },
+            Key::Z => {
+                // Toggle zero-velocity curves
+                self.show_zero_velocity = !self.show_zero_velocity;
+            },
+            Key::K => {
+                // Toggle the Lagrange point markers
+                self.show_lagrange = !self.show_lagrange;
+            },
+            Key::Y => {
+                // Turn the view of a three-dimensional world, the other way with Shift
+                if let Some(tilt) = self.tilt.as_mut() {
+                    tilt.turn(self.modifiers.shift);
+                }
+            },
+            Key::U => {
+                // Tip the view of a three-dimensional world over, or back with Shift
+                if let Some(tilt) = self.tilt.as_mut() {
+                    tilt.tip(self.modifiers.shift);
+                }
+            },
+            Key::F => {
+                // Toggle viewing from the rotating frame
+                self.rotating_view = !self.rotating_view;
+            },
+            Key::J => {
+                // Toggle keeping the barycentre centred
+                self.barycentric = !self.barycentric;
+                self.recentre();
+            },
+            Key::LeftBracket => {
+                self.jacobi_level = (self.jacobi_level - 0.01).max(JACOBI_LEVEL_MIN);
+            },
+            Key::RightBracket => {
+                self.jacobi_level = (self.jacobi_level + 0.01).min(JACOBI_LEVEL_MAX);
+            },
+            Key::L => {
+                // Toggle the launch tool, starting from the planet nearest the cursor
+                self.launcher = match self.launcher {
+                    Some(_) => None,
+                    None => Launcher::pick(self.cursor[0], self.cursor[1], &self.sim.planets, self.sim.params.gravity_constant, self.sim.params.sat_radius),
+                };
+                self.aiming = false;
+                if self.launcher.is_some() {
+                    self.tutorial_done(Action::LaunchTool);
+                }
+            },
+            Key::Left | Key::Right | Key::Up | Key::Down if self.modifiers.shift => {
+                // Fire the engine of the last satellite launched by hand
+                self.engine.insert(key);
+                self.fire_engine();
+            },
+            Key::Left | Key::Right | Key::Up | Key::Down => {
+                // Adjust the launch angle and speed
+                if let Some(launcher) = self.launcher.as_mut() {
+                    match key {
+                        Key::Left => launcher.angle = (launcher.angle + 1.0).min(180.0),
+                        Key::Right => launcher.angle = (launcher.angle - 1.0).max(0.0),
+                        Key::Up => launcher.speed += 5.0,
+                        _ => launcher.speed = (launcher.speed - 5.0).max(0.0),
+                    }
+                }
+            },
+            Key::Space => {
+                // Fire from the launch site, unless hand-launched satellites are over quota
+                if let Some((launcher, _)) = self.aimed() {
+                    if let Some((x, y)) = launcher.site(&self.sim.planets) {
+                        let (v_x, v_y) = launcher.velocity();
+                        let id = match self.sim.launch(x, y, v_x, v_y, Origin::User) {
+                            Some(id) => id,
+                            None => return,
+                        };
+                        self.piloted = Some(id);
+                        self.tutorial_done(Action::Launch);
+
+                        // Score launches that stay up by how little speed they needed to get as high
+                        let path = self.sim.predict(x, y, v_x, v_y, PREDICTION_STEPS, PREDICTION_DT);
+                        let &(end_x, end_y) = path.last().unwrap();
+                        let mut score = None;
+                        if !hits_planet(end_x, end_y, &self.sim.planets, self.sim.params.sat_radius) {
+                            let minimum = launcher.minimum_speed(&self.sim.planets, self.sim.params.gravity_constant);
+                            score = minimum.map(|minimum| (minimum / launcher.speed).min(1.0));
+                        }
+                        if let Some(score) = score {
+                            self.score = Some(score);
+                            if self.scores.qualifies(scores::LAUNCH, score) {
+                                self.naming = Some((score, String::new()));
+                            }
+                        }
+                        self.ghost.launched(id, x, y, score);
+                    }
+                }
+            },
+            Key::O => {
+                // Toggle snapping launches onto nice orbits
+                self.snap = !self.snap;
+            },
+            Key::T => {
+                // Toggle drawing satellites as comets
+                self.comets = !self.comets;
+            },
+            Key::I => {
+                // Toggle glowing trails
+                self.glow = !self.glow;
+            },
+            Key::M => {
+                // Toggle the centre of mass and dispersion markers
+                self.show_swarm = !self.show_swarm;
+            },
+            Key::B => {
+                // Toggle the rings counting satellites bound to each planet
+                self.show_bound = !self.show_bound;
+                self.bound.clear();
+                self.since_analysis = 1.0;
+            },
+            Key::G => {
+                // Cycle which origin of satellites is shown, from all of them through each in turn
+                let next = match self.show_origin {
+                    None => 0,
+                    Some(name) => Origin::NAMES.iter().position(|n| *n == name).unwrap() + 1,
+                };
+                self.show_origin = Origin::NAMES.get(next).copied();
+            },
+            Key::P => {
+                // Feeling lucky: start over with a fresh pick of tuning, printed so it can be saved
+                let tuning = Tuning::lucky(&mut self.sim.rng);
+                tuning.apply(&mut self.sim.params);
+                self.reset();
+                println!("Feeling lucky, save this as a scene to keep it:\n{}", tuning.scene());
+                self.lucky = Some((tuning, LUCKY_SHOWN));
+            },
+            Key::W => {
+                // Toggle the wall tool, printing the walls as a scene on the way out so they can be saved
+                self.drawing_walls = !self.drawing_walls;
+                self.wall_start = None;
+                if !self.drawing_walls && !self.sim.walls.is_empty() {
+                    println!("Walls, save this as a scene to keep them:\n{}", wall::scene(&self.sim.walls));
+                }
+            },
+            Key::Backspace if self.drawing_walls => {
+                // Take back the last wall drawn
+                self.sim.walls.pop();
+            },
+            Key::X => {
+                // Firework: split every satellite into pieces
+                self.sim.split(self.args.fragments);
+            },
+            Key::N => {
+                // Pause or resume all spawning
+                self.sim.spawning = !self.sim.spawning;
+                if !self.sim.spawning {
+                    self.tutorial_done(Action::Pause);
+                }
+            },
+            Key::Tab => {
+                // Skip the tutorial
+                self.end_tutorial();
+            },
+            Key::R => {
+                // Toggle resonance annotations
+                self.show_resonances = !self.show_resonances;
+                self.resonances.clear();
+                self.since_analysis = 1.0;
+            },
+            _ => {}
+        }
+    }
+
+    // Prints a share code for the planets and tuning, copying it to the clipboard too if there is a
+    // clipboard program to copy it with
+    fn share(&self) {
+        let code = ShareCode::capture(&self.sim).encode();
+        println!("Share code, start from it with --from_code:\n{}", code);
+        if let Err(e) = clipboard::copy(&code) {
+            eprintln!("Could not copy the share code: {}", e);
+        }
+    }
+
+    // Adds satellites from a JSON snippet on the clipboard, as hand-launched ones, reporting anything
+    // that goes wrong rather than stopping
+    fn paste(&mut self) {
+        let bodies = match clipboard::paste().map_err(|e| e.to_string()).and_then(|text| clipboard::parse(&text)) {
+            Ok(bodies) => bodies,
+            Err(e) => {
+                eprintln!("Could not paste satellites: {}", e);
+                return;
+            },
+        };
+        let mut added = 0;
+        for body in bodies.iter() {
+            let color = body.color().unwrap_or_else(|| self.sim.sat_color());
+            if let Some(id) = self.sim.add_satellite(color, body.x, body.y, body.v_x, body.v_y, Origin::User) {
+                self.piloted = Some(id);
+                added += 1;
+            }
+        }
+        println!("Pasted {} of {} satellites", added, bodies.len());
+    }
+
+    // Where bodies are drawn in the main window relative to where they are in the simulation
+    fn placed(&self) -> math::Matrix2d {
+        let tilted = match self.tilt {
+            Some(tilt) => tilt.transform(math::identity(), self.sim.params.width / 2.0, self.sim.params.height / 2.0),
+            None => math::identity(),
+        };
+        match self.sim.co_rotation.now() {
+            Some(pose) if self.rotating_view => co_rotated(tilted, self.sim.co_rotation.origin(), pose),
+            _ => tilted,
+        }
+    }
+
+    // Where a satellite is drawn in the main window, through `placed`
+    fn lifted(&self, sat: &Satellite) -> [f64; 2] {
+        match self.tilt {
+            Some(tilt) => {
+                let turned = match self.sim.co_rotation.now() {
+                    Some(pose) if self.rotating_view => -pose.angle,
+                    _ => 0.0,
+                };
+                let (x, y) = tilt.lift(sat.x, sat.y, sat.z, turned);
+                [x, y]
+            },
+            None => [sat.x, sat.y],
+        }
+    }
+
+    // Planet under the mouse in the main window, as drawn there
+    fn hovered_planet(&self) -> Option<usize> {
+        let placed = self.placed();
+        self.sim.planets.iter().position(|planet| {
+            let [x, y] = math::transform_pos(placed, [planet.x, planet.y]);
+            (x - self.cursor[0]).hypot(y - self.cursor[1]) <= planet.radius + PICK_MARGIN
+        })
+    }
+
+    // Id of the live satellite nearest the mouse in the main window, if it is close enough to pick
+    fn hovered_satellite(&self) -> Option<u64> {
+        let placed = self.placed();
+        let bodies = self.sim.satellites.iter()
+            .enumerate()
+            .filter(|(_, sat)| sat.fate.is_none())
+            .map(|(i, sat)| {
+                let [x, y] = math::transform_pos(placed, self.lifted(sat));
+                (i, x, y, sat.radius)
+            })
+            .collect();
+        let index = pick::Index::build(bodies, PICK_MARGIN);
+        index.nearest(self.cursor[0], self.cursor[1]).map(|i| self.sim.satellites[i].id)
+    }
+
+    // Looks again for the satellite under the mouse once the mouse has moved, or once the one it was
+    // over has died or drifted out from under it
+    fn update_hover(&mut self) {
+        let placed = self.placed();
+        let still_over = self.hover.is_some_and(|id| {
+            self.sim.satellites.iter().find(|sat| sat.id == id).is_some_and(|sat| {
+                let [x, y] = math::transform_pos(placed, self.lifted(sat));
+                sat.fate.is_none() && (x - self.cursor[0]).hypot(y - self.cursor[1]) - sat.radius <= PICK_MARGIN
+            })
+        });
+        if self.cursor_moved || (self.hover.is_some() && !still_over) {
+            self.hover = match self.hovered_planet() {
+                Some(_) => None,
+                None => self.hovered_satellite(),
+            };
+            self.cursor_moved = false;
+        }
+    }
+
+    // The launch tool's aim, snapped onto a nice orbit if the snap assist is on and it is close enough,
+    // with whether it was snapped
+    fn aimed(&self) -> Option<(Launcher, bool)> {
+        let launcher = self.launcher?;
+        if self.snap {
+            if let Some(snapped) = launcher.snapped(&self.sim.planets, self.sim.params.gravity_constant, self.args.snap_eccentricity) {
+                return Some((snapped, true));
+            }
+        }
+        Some((launcher, false))
+    }
+
+    // Typed text, which goes into the name of a new high score or the label being annotated
+    fn text(&mut self, text: &str) {
+        if let Some(annotations) = self.annotations.as_mut() {
+            annotations.type_text(text);
+        }
+        if let Some((_, name)) = self.naming.as_mut() {
+            for c in text.chars().filter(|c| !c.is_control()) {
+                if name.chars().count() < scores::NAME_LENGTH {
+                    name.push(c);
+                }
+            }
+        }
+    }
+
+    fn key_release(&mut self, key: Key) {
+        self.modifiers.set(key, false);
+        if self.engine.remove(&key) {
+            self.fire_engine();
+        }
+    }
+
+    // Points the engine of the piloted satellite whichever way the held arrow keys add up to, or
+    // stops it once none are held
+    fn fire_engine(&mut self) {
+        let id = match self.piloted {
+            Some(id) => id,
+            None => return,
+        };
+        let (mut a_x, mut a_y) = (0.0, 0.0);
+        for key in self.engine.iter() {
+            match key {
+                Key::Left => a_x -= PILOT_THRUST,
+                Key::Right => a_x += PILOT_THRUST,
+                Key::Up => a_y -= PILOT_THRUST,
+                _ => a_y += PILOT_THRUST,
+            }
+        }
+        let duration = if self.engine.is_empty() { 0.0 } else { f64::INFINITY };
+        if !self.sim.thrust(id, (a_x, a_y), duration) {
+            self.piloted = None;
+        }
+    }
+
+    fn mouse_move(&mut self, pos: [f64; 2]) {
+        self.cursor = self.fit.to_world([pos[0] / self.ui_zoom, pos[1] / self.ui_zoom]);
+        self.cursor_moved = true;
+        if self.dragging {
+            self.drag_slider();
+        }
+        if let (true, Some(launcher)) = (self.aiming, self.launcher.as_mut()) {
+            launcher.aim(self.cursor[0], self.cursor[1], &self.sim.planets);
+        }
+    }
 
-use fps_counter::FPSCounter;
+    fn mouse_press(&mut self, button: MouseButton) {
+        if self.args.kiosk || button != MouseButton::Left {
+            return;
+        }
+        // Start an arrow or a label on the frozen frame
+        if let Some(annotations) = self.annotations.as_mut() {
+            annotations.press(self.cursor);
+            return;
+        }
+        // Start dragging out a wall
+        if self.drawing_walls {
+            self.wall_start = Some(self.cursor);
+            return;
+        }
+        // Grab the slider anywhere near its track
+        let track = slider_track(self.fit.window[0], self.fit.window[1]);
+        let pointer = self.fit.to_window(self.cursor);
+        if self.show_zero_velocity && pointer[0] >= track[0] - 5.0 && pointer[0] <= track[0] + track[2] + 5.0 && (pointer[1] - track[1]).abs() <= 8.0 {
+            self.dragging = true;
+            self.drag_slider();
+            return;
+        }
+        // Switch an emitter on or off by clicking it
+        let cursor = self.cursor;
+        if let Some(emitter) = self.sim.emitters.iter_mut().find(|emitter| (emitter.x - cursor[0]).hypot(emitter.y - cursor[1]) <= EMITTER_CLICK_RADIUS) {
+            emitter.enabled = !emitter.enabled;
+            return;
+        }
+        // Move the launch site to the surface nearest the cursor, keeping the aim until dragged
+        if let Some(launcher) = self.launcher.as_mut() {
+            if let Some(picked) = Launcher::pick(self.cursor[0], self.cursor[1], &self.sim.planets, self.sim.params.gravity_constant, self.sim.params.sat_radius) {
+                launcher.planet = picked.planet;
+                launcher.bearing = picked.bearing;
+            }
+            self.aiming = true;
+        }
+    }
 
+    fn mouse_release(&mut self, button: MouseButton) {
+        if button == MouseButton::Left {
+            if let Some(annotations) = self.annotations.as_mut() {
+                annotations.release(self.cursor);
+            }
+            self.dragging = false;
+            self.aiming = false;
 
-struct Planet {
-    color: [f32; 4],
-    mass: f64,
-    radius: f64,
-    x: f64,
-    y: f64,
-}
+            // Finish the wall being dragged out, unless it is too short to have been meant
+            if let Some(start) = self.wall_start.take() {
+                if (self.cursor[0] - start[0]).hypot(self.cursor[1] - start[1]) >= MIN_WALL_LENGTH {
+                    self.sim.walls.push(Wall {
+                        x1: start[0],
+                        y1: start[1],
+                        x2: self.cursor[0],
+                        y2: self.cursor[1],
+                        kind: WallKind::Bounce,
+                    });
+                }
+            }
+        }
+    }
 
-struct Satellite {
-    color: [f32; 4],
-    radius: f64,
-    dead: bool,
-    x: f64,
-    y: f64,
-    v_x: f64,
-    v_y: f64,
-    trail: VecDeque<(f64, f64)>,
+    // Sets the Jacobi level from where the cursor is along the slider
+    fn drag_slider(&mut self) {
+        let track = slider_track(self.fit.window[0], self.fit.window[1]);
+        let t = ((self.fit.to_window(self.cursor)[0] - track[0]) / track[2]).clamp(0.0, 1.0);
+        self.jacobi_level = JACOBI_LEVEL_MIN + t * (JACOBI_LEVEL_MAX - JACOBI_LEVEL_MIN);
+    }
 }
 
-struct Args {
-    title: String,         // Window title
-    width: f64,            // Viewport width
-    height: f64,           // Viewport height
-    add_chance: f64,       // Chance to add a satellite each frame
-    sat_radius: f64,       // Radius (in px) of each satellite
-    sat_velocity: f64,     // Initial velocity (in px/s) of each satellite
-    gravity_constant: f64, // 'G' constant used to update velocities
-    trail_length: usize,   // Trail length, measured in number of frames of history
-}
+fn main() {
+    // Parse command-line arguments
+    let yaml = load_yaml!("cli.yml");
+    let matches = ClapApp::from_yaml(yaml).get_matches();
 
-// Returns true if the point with given radius is outside the window, for given window size
-fn outside(x: f64, y: f64, radius: f64, width: f64, height: f64) -> bool {
-    (x + radius < 0.0)
-    | (y + radius < 0.0)
-    | (x - radius > width)
-    | (y - radius > height)
-}
+    if matches.is_present("show_scores") {
+        println!("{}", Scores::load().expect("Could not read high scores").table());
+        return;
+    }
 
-// Returns a random color
-fn random_color(rng: &mut ThreadRng) -> [f32; 4] {
-    [rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0), 1.0]
-}
+    if let Some(path) = matches.value_of("repair_input") {
+        match input::repair(path) {
+            Ok(summary) => println!("Repaired {}: {}", path, summary),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            },
+        }
+        return;
+    }
 
+    if let Some(path) = matches.value_of("animate") {
+        let output = match matches.value_of("animate_to") {
+            Some(s) => s.to_string(),
+            None => std::path::Path::new(path).with_extension("svg").to_string_lossy().into_owned(),
+        };
+        match animate::convert(path, &output) {
+            Ok(summary) => println!("Animated {} into {}", summary, output),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            },
+        }
+        return;
+    }
 
-pub struct App {
-    gl: GlGraphics,              // OpenGL drawing backend
-    rng: ThreadRng,              // Random number generator
-    fps_counter: FPSCounter,     // FPS counter
-    planets: Vec<Planet>,        // Data for planets
-    satellites: Vec<Satellite>,  // Data for satellites
-    args: Args,                  // Any other useful arguments
-}
+    let autosave: Option<String> = matches.value_of("autosave").map(String::from);
+    if let Some(s) = matches.value_of("supervise") {
+        let timeout: f64 = s.parse().expect("Supervise must be a number of seconds");
+        if timeout <= 0.0 {
+            panic!("Supervise must be a positive number of seconds");
+        }
+        supervise::run(timeout, autosave.as_deref()).expect("Could not supervise orbits");
+        return;
+    }
 
-impl App {
-    fn render(&mut self, args: &RenderArgs, window: &mut Window) {
-        let fps = self.fps_counter.tick();
-        window.set_title(format!("{} ({} fps)", self.args.title, fps));
+    let code: Option<ShareCode> = matches.value_of("from_code").map(|s| ShareCode::decode(s).unwrap_or_else(|e| panic!("{}", e)));
+    let scene: Scene = match matches.value_of("scene") {
+        Some(path) => Scene::load(path).unwrap_or_else(|e| panic!("{}", e)),
+        None => Scene::default(),
+    };
+    let mut clock = Clock::new(scene.clock.as_ref()).unwrap_or_else(|e| panic!("{}", e));
+    let realtime: bool = matches.is_present("realtime");
+    if realtime && scene.clock.is_none() {
+        panic!("Real-time mode needs a scene with a clock epoch");
+    }
 
-        use graphics::*;
+    // Scenes are laid out in a world of their own size, which the window opens at unless fullscreen
+    let (design_width, design_height) = match code.as_ref() {
+        Some(code) => (code.width, code.height),
+        None => scene.size.map_or((800.0, 800.0), |size| (size.width, size.height)),
+    };
+    if design_width < 1.0 || design_height < 1.0 {
+        panic!("Scene size must be at least 1x1");
+    }
+    if scene.repulsors.iter().any(|repulsor| repulsor.mass >= 0.0 || repulsor.radius <= 0.0) {
+        panic!("Repulsors must have negative mass and a positive radius");
+    }
+    for asteroid in scene.asteroids.iter() {
+        asteroid.validate().unwrap_or_else(|e| panic!("{}", e));
+    }
+    if scene.portals.iter().any(|portal| portal.a.radius <= 0.0 || portal.b.radius <= 0.0) {
+        panic!("Portal mouths must have a positive radius");
+    }
 
-        const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+    let fullscreen: bool = matches.is_present("fullscreen");
+    let aspect: Aspect = match matches.value_of("aspect") {
+        Some("extend") => Aspect::Extend,
+        Some("letterbox") | None => Aspect::Letterbox,
+        Some("crop") => Aspect::Crop,
+        Some(_) => panic!("Aspect must be one of: extend, letterbox, crop"),
+    };
+    let ui_scale: Option<f64> = matches.value_of("ui_scale").map(|s| s.parse().expect("UI scale must be a number"));
+    if ui_scale.is_some_and(|scale| scale <= 0.0) {
+        panic!("UI scale must be positive");
+    }
+    let annotations = matches.value_of("annotations").unwrap_or("annotations").to_string();
+    let overlay: bool = matches.is_present("overlay");
+    let extra_view: Option<Camera> = matches.value_of("extra_view").map(|s| Camera::parse(s).unwrap_or_else(|e| panic!("{}", e)));
+    let kiosk: bool = matches.is_present("kiosk");
 
-        let planets_iter = self.planets.iter();
-        let satellites_iter = self.satellites.iter();
+    let kiosk_quit: KioskQuit = match matches.value_of("kiosk_quit") {
+        Some("combo") | None => KioskQuit::Combo,
+        Some("signal") => KioskQuit::Signal,
+        Some(_) => panic!("Kiosk_quit must be one of: combo, signal"),
+    };
 
-        self.gl.draw(args.viewport(), |c, gl| {
-            // Clear the screen.
-            clear(BLACK, gl);
+    let trail_export: Option<&str> = matches.value_of("export_trails");
+    let keyframe_export: Option<&str> = matches.value_of("export_keyframes");
 
-            // Draw planets
-            for planet in planets_iter {
-                let rect = rectangle::rectangle_by_corners(planet.x - planet.radius, planet.y - planet.radius, planet.x + planet.radius, planet.y + planet.radius);
-                ellipse(planet.color, rect, c.transform, gl);
-            }
+    let keyframe_rate: f64 = match matches.value_of("keyframe_rate") {
+        Some(s) => s.parse().expect("Keyframe rate must be a number"),
+        None => 30.0,
+    };
+    if !keyframe_rate.is_finite() || keyframe_rate <= 0.0 {
+        panic!("Keyframe rate must be a positive number");
+    }
 
-            // Draw satellites
-            for satellite in satellites_iter {
-                // Draw trail
-                if satellite.trail.len() > 1 {
-                    let mut pos_old = satellite.trail[0];
-                    for pos in satellite.trail.iter().skip(1) {
-                        line(satellite.color, 1.0, [pos.0, pos.1, pos_old.0, pos_old.1], c.transform, gl);
-                        pos_old = *pos;
-                    }
-                }
+    let capture: Option<&str> = matches.value_of("capture");
+    let screenshot_on: Option<Vec<String>> = matches.value_of("screenshot_on").map(|s| s.split(',').map(|trigger| trigger.trim().to_string()).collect());
+    if let Some(trigger) = screenshot_on.iter().flatten().find(|trigger| !capture::TRIGGERS.contains(&trigger.as_str())) {
+        panic!("Unknown screenshot trigger {}, must be among: {}", trigger, capture::TRIGGERS.join(", "));
+    }
+    let screenshot_dir: &str = matches.value_of("screenshot_dir").unwrap_or("screenshots");
+    let screenshot_limit: usize = match matches.value_of("screenshot_limit") {
+        Some(s) => s.parse().expect("Screenshot limit must be a whole number"),
+        None => 1,
+    };
 
-                // Draw satellite
-                if !satellite.dead {
-                    let rect = rectangle::rectangle_by_corners(satellite.x - satellite.radius, satellite.y - satellite.radius, satellite.x + satellite.radius, satellite.y + satellite.radius);
-                    ellipse(satellite.color, rect, c.transform, gl);
-                }
-            }
-        });
+    let capture_sim_fps: f64 = match matches.value_of("capture_sim_fps") {
+        Some(s) => s.parse().expect("Capture sim fps must be a number"),
+        None => 30.0,
+    };
+    if !capture_sim_fps.is_finite() || capture_sim_fps <= 0.0 {
+        panic!("Capture sim fps must be a positive number");
     }
 
-    fn update(&mut self, args: &UpdateArgs) {
-        let width = self.args.width;
-        let height = self.args.height;
-
-        // Chance to add a new satellite
-        // TODO make dependent on args.dt
-        let c: f64 = self.rng.gen_range(0.0..1.0);
-        if c < self.args.add_chance {
-            // Add new satellite
-            let color: [f32; 4] = random_color(&mut self.rng);
-            let x: f64 = self.rng.gen_range(0.0..width);
-            let y: f64 = self.rng.gen_range(0.0..height);
-            let angle: f64 = self.rng.gen_range(0.0..2.0 * std::f64::consts::PI);
-            let v_x: f64 = self.args.sat_velocity * angle.cos();
-            let v_y: f64 = self.args.sat_velocity * angle.sin();
-            let sat = Satellite {
-                color,
-                radius: self.args.sat_radius,
-                dead: false,
-                x,
-                y,
-                v_x,
-                v_y,
-                trail: VecDeque::new(),
-            };
-            self.satellites.push(sat);
-        }
+    let show_clusters: bool = matches.is_present("clusters");
+    let show_resonances: bool = matches.is_present("resonances");
 
+    let color_mode: ColorMode = match matches.value_of("color_by") {
+        Some("random") | None => ColorMode::Random,
+        Some("stability") => ColorMode::Stability,
+        Some("jacobi") => ColorMode::Jacobi,
+        Some(_) => panic!("Color_by must be one of: random, stability, jacobi"),
+    };
 
-        // Update satellites
-        for sat in self.satellites.iter_mut() {
-            // Update velocities
-            for planet in self.planets.iter() {
-                let distance_x = sat.x - planet.x;
-                let distance_y = sat.y - planet.y;
-                let distance_sq = (distance_x * distance_x) + (distance_y * distance_y);
-                let delta_velocity = (self.args.gravity_constant * planet.mass * args.dt) / (distance_sq);
-                let angle = distance_y.atan2(distance_x);
-                sat.v_x += -1.0 * delta_velocity * angle.cos();
-                sat.v_y += -1.0 * delta_velocity * angle.sin();
-            }
+    let stability_orbits: f64 = match matches.value_of("stability_orbits") {
+        Some(s) => s.parse().expect("Stability orbits must be a number"),
+        None => 5.0,
+    };
 
-            // Update positions
-            sat.x += sat.v_x * args.dt;
-            sat.y += sat.v_y * args.dt;
+    let cluster_eps: f64 = match matches.value_of("cluster_eps") {
+        Some(s) => s.parse().expect("Cluster eps must be a number"),
+        None => 30.0,
+    };
 
-            // Update trails
-            if !sat.dead {
-                sat.trail.push_back((sat.x, sat.y));
-            }
-            if (sat.trail.len() > self.args.trail_length) | sat.dead {
-                sat.trail.pop_front();
-            }
-        }
+    let cluster_min: usize = match matches.value_of("cluster_min") {
+        Some(s) => s.parse().expect("Cluster min must be an integer"),
+        None => 4,
+    };
 
-        // Destroy satellites if they pass outside the screen or hit a planet
-        let planets = &(self.planets);
-        for sat in self.satellites.iter_mut() {
-            sat.dead = sat.dead
-                | outside(sat.x, sat.y, sat.radius, width, height)
-                | planets.iter().any(|planet| {
-                    let distance_x = sat.x - planet.x;
-                    let distance_y = sat.y - planet.y;
-                    let distance_sq = (distance_x * distance_x) + (distance_y * distance_y);
-                    distance_sq.sqrt() < sat.radius + planet.radius
-                });
-        }
-        self.satellites.retain(|sat| !sat.dead | (sat.trail.len() > 0));
-    }
-}
+    let highlights: Option<&str> = matches.value_of("highlights");
 
-fn main() {
-    // Parse command-line arguments
-    let yaml = load_yaml!("cli.yml");
-    let matches = ClapApp::from_yaml(yaml).get_matches();
+    let clip_length: f64 = match matches.value_of("clip_length") {
+        Some(s) => s.parse().expect("Clip length must be a number"),
+        None => 20.0,
+    };
+
+    let stats_log: f64 = match matches.value_of("stats_log") {
+        Some(s) => s.parse().expect("Stats log must be a number"),
+        None => 0.0,
+    };
+
+    let snap_eccentricity: f64 = match matches.value_of("snap_eccentricity") {
+        Some(s) => s.parse().expect("Snap eccentricity must be a number"),
+        None => 0.0,
+    };
+    if !(0.0..1.0).contains(&snap_eccentricity) {
+        panic!("Snap eccentricity must be at least 0 and less than 1");
+    }
 
-    let fullscreen: bool = match matches.occurrences_of("fullscreen") {
-        0 => false,
-        _ => true,
+    let kiosk_reset: f64 = match matches.value_of("kiosk_reset") {
+        Some(s) => s.parse().expect("Kiosk reset must be a number"),
+        None => 0.0,
     };
 
     let trail_length: usize = match matches.value_of("trail_length") {
@@ -209,11 +2026,458 @@ fn main() {
         None => 1,
     };
 
+    // The cannonball demo fires from a lone planet, with nothing else spawning to distract from it
+    let cannonball: bool = matches.value_of("demo") == Some("cannonball");
+    let num_planets = if cannonball { 1 } else { num_planets };
+
+    // The Kirkwood demo fills a belt around the heavier planet of a lopsided binary, then speeds time
+    // up until the outer planet clears gaps in it
+    let kirkwood: bool = matches.value_of("demo") == Some("kirkwood");
+
+    // A replayed session starts from the seed it was recorded with
+    let mut input_replay: Option<InputReplay> = matches.value_of("replay_input").map(|path| {
+        InputReplay::load(path).unwrap_or_else(|e| panic!("{}", e))
+    });
+    // Deterministic runs take nothing from the wall clock or the system's randomness, so need a seed
+    let deterministic: bool = matches.is_present("deterministic");
+    let seed: u64 = match (matches.value_of("seed"), input_replay.as_ref()) {
+        (Some(_), Some(_)) => panic!("Replayed input brings its own seed"),
+        (Some(s), None) => s.parse().expect("Seed must be an integer"),
+        (None, Some(replay)) => replay.seed,
+        (None, None) => match code.as_ref() {
+            Some(code) => code.seed,
+            None if deterministic => panic!("Deterministic runs need a seed, from --seed, a share code or replayed input"),
+            None => rand::thread_rng().gen(),
+        },
+    };
+    if realtime && (input_replay.is_some() || matches.is_present("record_input")) {
+        panic!("Real-time mode follows the wall clock, so cannot be recorded or replayed");
+    }
+
+    // A lucky dip at the start is picked from the seed, so the same seed picks the same tuning
+    let lucky: Option<Tuning> = match matches.is_present("lucky") {
+        true => Some(Tuning::lucky(&mut ChaCha8Rng::seed_from_u64(seed))),
+        false => None,
+    };
+    if let Some(tuning) = lucky.as_ref() {
+        println!("Feeling lucky with seed {}, save this as a scene to keep it:\n{}", seed, tuning.scene());
+    }
+
+    let show_origin: Option<&'static str> = matches.value_of("show_origin").map(|s| {
+        *Origin::NAMES.iter().find(|name| **name == s).expect("Show_origin must be one of: random, emitter, user, script, breakup, belt, fragment")
+    });
+
+    let ecosystem: bool = matches.is_present("ecosystem");
+    let balance_spawns: bool = matches.is_present("balance_spawns");
+    let spawn_bias: Option<f64> = match matches.value_of("spawn_bias") {
+        Some("uniform") | None => None,
+        Some("stable") => Some(match matches.value_of("spawn_horizon") {
+            Some(s) => s.parse().expect("Spawn_horizon must be a number"),
+            None => 10.0,
+        }),
+        Some(_) => panic!("Spawn_bias must be one of: uniform, stable"),
+    };
+    if spawn_bias.is_some_and(|horizon| horizon <= 0.0) {
+        panic!("Spawn_horizon must be positive");
+    }
+
+    let inclination: Option<f64> = match matches.is_present("three_d") {
+        true => Some(match matches.value_of("inclination") {
+            Some(s) => s.parse().expect("Inclination must be a number"),
+            None => 30.0,
+        }),
+        false => None,
+    };
+    if inclination.is_some_and(|degrees| !(0.0..=90.0).contains(&degrees)) {
+        panic!("Inclination must be between 0 and 90 degrees");
+    }
+    let inclination = inclination.map(f64::to_radians);
+
+    let max_speed: Option<f64> = matches.value_of("max_speed").map(|s| s.parse().expect("Max speed must be a number"));
+    if max_speed.is_some_and(|speed| speed <= 0.0) {
+        panic!("Max speed must be positive");
+    }
+
+    let world_scale: f64 = matches.value_of("world_scale").map_or(1.0, |s| s.parse().expect("World scale must be a number"));
+    if world_scale.is_nan() || world_scale < 1.0 {
+        panic!("World scale must be at least 1");
+    }
+
+    let mode: Mode = match matches.value_of("mode") {
+        None if kirkwood => Mode::Binary,
+        None => code.as_ref().map_or(Mode::Orbits, |code| code.mode),
+        Some("orbits") => Mode::Orbits,
+        Some("projectile") => Mode::Projectile,
+        Some("binary") => Mode::Binary,
+        Some(_) => panic!("Mode must be one of: orbits, projectile, binary"),
+    };
+    let integrator: Integrator = match matches.value_of("integrator") {
+        Some("euler") | None => Integrator::Euler,
+        Some("verlet") => Integrator::Verlet,
+        Some("rk4") => Integrator::Rk4,
+        Some(_) => panic!("Integrator must be one of: euler, verlet, rk4"),
+    };
+    let max_substeps: usize = match matches.value_of("max_substeps") {
+        Some(s) => s.parse().expect("Max substeps must be an integer"),
+        None => 1,
+    };
+    if max_substeps == 0 {
+        panic!("Max substeps must be at least 1");
+    }
+
+    // Satellites only have mass in n-body mode
+    let sat_mass: f64 = match (matches.is_present("nbody"), matches.value_of("sat_mass")) {
+        (false, _) => 0.0,
+        (true, Some(s)) => s.parse().expect("Sat mass must be a number"),
+        (true, None) => 1.0,
+    };
+    let tug_planets: bool = matches.is_present("tug_planets");
+    let theta: f64 = match matches.value_of("theta") {
+        Some(s) => s.parse().expect("Theta must be a number"),
+        None => 0.5,
+    };
+    let epsilon: f64 = match matches.value_of("epsilon") {
+        Some(s) => s.parse().expect("Epsilon must be a number"),
+        None => 0.0,
+    };
+    if theta < 0.0 {
+        panic!("Theta must not be negative");
+    }
+    if epsilon < 0.0 {
+        panic!("Epsilon must not be negative");
+    }
+    let atmosphere_height: f64 = match matches.value_of("atmosphere_height") {
+        Some(s) => s.parse().expect("Atmosphere height must be a number"),
+        None => 0.0,
+    };
+    let drag_coefficient: f64 = match matches.value_of("drag_coefficient") {
+        Some(s) => s.parse().expect("Drag coefficient must be a number"),
+        None => 0.001,
+    };
+    if atmosphere_height < 0.0 || drag_coefficient < 0.0 {
+        panic!("Atmosphere height and drag coefficient must not be negative");
+    }
+    let relativity: bool = matches.is_present("relativity");
+    let roche: f64 = match matches.value_of("roche") {
+        Some(s) => s.parse().expect("Roche limit must be a number"),
+        None => 0.0,
+    };
+    if roche != 0.0 && roche <= 1.0 {
+        panic!("Roche limit must be more than 1 planet radius, or 0 for none");
+    }
+    let star: Option<usize> = matches.value_of("star").map(|s| s.parse().expect("Star must be a planet index"));
+    let radiation: f64 = match (star, matches.value_of("radiation")) {
+        (None, _) => 0.0,
+        (Some(_), Some(s)) => s.parse().expect("Radiation must be a number"),
+        (Some(_), None) => 0.5,
+    };
+    if radiation < 0.0 {
+        panic!("Radiation must not be negative");
+    }
+    if star.is_some() && mode == Mode::Projectile {
+        panic!("There are no planets to make a star of in projectile mode");
+    }
+    let oblateness: Option<Oblateness> = matches.value_of("oblateness").map(|s| Oblateness::parse(s).unwrap_or_else(|e| panic!("{}", e)));
+    let anisotropy: Option<Anisotropy> = matches.value_of("anisotropy").map(|s| Anisotropy::parse(s).unwrap_or_else(|e| panic!("{}", e)));
+    let boundary: Boundary = match matches.value_of("boundary") {
+        Some("kill") | None => Boundary::Kill,
+        Some("wrap") => Boundary::Wrap,
+        Some("bounce") => Boundary::Bounce {
+            restitution: match matches.value_of("restitution") {
+                Some(s) => s.parse().expect("Restitution must be a number"),
+                None => 1.0,
+            },
+        },
+        Some(_) => panic!("Boundary must be one of: kill, wrap, bounce"),
+    };
+    if let Boundary::Bounce { restitution } = boundary {
+        if !(0.0..=1.0).contains(&restitution) {
+            panic!("Restitution must be between 0 and 1");
+        }
+    } else if matches.is_present("restitution") {
+        panic!("Restitution only applies with --boundary bounce");
+    }
+    let mass_cue: MassCue = match matches.value_of("mass_cue") {
+        Some("off") | None => MassCue::Off,
+        Some(cue) => {
+            let strength: f64 = match matches.value_of("mass_cue_strength") {
+                Some(s) => s.parse().expect("Mass cue strength must be a number"),
+                None => 0.3,
+            };
+            if strength <= 0.0 {
+                panic!("Mass cue strength must be positive");
+            }
+            match cue {
+                "radius" => MassCue::Radius { strength },
+                "ring" => MassCue::Ring { strength },
+                _ => panic!("Mass cue must be one of: off, radius, ring"),
+            }
+        },
+    };
+    let collisions: Collisions = match matches.value_of("collisions") {
+        Some("kill") | None => Collisions::Kill,
+        Some("bounce") => Collisions::Bounce,
+        Some("merge") => Collisions::Merge,
+        Some(_) => panic!("Collisions must be one of: kill, bounce, merge"),
+    };
+    if collisions == Collisions::Merge && !matches.is_present("nbody") {
+        panic!("Only satellites with mass can merge, in n-body mode");
+    }
+    if tug_planets && mode != Mode::Orbits {
+        panic!("Only planets in orbits mode can be tugged by satellites");
+    }
+
+    if cannonball && mode != Mode::Orbits {
+        panic!("The cannonball demo can only run in orbits mode");
+    }
+    if kirkwood && mode != Mode::Binary {
+        panic!("The Kirkwood demo can only run in binary mode");
+    }
+
+    let gravity: f64 = match matches.value_of("gravity") {
+        Some(s) => s.parse().expect("Gravity must be a number"),
+        None => 400.0,
+    };
+
+    let show_zero_velocity: bool = matches.is_present("zero_velocity");
+    let show_lagrange: bool = matches.is_present("lagrange");
+    let rotating_view: bool = matches.is_present("rotating_view");
+    let barycentric: bool = matches.is_present("barycentric");
+
+    let ripple_impulse: f64 = match matches.value_of("ripple_impulse") {
+        Some(s) => s.parse().expect("Ripple impulse must be a number"),
+        None => 0.0,
+    };
+    let ripples: bool = matches.is_present("ripples") || ripple_impulse > 0.0;
+
+    let find_orbit: Option<Orbit> = matches.value_of("find_orbit").map(|s| Orbit::parse(s).unwrap_or_else(|e| panic!("{}", e)));
+    let injected: Vec<Orbit> = match matches.values_of("inject") {
+        Some(values) => values.map(|s| Orbit::parse(s).unwrap_or_else(|e| panic!("{}", e))).collect(),
+        None => Vec::new(),
+    };
+    let on_rails: bool = matches.is_present("on_rails");
+
+    let quotas: Vec<(usize, usize)> = match matches.values_of("quota") {
+        Some(values) => values.map(|s| Quotas::parse(s).unwrap_or_else(|e| panic!("{}", e))).collect(),
+        None => Vec::new(),
+    };
+    // Periodic orbits are only periodic while the planets stay put
+    if (find_orbit.is_some() || !injected.is_empty()) && mode != Mode::Orbits {
+        panic!("Periodic orbits can only be found and injected in orbits mode");
+    }
+    let planet_gravity: bool = !matches.is_present("static_planets") && find_orbit.is_none() && injected.is_empty();
+
+    let headless: Option<u64> = matches.value_of("headless").map(|s| s.parse().expect("Headless must be an integer"));
+    if realtime && headless.is_some() {
+        panic!("Real-time mode needs a window");
+    }
+    if input_replay.is_some() && headless.is_some() {
+        panic!("Replaying input needs a window");
+    }
+
+    let time_warp: usize = match matches.value_of("time_warp") {
+        Some(s) => s.parse().expect("Time warp must be an integer"),
+        None if kirkwood => KIRKWOOD_WARP,
+        None => 1,
+    };
+    if time_warp == 0 {
+        panic!("Time warp must be at least 1");
+    }
+    if realtime && time_warp > 1 {
+        panic!("Real-time mode cannot be sped up");
+    }
+    let slow_motion: Option<SlowMotion> = match matches.is_present("slow_motion") {
+        true if realtime => panic!("Real-time mode cannot be slowed down"),
+        true => Some(SlowMotion::new(SLOW_MOTION_SPEED)),
+        false => None,
+    };
+    // Deterministic runs take nothing from the wall clock, so palette cycles follow simulation time
+    let moods: Option<Moods> = match (matches.is_present("palette_clock"), matches.value_of("palette_cycle")) {
+        (true, _) => Some(Moods::new(Timing::Clock)),
+        (false, Some(s)) => {
+            let hours: f64 = s.parse().expect("Palette cycle must be a number");
+            if hours <= 0.0 {
+                panic!("Palette cycle must be positive");
+            }
+            match deterministic {
+                true => Some(Moods::new(Timing::Simulated(hours * 3600.0))),
+                false => Some(Moods::new(Timing::Cycle(hours * 3600.0))),
+            }
+        },
+        (false, None) => None,
+    };
+    let fragments: usize = match matches.value_of("fragments") {
+        Some(s) => s.parse().expect("Fragments must be an integer"),
+        None => 8,
+    };
+    if fragments < 2 {
+        panic!("Fragments must be at least 2");
+    }
+    let hash_trace: Option<&str> = matches.value_of("hash_trace");
+    let hash_compare: Option<&str> = matches.value_of("hash_compare");
+
+    // Scene tuning takes over from the defaults and a lucky dip from that, though not from options
+    // given explicitly or anything a demo relies on
+    let params = |width: f64, height: f64| {
+        let mut params = Params {
+            width,
+            height,
+            add_chance: 0.01,
+            sat_radius: 5.0,
+            sat_velocity: 200.0,
+            gravity_constant: 4000.0,
+            trail_length,
+            num_planets,
+            stability_orbits,
+            record_paths: trail_export.is_some(),
+            exclusions: scene.exclusions.clone(),
+            repulsors: scene.repulsors.clone(),
+            asteroids: scene.asteroids.clone(),
+            moons: scene.moons.clone(),
+            ripples,
+            ripple_impulse,
+            mode,
+            gravity,
+            binary_ratio: if kirkwood { KIRKWOOD_RATIO } else { 1.0 },
+            palette: None,
+            integrator,
+            max_substeps,
+            sat_mass,
+            tug_planets,
+            planet_gravity,
+            theta,
+            epsilon,
+            collisions,
+            boundary,
+            atmosphere_height,
+            drag_coefficient,
+            anisotropy,
+            relativity,
+            oblateness,
+            roche,
+            star,
+            radiation,
+            spawn_bias,
+            inclination,
+            max_speed,
+            world_scale,
+        };
+        for tuning in scene.tuning.iter().chain(lucky.iter()).chain(code.as_ref().map(|code| &code.tuning)) {
+            tuning.apply(&mut params);
+        }
+        if matches.is_present("num_planets") || cannonball {
+            params.num_planets = num_planets;
+        }
+        if cannonball || kirkwood {
+            params.add_chance = 0.0;
+        }
+        // How many planets there are depends on the mode, which the scene may have set, and moons go
+        // round bodies before them
+        let planets = match params.mode {
+            Mode::Orbits => params.num_planets,
+            Mode::Projectile => 0,
+            Mode::Binary => 2,
+        };
+        if params.star.is_some_and(|star| star >= planets) {
+            panic!("Star must be a planet index below {}", planets);
+        }
+        let before = planets + params.repulsors.len() + params.asteroids.len();
+        for (i, moon) in params.moons.iter().enumerate() {
+            moon.validate(i, before).unwrap_or_else(|e| panic!("{}", e));
+        }
+        params
+    };
+
+    // Projectile mode has nothing to watch without something launching satellites
+    let emitters = |width: f64, height: f64| -> Vec<Emitter> {
+        if scene.emitters.is_empty() && mode == Mode::Projectile {
+            vec![Emitter::fountain(width, height, gravity)]
+        } else {
+            scene.emitters.clone()
+        }
+    };
+
+    // The Kirkwood demo brings its own belt in place of any in the scene
+    let belts = |planets: &[Planet]| -> Vec<Belt> {
+        if kirkwood {
+            vec![Belt::kirkwood(planets)]
+        } else {
+            scene.belts.clone()
+        }
+    };
+
+    let formation: Option<Formation> = scene.formation.as_ref().map(|config| {
+        Formation::new(config, Shape::load(config).unwrap_or_else(|e| panic!("{}", e)))
+    });
+
+    if let Some(guess) = find_orbit {
+        let sim = Simulation::new(params(design_width, design_height), seed);
+        println!("seed {}", seed);
+        match periodic::find(guess, &sim.planets, sim.params.gravity_constant) {
+            Ok((orbit, iterations)) => {
+                println!("converged after {} iterations", iterations);
+                println!("--inject {}", orbit);
+            },
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            },
+        }
+        return;
+    }
+
+    if let Some(explore) = matches.subcommand_matches("explore") {
+        let samples: usize = match explore.value_of("samples") {
+            Some(s) => s.parse().expect("Samples must be an integer"),
+            None => 50,
+        };
+        let steps: u64 = match explore.value_of("steps") {
+            Some(s) => s.parse().expect("Steps must be an integer"),
+            None => 3600,
+        };
+        let top: usize = match explore.value_of("top") {
+            Some(s) => s.parse().expect("Top must be an integer"),
+            None => 5,
+        };
+        let out: &str = explore.value_of("out").unwrap_or("explore");
+        explore::run(|| params(design_width, design_height), seed, samples, steps, HEADLESS_DT, top, out);
+        return;
+    }
+
+    if let Some(steps) = headless {
+        let mut sim = Simulation::new(params(design_width, design_height), seed);
+        if let Some(code) = code.as_ref() {
+            code.restore(&mut sim);
+        }
+        sim.formation = formation;
+        sim.emitters = emitters(design_width, design_height);
+        sim.belts = belts(&sim.planets);
+        sim.walls = scene.walls.clone();
+        sim.fields = scene.fields.clone();
+        sim.portals = scene.portals.clone();
+        if ecosystem {
+            sim.ecosystem = Some(Ecosystem::new(&sim.params, &mut sim.rng));
+        }
+        if balance_spawns {
+            sim.density = Some(Density::new(design_width, design_height));
+        }
+        for &(kind, limit) in quotas.iter() {
+            sim.quotas.limits[kind] = Some(limit);
+        }
+        for orbit in injected.iter() {
+            sim.inject(*orbit, on_rails).unwrap_or_else(|e| panic!("{}", e));
+        }
+        sim.fill_belts();
+        hashtrace::run(sim, steps, HEADLESS_DT, hash_trace, hash_compare);
+        return;
+    }
+
     // Change this to OpenGL::V2_1 if not working.
     let opengl = OpenGL::V3_2;
 
-    let mut width = 800;
-    let mut height = 800;
+    let mut width = design_width as u32;
+    let mut height = design_height as u32;
+    let mut origin = None;
 
     if fullscreen {
         let get_resolution: Window = WindowSettings::new("get_resolution", [1, 1])
@@ -221,101 +2485,310 @@ fn main() {
             .unwrap();
 
         let monitors: Vec<_> = get_resolution.ctx.window().available_monitors().collect();
-        if monitors.len() == 0 {
+        if monitors.is_empty() {
             panic!("Could not find any monitors")
         }
         let monitor = &monitors[0]; // TODO allow selecting which monitor to put on
-        let size = monitor.size();
+        let size = monitor.size().to_logical::<u32>(monitor.scale_factor());
         width = size.width;
         height = size.height;
+        origin = Some(monitor.position());
     }
 
-    let mut rng = rand::thread_rng();
-
-    let mut window: Window = WindowSettings::new("orbits", [width, height])
-        .fullscreen(fullscreen)
-        .resizable(false)
+    let settings = WindowSettings::new("orbits", [width, height])
+        .fullscreen(fullscreen && !overlay)
+        .resizable(!kiosk)
         .graphics_api(opengl)
-        .exit_on_esc(true)
-        .build()
-        .unwrap();
-    if fullscreen {
+        .exit_on_esc(!kiosk)
+        .automatic_close(!kiosk);
+    let mut window: Window = if overlay {
+        // Window settings have no way to ask for transparency or staying on top, so build the window
+        // directly. A fullscreen overlay is a borderless window covering the monitor, as compositors
+        // tend to draw true fullscreen windows opaque.
+        let builder = glutin::window::WindowBuilder::new()
+            .with_title("orbits")
+            .with_inner_size(glutin::dpi::LogicalSize::new(width, height))
+            .with_resizable(false)
+            .with_decorations(false)
+            .with_transparent(true)
+            .with_always_on_top(true);
+        let window = Window::from_raw(&settings, glutin::event_loop::EventLoop::with_user_event(), builder).unwrap();
+        if let Some(position) = origin {
+            window.ctx.window().set_outer_position(position);
+        }
+        window
+    } else {
+        settings.build().unwrap()
+    };
+    if (fullscreen || kiosk) && !overlay {
         window = window.capture_cursor(true);
     }
 
+    // Drawn at a scale other than the monitor's, a window is resized to fit the scene at that scale
+    let zoom = ui_zoom(ui_scale, window.size().width, window.draw_size().width);
+    if !fullscreen && zoom != 1.0 {
+        window.set_size([design_width * zoom, design_height * zoom]);
+    }
+    let size = window.size();
+    let (width, height) = (size.width / zoom, size.height / zoom);
+
+    // The world keeps the scene's size whatever the window's, so that the window only changes how
+    // much of the screen it takes up and not how bodies move, unless it is extended to the window
+    let (world_width, world_height) = match aspect {
+        Aspect::Extend => (width, height),
+        Aspect::Letterbox | Aspect::Crop => (design_width, design_height),
+    };
+    let fit = Fit::new(aspect, world_width, world_height, width, height);
+
     // Create planets
-    let mut planets: Vec<Planet> = Vec::new();
-    let radius: f64 = std::cmp::min(width, height) as f64 / 4.0;
-    match num_planets {
-        0 => panic!("Num_planets must be greater than 0"),
-        1 => {
-            planets.push(Planet {
-                color: random_color(&mut rng),
-                mass: 1000.0,
-                radius: 25.0,
-                x: width as f64 / 2.0,
-                y: height as f64 / 2.0,
-            });
-        },
-        2 => {
-            planets.push(Planet {
-                color: random_color(&mut rng),
-                mass: 1000.0,
-                radius: 25.0,
-                x: width as f64 / 2.0 - radius,
-                y: height as f64 / 2.0,
-            });
-            planets.push(Planet {
-                color: random_color(&mut rng),
-                mass: 1000.0,
-                radius: 25.0,
-                x: width as f64 / 2.0 + radius,
-                y: height as f64 / 2.0,
-            });
-        },
-        n => {
-            for i in 0..n {
-                let theta = (std::f64::consts::PI * 2.0 * i as f64 / n as f64) - std::f64::consts::FRAC_PI_2;
-                let x = width as f64 / 2.0 + (theta.cos() * radius);
-                let y = height as f64 / 2.0 + (theta.sin() * radius);
-                planets.push(Planet {
-                    color: random_color(&mut rng),
-                    mass: 1000.0,
-                    radius: 25.0,
-                    x,
-                    y,
-                });
-            }
-        },
+    let mut sim = Simulation::new(params(world_width, world_height), seed);
+    if let Some(code) = code.as_ref() {
+        code.restore(&mut sim);
+    }
+    sim.formation = formation;
+    sim.emitters = emitters(world_width, world_height);
+    sim.belts = belts(&sim.planets);
+    sim.walls = scene.walls.clone();
+    sim.fields = scene.fields.clone();
+    sim.portals = scene.portals.clone();
+    if ecosystem {
+        sim.ecosystem = Some(Ecosystem::new(&sim.params, &mut sim.rng));
+    }
+    if balance_spawns {
+        sim.density = Some(Density::new(world_width, world_height));
+    }
+    for &(kind, limit) in quotas.iter() {
+        sim.quotas.limits[kind] = Some(limit);
     }
+    for orbit in injected.iter() {
+        sim.inject(*orbit, on_rails).unwrap_or_else(|e| panic!("{}", e));
+    }
+    sim.fill_belts();
+    // Picking up from an autosave that cannot be read would only crash again, so it is skipped
+    match autosave.as_deref().map(Autosave::load) {
+        Some(Ok(Some(save))) => save.restore(&mut sim),
+        Some(Err(e)) => eprintln!("{}", e),
+        _ => {},
+    }
+    let mut heartbeat: Option<Heartbeat> = matches.value_of("heartbeat")
+        .map(|s| Heartbeat::connect(s.parse().expect("Heartbeat must be a port")).expect("Could not connect to the supervisor"));
+    let timeline = if cannonball {
+        Some(Timeline::cannonball(&sim.planets[0], sim.params.gravity_constant))
+    } else if !scene.timeline.is_empty() {
+        Some(Timeline::new(scene.timeline.clone()))
+    } else {
+        None
+    };
+    // The tutorial is shown on the first run, unless the window is left running unattended or something
+    // else is already captioned or driving the input
+    let tutorial = if matches.is_present("tutorial") {
+        Some(Tutorial::new())
+    } else if kiosk || timeline.is_some() || input_replay.is_some() {
+        None
+    } else {
+        Tutorial::first_run()
+    };
+    let outcomes = Outcomes::new(sim.planets.len());
+
+    // In real time, start from now, with the planets moved on from where they were at the scene's epoch
+    let realtime = if realtime {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("System clock is before 1970").as_secs() as i64;
+        let offset = clock.rebase(now).unwrap();
+        sim.pass_time(offset);
+        Some(Realtime { started: Instant::now(), offset })
+    } else {
+        None
+    };
+    let planets = &sim.planets;
+    let three_d = sim.params.inclination.is_some();
+
+    let trail_export = trail_export.map(|path| {
+        TrailExporter::create(path, world_width, world_height, planets, three_d, &clock).expect("Could not create trail export")
+    });
+    let keyframe_export = keyframe_export.map(|path| {
+        KeyframeExporter::create(path, world_width, world_height, planets, keyframe_rate, three_d, &clock).expect("Could not create keyframe export")
+    });
+
+    let input_recorder = matches.value_of("record_input").map(|path| {
+        InputRecorder::create(path, seed).expect("Could not create input recording")
+    });
+
+    let frame_capture = capture.map(|dir| {
+        FrameCapture::create(dir, capture_sim_fps).expect("Could not create capture directory")
+    });
+
+    let event_shots = screenshot_on.map(|triggers| {
+        EventShots::create(screenshot_dir, triggers, screenshot_limit).expect("Could not create screenshot directory")
+    });
+
+    let clip_recorder = highlights.map(|dir| {
+        ClipRecorder::create(dir, clip_length, keyframe_rate, three_d).expect("Could not create highlights directory")
+    });
+
+    let mut gl = GlGraphics::new(opengl);
+
+    let director = match matches.is_present("director") || extra_view == Some(Camera::Director) {
+        true => Some(Director::new(sim.params.width, sim.params.height, matches.is_present("director"))),
+        false => None,
+    };
+
+    // Open the extra window last, then switch back to drawing in the main one
+    let mut extra_view = extra_view.map(|camera| {
+        let window: Window = WindowSettings::new("orbits (extra view)", [800, 800])
+            .graphics_api(opengl)
+            .build()
+            .unwrap();
+        ExtraView {
+            window,
+            gl: GlGraphics::new(opengl),
+            camera,
+        }
+    });
+    window.make_current();
 
     // Create a new game and run it.
+    let scores = Scores::load().unwrap_or_else(|e| {
+        eprintln!("Could not read high scores: {}", e);
+        Scores::default()
+    });
+
+    // The Kirkwood demo shows the gaps opening in a histogram along the bottom of the window
+    let mut hud = Hud::new(&scene.hud);
+    if kirkwood {
+        hud.add(&WidgetConfig {
+            widget: WidgetKind::Axes,
+            x: 10.0,
+            y: height - 110.0,
+            width: 300.0,
+            height: 100.0,
+            scale: 1.0,
+        });
+    }
+
     let mut app = App {
-        gl: GlGraphics::new(opengl),
-        rng,
         fps_counter: FPSCounter::default(),
-        planets: planets,
-        satellites: Vec::new(),
+        sim,
+        trail_export,
+        keyframe_export,
+        clusters: Vec::new(),
+        show_clusters,
+        since_analysis: 1.0,
+        resonances: Vec::new(),
+        show_resonances,
+        color_mode,
+        events: Vec::new(),
+        known_resonances: HashSet::new(),
+        clip_recorder,
+        frame_capture,
+        event_shots,
+        hud,
+        fps: 0,
+        bodies: (0, 0),
+        step_time: 0.0,
+        modifiers: Modifiers::default(),
+        show_zero_velocity,
+        show_lagrange,
+        jacobi_level: 1.0,
+        rotating_view,
+        barycentric,
+        tilt: inclination.map(|_| Tilt::default()),
+        cursor: [0.0, 0.0],
+        cursor_moved: false,
+        hover: None,
+        fit,
+        ui_zoom: zoom,
+        dragging: false,
+        launcher: None,
+        piloted: None,
+        engine: HashSet::new(),
+        timeline,
+        tutorial,
+        outcomes,
+        clock,
+        realtime,
+        slow_motion,
+        moods,
+        director,
+        since_stats: 0.0,
+        since_autosave: 0.0,
+        aiming: false,
+        snap: matches.is_present("snap_eccentricity"),
+        score: None,
+        scores,
+        naming: None,
+        ghost: Ghost::new(),
+        show_origin,
+        comets: matches.is_present("comets"),
+        glow: matches.is_present("glow"),
+        show_swarm: matches.is_present("swarm"),
+        show_bound: matches.is_present("bound_rings"),
+        bound: Vec::new(),
+        drawing_walls: false,
+        wall_start: None,
+        annotations: None,
+        since_reset: 0.0,
+        lucky: lucky.map(|tuning| (tuning, LUCKY_SHOWN)),
+        updates: 0,
+        input_recorder,
         args: Args {
             title: "orbits".to_string(),
-            width: width as f64,
-            height: height as f64,
-            add_chance: 0.01,
-            sat_radius: 5.0,
-            sat_velocity: 200.0,
-            gravity_constant: 4000.0,
-            trail_length: trail_length,
+            overlay,
+            kiosk,
+            kiosk_quit,
+            kiosk_reset,
+            cluster_eps,
+            cluster_min,
+            stats_log,
+            snap_eccentricity,
+            time_warp,
+            fragments,
+            aspect,
+            ui_scale,
+            annotations,
+            autosave: autosave.clone(),
+            mass_cue,
         }
     };
 
     let mut events = Events::new(EventSettings::new());
     while let Some(e) = events.next(&mut window) {
         if let Some(args) = e.render_args() {
-            app.render(&args, &mut window);
+            app.render(&args, &mut window, &mut gl);
+            if let Some(heartbeat) = heartbeat.as_mut() {
+                if !heartbeat.beat() {
+                    eprintln!("Supervisor has gone, quitting");
+                    break;
+                }
+            }
+            if let Some(view) = extra_view.as_mut() {
+                app.render_extra(view);
+                if view.window.should_close() {
+                    extra_view = None;
+                }
+                window.make_current();
+            }
         }
 
         if let Some(args) = e.update_args() {
+            // Feed in recorded input at the same point it first arrived, before the update after it
+            if let Some(replay) = input_replay.as_mut() {
+                for input in replay.due(app.updates) {
+                    app.input(&input, &mut window);
+                }
+                if replay.finished() {
+                    println!("Replay finished after {} updates", app.updates);
+                    input_replay = None;
+                }
+            }
             app.update(&args);
+            app.updates += 1;
+        }
+
+        // Live input is ignored until a replay has finished
+        if let (None, Some(input)) = (input_replay.as_ref(), Input::from_event(&e)) {
+            app.input(&input, &mut window);
         }
     }
+
+    app.finish();
 }
@@ -0,0 +1,42 @@
+use crate::simulation::{Planet, PLANET_MASS};
+
+// Smallest and largest a planet's mass can make it look relative to its true size, so one that has
+// lost nearly all its mass does not vanish and one that has swallowed thousands of satellites does
+// not cover the screen
+const MIN_SCALE: f64 = 0.25;
+const MAX_SCALE: f64 = 3.0;
+
+// How a planet's mass is shown when it is drawn. Every planet has the same true size, whatever mass a
+// scene gives it or it gains by accretion, so without a cue the heavy and the light look alike.
+#[derive(Clone, Copy, PartialEq)]
+pub enum MassCue {
+    Off,                      // Planets are drawn at their true size
+    Radius { strength: f64 }, // Planets are drawn larger the heavier they are
+    Ring { strength: f64 },   // Planets are drawn at their true size, circled at a radius that grows with their mass
+}
+
+impl MassCue {
+    // Size a planet's mass makes it look, relative to its true size. One of the usual mass looks as it
+    // is, and each time its mass goes up e-fold it looks `strength` of its size larger, on a log scale
+    // so that a planet of twice the mass and one of a hundred times both stay readable.
+    fn scale(strength: f64, planet: &Planet) -> f64 {
+        (1.0 + strength * (planet.mass.abs() / PLANET_MASS).ln()).clamp(MIN_SCALE, MAX_SCALE)
+    }
+
+    // Radius (in px) to draw the planet at
+    pub fn radius(self, planet: &Planet) -> f64 {
+        match self {
+            MassCue::Radius { strength } => planet.radius * MassCue::scale(strength, planet),
+            MassCue::Off | MassCue::Ring { .. } => planet.radius,
+        }
+    }
+
+    // Radius (in px) of the ring to circle the planet with, if any: outside the planet if it is
+    // heavier than usual and inside if it is lighter
+    pub fn ring(self, planet: &Planet) -> Option<f64> {
+        match self {
+            MassCue::Ring { strength } => Some(planet.radius * MassCue::scale(strength, planet)),
+            MassCue::Off | MassCue::Radius { .. } => None,
+        }
+    }
+}
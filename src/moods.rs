@@ -0,0 +1,73 @@
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::simulation::palette::Palette;
+
+// Themes the day passes through, evenly spaced round it from sunrise
+const THEMES: [Palette; 4] = [
+    Palette { hue: 15.0, spread: 50.0 },  // Sunrise: pinks and oranges
+    Palette { hue: 190.0, spread: 90.0 }, // Day: sky blues and greens
+    Palette { hue: 330.0, spread: 70.0 }, // Sunset: magentas and reds
+    Palette { hue: 235.0, spread: 40.0 }, // Night: deep blues
+];
+
+// Seconds in a day
+const DAY: f64 = 86400.0;
+
+// What the day the themes pass through is kept in step with
+pub enum Timing {
+    Cycle(f64),     // A day of the given length (in seconds), starting at sunrise when the run starts
+    Simulated(f64), // A day of the given length in simulation seconds, so it plays out the same on every run
+    Clock,          // The local time of day, with sunrise at 6am, day at noon, sunset at 6pm and night at midnight
+}
+
+// Moves the spawn palette slowly round a day of colour themes, sunrise to day to sunset to night,
+// so displays left running for hours shift mood as they go
+pub struct Moods {
+    timing: Timing,
+    started: Instant,
+}
+
+impl Moods {
+    pub fn new(timing: Timing) -> Moods {
+        Moods {
+            timing,
+            started: Instant::now(),
+        }
+    }
+
+    // How far round the day it is, from 0 at sunrise to 1 at the next, `time` seconds into the
+    // simulation
+    fn phase(&self, time: f64) -> f64 {
+        match self.timing {
+            Timing::Cycle(length) => (self.started.elapsed().as_secs_f64() / length).fract(),
+            Timing::Simulated(length) => (time / length).fract(),
+            Timing::Clock => (local_seconds() / DAY - 0.25).rem_euclid(1.0),
+        }
+    }
+
+    // Palette for right now, `time` seconds into the simulation, easing from one theme into the next
+    // so it lingers on each
+    pub fn palette(&self, time: f64) -> Palette {
+        let at = self.phase(time) * THEMES.len() as f64;
+        let i = at.floor() as usize % THEMES.len();
+        let t = at.fract();
+        THEMES[i].mix(THEMES[(i + 1) % THEMES.len()], t * t * (3.0 - 2.0 * t))
+    }
+}
+
+// Seconds since midnight, local time
+#[cfg(unix)]
+fn local_seconds() -> f64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()) as libc::time_t;
+    let mut local: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&now, &mut local) }.is_null() {
+        return now as f64 % DAY;
+    }
+    (local.tm_hour * 3600 + local.tm_min * 60 + local.tm_sec) as f64
+}
+
+// Seconds since midnight, taken as UTC where the local time zone cannot be looked up
+#[cfg(not(unix))]
+fn local_seconds() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |elapsed| elapsed.as_secs_f64()) % DAY
+}
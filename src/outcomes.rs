@@ -0,0 +1,108 @@
+use crate::simulation::{Fate, Satellite};
+
+// Running tally of how satellites have died over the session
+pub struct Outcomes {
+    pub crashed: Vec<usize>, // Satellites that hit each planet, by planet index
+    pub escaped: usize,
+    pub lost: usize,         // Satellites that left the screen while still bound
+    pub expired: usize,
+    pub walled: usize,
+    pub absorbed: usize,
+    lifetime: f64,           // Total seconds lived by satellites that have died
+}
+
+impl Outcomes {
+    pub fn new(planets: usize) -> Outcomes {
+        Outcomes {
+            crashed: vec![0; planets],
+            escaped: 0,
+            lost: 0,
+            expired: 0,
+            walled: 0,
+            absorbed: 0,
+            lifetime: 0.0,
+        }
+    }
+
+    // Counts a satellite that has just died
+    pub fn record(&mut self, sat: &Satellite) {
+        match sat.fate {
+            Some(Fate::Crashed(i)) => {
+                if self.crashed.len() <= i {
+                    self.crashed.resize(i + 1, 0);
+                }
+                self.crashed[i] += 1;
+            },
+            Some(Fate::Escaped) => self.escaped += 1,
+            Some(Fate::Lost) => self.lost += 1,
+            Some(Fate::Expired) => self.expired += 1,
+            Some(Fate::Walled) => self.walled += 1,
+            Some(Fate::Absorbed) => self.absorbed += 1,
+            None => return,
+        }
+        self.lifetime += sat.died - sat.born;
+    }
+
+    pub fn dead(&self) -> usize {
+        self.crashed.iter().sum::<usize>() + self.escaped + self.lost + self.expired + self.walled + self.absorbed
+    }
+
+    // Mean seconds lived by satellites that have died
+    pub fn mean_lifetime(&self) -> Option<f64> {
+        match self.dead() {
+            0 => None,
+            dead => Some(self.lifetime / dead as f64),
+        }
+    }
+
+    // Share of all satellites so far with each outcome, as (label, fraction), with `alive` still flying
+    pub fn fractions(&self, alive: usize) -> Vec<(String, f64)> {
+        let total = (self.dead() + alive).max(1) as f64;
+        let mut fractions: Vec<(String, f64)> = self.crashed.iter().enumerate()
+            .map(|(i, count)| (format!("hit planet {}", i), *count as f64 / total))
+            .collect();
+        fractions.push(("escaped".to_string(), self.escaped as f64 / total));
+        if self.lost > 0 {
+            fractions.push(("lost".to_string(), self.lost as f64 / total));
+        }
+        if self.expired > 0 {
+            fractions.push(("expired".to_string(), self.expired as f64 / total));
+        }
+        if self.walled > 0 {
+            fractions.push(("hit a wall".to_string(), self.walled as f64 / total));
+        }
+        if self.absorbed > 0 {
+            fractions.push(("merged".to_string(), self.absorbed as f64 / total));
+        }
+        fractions.push(("alive".to_string(), alive as f64 / total));
+        fractions
+    }
+
+    // One-line summary for the log
+    pub fn summary(&self, alive: usize) -> String {
+        let shares: Vec<String> = self.fractions(alive).iter()
+            .map(|(label, fraction)| format!("{} {:.0}%", label, fraction * 100.0))
+            .collect();
+        let lifetime = match self.mean_lifetime() {
+            Some(lifetime) => format!("{:.1}s", lifetime),
+            None => "-".to_string(),
+        };
+        format!("{} satellites: {}, mean lifetime {}", self.dead() + alive, shares.join(", "), lifetime)
+    }
+
+    // Counts of every outcome over the session, printed on exit, with the satellites still flying
+    // split into those `bound` to the planets and those on their way out
+    pub fn totals(&self, bound: usize, unbound: usize) -> String {
+        let mut counts: Vec<String> = self.crashed.iter().enumerate()
+            .map(|(i, count)| format!("{} hit planet {}", count, i))
+            .collect();
+        counts.push(format!("{} escaped", self.escaped));
+        counts.push(format!("{} lost", self.lost));
+        counts.push(format!("{} expired", self.expired));
+        counts.push(format!("{} hit a wall", self.walled));
+        counts.push(format!("{} merged", self.absorbed));
+        counts.push(format!("{} still bound", bound));
+        counts.push(format!("{} unbound", unbound));
+        format!("{} satellites: {}", self.dead() + bound + unbound, counts.join(", "))
+    }
+}
@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+// A body to pick, as its index, position and radius
+pub type Body = (usize, f64, f64, f64);
+
+// Bodies bucketed on a grid of square cells, so the one under the mouse is found by looking only at
+// those in the cells around it. Cells are wide enough that any body within reach of a point is in
+// the cell holding the point or one of its eight neighbours.
+pub struct Index {
+    size: f64,                             // Length of each side of a cell
+    reach: f64,                            // How far (in px) outside a body a point can be and still pick it
+    cells: HashMap<(i64, i64), Vec<Body>>, // Bodies in each cell
+}
+
+impl Index {
+    pub fn build(bodies: Vec<Body>, reach: f64) -> Index {
+        let largest = bodies.iter().map(|&(_, _, _, radius)| radius).fold(0.0, f64::max);
+        let size = (2.0 * (largest + reach)).max(1.0);
+        let mut cells: HashMap<(i64, i64), Vec<Body>> = HashMap::new();
+        for body in bodies.into_iter().filter(|&(_, x, y, _)| x.is_finite() && y.is_finite()) {
+            cells.entry(Index::cell(size, body.1, body.2)).or_default().push(body);
+        }
+        Index { size, reach, cells }
+    }
+
+    fn cell(size: f64, x: f64, y: f64) -> (i64, i64) {
+        ((x / size).floor() as i64, (y / size).floor() as i64)
+    }
+
+    // Body whose edge is nearest the point, if it is within reach
+    pub fn nearest(&self, x: f64, y: f64) -> Option<usize> {
+        let (cx, cy) = Index::cell(self.size, x, y);
+        let mut nearest = None;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for &(i, body_x, body_y, radius) in self.cells.get(&(cx + dx, cy + dy)).into_iter().flatten() {
+                    let distance = (body_x - x).hypot(body_y - y) - radius;
+                    if distance <= self.reach && nearest.is_none_or(|(_, best)| distance < best) {
+                        nearest = Some((i, distance));
+                    }
+                }
+            }
+        }
+        nearest.map(|(i, _)| i)
+    }
+}
@@ -0,0 +1,213 @@
+use graphics::math::Matrix2d;
+use graphics::Viewport;
+
+use crate::draw::{Backend, Blend, DrawList, Shape};
+use crate::text;
+
+// Draws draw lists into an image in memory rather than a window, for pictures made without one.
+// Shapes are not antialiased, but lines thinner than a pixel are drawn a pixel wide and faded to
+// match, so that trails still show when shrunk right down.
+#[derive(Default)]
+pub struct Raster {
+    pub image: image::RgbImage,
+}
+
+// Image being drawn into, with each pixel's colour kept unrounded until the end
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 3]>,
+    blend: Blend,
+}
+
+impl Canvas {
+    fn paint(&mut self, x: i64, y: i64, [r, g, b, a]: [f32; 4]) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let pixel = &mut self.pixels[y as usize * self.width + x as usize];
+        for (channel, value) in pixel.iter_mut().zip([r, g, b].iter()) {
+            *channel = match self.blend {
+                Blend::Alpha => value * a + *channel * (1.0 - a),
+                Blend::Additive => (*channel + value * a).min(1.0),
+            };
+        }
+    }
+
+    // Paints every pixel whose centre passes the test, looking only at pixels within the given
+    // bounds (in pixels) of the image
+    fn fill(&mut self, bounds: [f64; 4], color: [f32; 4], inside: impl Fn(f64, f64) -> bool) {
+        let (x0, y0) = (bounds[0].floor().max(0.0) as i64, bounds[1].floor().max(0.0) as i64);
+        let x1 = bounds[2].ceil().min(self.width as f64) as i64;
+        let y1 = bounds[3].ceil().min(self.height as f64) as i64;
+        for y in y0..y1 {
+            for x in x0..x1 {
+                if inside(x as f64 + 0.5, y as f64 + 0.5) {
+                    self.paint(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+// Affine map from a shape's own coordinates to pixels, as [[a, b, c], [d, e, f]], given its
+// transform to normalised device coordinates and the size of the image
+fn to_pixels(t: Matrix2d, width: f64, height: f64) -> Matrix2d {
+    let (w, h) = (width / 2.0, height / 2.0);
+    [
+        [w * t[0][0], w * t[0][1], w * (t[0][2] + 1.0)],
+        [-h * t[1][0], -h * t[1][1], h * (1.0 - t[1][2])],
+    ]
+}
+
+fn apply(m: Matrix2d, x: f64, y: f64) -> (f64, f64) {
+    (m[0][0] * x + m[0][1] * y + m[0][2], m[1][0] * x + m[1][1] * y + m[1][2])
+}
+
+// Inverse of an affine map, or None if it squashes everything flat
+fn invert(m: Matrix2d) -> Option<Matrix2d> {
+    let det = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+    if det.abs() < f64::EPSILON {
+        return None;
+    }
+    let (a, b, d, e) = (m[1][1] / det, -m[0][1] / det, -m[1][0] / det, m[0][0] / det);
+    Some([[a, b, -(a * m[0][2] + b * m[1][2])], [d, e, -(d * m[0][2] + e * m[1][2])]])
+}
+
+// Bounds (in pixels) of a rectangle in a shape's own coordinates once mapped to pixels, grown by a
+// margin on every side
+fn bounds(m: Matrix2d, rect: [f64; 4], margin: f64) -> [f64; 4] {
+    let corners = [
+        apply(m, rect[0], rect[1]),
+        apply(m, rect[0] + rect[2], rect[1]),
+        apply(m, rect[0], rect[1] + rect[3]),
+        apply(m, rect[0] + rect[2], rect[1] + rect[3]),
+    ];
+    corners.iter().fold([f64::MAX, f64::MAX, f64::MIN, f64::MIN], |b, (x, y)| {
+        [b[0].min(x - margin), b[1].min(y - margin), b[2].max(x + margin), b[3].max(y + margin)]
+    })
+}
+
+// How far (in local units) a point is outside the ellipse filling a rectangle, roughly, negative inside
+fn ellipse_distance(rect: [f64; 4], x: f64, y: f64) -> f64 {
+    let (rx, ry) = (rect[2] / 2.0, rect[3] / 2.0);
+    let (dx, dy) = ((x - rect[0] - rx) / rx, (y - rect[1] - ry) / ry);
+    (dx.hypot(dy) - 1.0) * rx.min(ry)
+}
+
+// How far (in local units) a point is outside a rectangle's edge, negative inside
+fn rectangle_distance(rect: [f64; 4], x: f64, y: f64) -> f64 {
+    let dx = (rect[0] - x).max(x - rect[0] - rect[2]);
+    let dy = (rect[1] - y).max(y - rect[1] - rect[3]);
+    dx.max(dy)
+}
+
+// Whether a point is inside a polygon, by the even-odd rule
+fn in_polygon(points: &[[f64; 2]], x: f64, y: f64) -> bool {
+    let mut inside = false;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        if (a[1] > y) != (b[1] > y) && x < a[0] + (y - a[1]) / (b[1] - a[1]) * (b[0] - a[0]) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+impl Backend for Raster {
+    fn render(&mut self, list: &DrawList, viewport: Viewport) {
+        let [width, height] = viewport.draw_size;
+        let mut canvas = Canvas {
+            width: width as usize,
+            height: height as usize,
+            pixels: vec![[0.0; 3]; (width * height) as usize],
+            blend: Blend::Alpha,
+        };
+        for (shape, transform) in list.items.iter() {
+            let m = to_pixels(*transform, width as f64, height as f64);
+            let local = match invert(m) {
+                Some(local) => local,
+                None => continue,
+            };
+            let scale = (m[0][0] * m[1][1] - m[0][1] * m[1][0]).abs().sqrt();
+            let full = [0.0, 0.0, width as f64, height as f64];
+            match shape {
+                Shape::Clear(color) => {
+                    let blend = canvas.blend;
+                    canvas.blend = Blend::Alpha;
+                    canvas.fill(full, [color[0], color[1], color[2], 1.0], |_, _| true);
+                    canvas.blend = blend;
+                },
+                Shape::Blend(blend) => canvas.blend = *blend,
+                Shape::Rectangle { color, rect } => {
+                    canvas.fill(bounds(m, *rect, 0.0), *color, |x, y| {
+                        let (x, y) = apply(local, x, y);
+                        rectangle_distance(*rect, x, y) <= 0.0
+                    });
+                },
+                Shape::RectangleBorder { color, radius, rect } => {
+                    canvas.fill(bounds(m, *rect, radius * scale + 1.0), *color, |x, y| {
+                        let (x, y) = apply(local, x, y);
+                        rectangle_distance(*rect, x, y).abs() <= radius.max(0.5 / scale)
+                    });
+                },
+                Shape::Ellipse { color, rect } => {
+                    canvas.fill(bounds(m, *rect, 0.0), *color, |x, y| {
+                        let (x, y) = apply(local, x, y);
+                        ellipse_distance(*rect, x, y) <= 0.0
+                    });
+                },
+                Shape::EllipseBorder { color, radius, rect } => {
+                    canvas.fill(bounds(m, *rect, radius * scale + 1.0), *color, |x, y| {
+                        let (x, y) = apply(local, x, y);
+                        ellipse_distance(*rect, x, y).abs() <= radius.max(0.5 / scale)
+                    });
+                },
+                Shape::Line { color, radius, ends } => {
+                    // Measured in pixels, so lines thinner than a pixel can be widened to one
+                    let (x1, y1) = apply(m, ends[0], ends[1]);
+                    let (x2, y2) = apply(m, ends[2], ends[3]);
+                    let width = radius * scale;
+                    let half = width.max(0.5);
+                    let color = [color[0], color[1], color[2], color[3] * (2.0 * width).min(1.0) as f32];
+                    let (dx, dy) = (x2 - x1, y2 - y1);
+                    let length_sq = (dx * dx + dy * dy).max(f64::EPSILON);
+                    let box_ = [x1.min(x2) - half, y1.min(y2) - half, x1.max(x2) + half, y1.max(y2) + half];
+                    canvas.fill(box_, color, |x, y| {
+                        let t = (((x - x1) * dx + (y - y1) * dy) / length_sq).clamp(0.0, 1.0);
+                        (x - x1 - t * dx).hypot(y - y1 - t * dy) <= half
+                    });
+                },
+                Shape::Polygon { color, points } => {
+                    if points.is_empty() {
+                        continue;
+                    }
+                    let (x0, y0, x1, y1) = points.iter().fold((f64::MAX, f64::MAX, f64::MIN, f64::MIN), |b, p| {
+                        (b.0.min(p[0]), b.1.min(p[1]), b.2.max(p[0]), b.3.max(p[1]))
+                    });
+                    canvas.fill(bounds(m, [x0, y0, x1 - x0, y1 - y0], 0.0), *color, |x, y| {
+                        let (x, y) = apply(local, x, y);
+                        in_polygon(points, x, y)
+                    });
+                },
+                Shape::Text { text, color, x, y, scale: size } => {
+                    let blend = canvas.blend;
+                    canvas.blend = Blend::Alpha;
+                    for (col, row) in text::pixels(text) {
+                        let rect = [x + col as f64 * size, y + row as f64 * size, *size, *size];
+                        canvas.fill(bounds(m, rect, 0.0), *color, |x, y| {
+                            let (x, y) = apply(local, x, y);
+                            rectangle_distance(rect, x, y) <= 0.0
+                        });
+                    }
+                    canvas.blend = blend;
+                },
+            }
+        }
+        self.image = image::RgbImage::from_fn(width, height, |x, y| {
+            let [r, g, b] = canvas.pixels[y as usize * canvas.width + x as usize];
+            let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+            image::Rgb([byte(r), byte(g), byte(b)])
+        });
+    }
+}
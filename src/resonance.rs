@@ -0,0 +1,45 @@
+use crate::elements::OrbitTrack;
+
+// Period ratios checked for, as (outer, inner) orbits
+const RATIOS: [(u32, u32); 6] = [(2, 1), (3, 2), (3, 1), (4, 3), (5, 2), (5, 3)];
+
+// Relative tolerance on the period ratio
+const TOLERANCE: f64 = 0.01;
+
+// Orbits a satellite must have completed around its planet before it is considered
+const MIN_ORBITS: f64 = 3.0;
+
+// A pair of satellites around the same planet with periods near a small-integer ratio
+pub struct Resonance {
+    pub a: u64, // Id of the satellite on the outer orbit
+    pub b: u64, // Id of the satellite on the inner orbit
+    pub p: u32,
+    pub q: u32,
+}
+
+// Finds mean-motion resonances between long-lived satellites, given each satellite's id and orbit track
+pub fn find_resonances(tracks: &[(u64, OrbitTrack)], time: f64) -> Vec<Resonance> {
+    let settled: Vec<&(u64, OrbitTrack)> = tracks.iter()
+        .filter(|(_, track)| track.orbits(time) >= MIN_ORBITS)
+        .collect();
+
+    let mut found = Vec::new();
+    for (n, &&(i, a)) in settled.iter().enumerate() {
+        for &&(j, b) in settled.iter().skip(n + 1) {
+            if a.elements.planet != b.elements.planet {
+                continue;
+            }
+            let (outer, inner) = if a.elements.period >= b.elements.period { (i, j) } else { (j, i) };
+            let ratio = a.elements.period.max(b.elements.period) / a.elements.period.min(b.elements.period);
+            if let Some(&(p, q)) = RATIOS.iter().find(|(p, q)| (ratio / (*p as f64 / *q as f64) - 1.0).abs() < TOLERANCE) {
+                found.push(Resonance {
+                    a: outer,
+                    b: inner,
+                    p,
+                    q,
+                });
+            }
+        }
+    }
+    found
+}
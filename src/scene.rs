@@ -0,0 +1,54 @@
+use std::fs::File;
+
+use serde::Deserialize;
+
+use crate::clock::ClockConfig;
+use crate::hud::WidgetConfig;
+use crate::simulation::asteroid::Asteroid;
+use crate::simulation::belt::Belt;
+use crate::simulation::emitter::Emitter;
+use crate::simulation::field::Field;
+use crate::simulation::formation::FormationConfig;
+use crate::simulation::moon::Moon;
+use crate::simulation::portal::Portal;
+use crate::simulation::region::Region;
+use crate::simulation::repulsor::Repulsor;
+use crate::simulation::wall::Wall;
+use crate::timeline::Cue;
+use crate::tuning::Tuning;
+
+// Optional settings loaded from a YAML scene file, each section overriding the defaults
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scene {
+    pub hud: Vec<WidgetConfig>,             // Widgets to overlay on the simulation
+    pub exclusions: Vec<Region>,            // Regions satellites are never spawned in
+    pub repulsors: Vec<Repulsor>,           // Bodies of negative mass pushing satellites away
+    pub asteroids: Vec<Asteroid>,           // Bodies shaped as polygons, pulling as their shape does
+    pub moons: Vec<Moon>,                   // Bodies going round others on rails
+    pub formation: Option<FormationConfig>, // Shape satellites are periodically steered into
+    pub emitters: Vec<Emitter>,             // Fixed launchers of satellites
+    pub belts: Vec<Belt>,                   // Rings of small bodies around planets, filled in at the start
+    pub walls: Vec<Wall>,                   // Static walls satellites bounce off or die on
+    pub fields: Vec<Field>,                 // Areas with physics of their own: weightless, with drag or with a boost
+    pub portals: Vec<Portal>,               // Linked pairs of mouths satellites jump between
+    pub timeline: Vec<Cue>,                 // Captions and launches played back over time
+    pub clock: Option<ClockConfig>,         // Calendar date the simulation starts from, and how fast it runs
+    pub tuning: Option<Tuning>,             // Physics, spawning and colours in place of the defaults
+    pub size: Option<Size>,                 // Size of the world the scene was laid out in, if not 800x800
+}
+
+// Width and height (in px) of the world a scene was laid out in
+#[derive(Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct Size {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Scene {
+    pub fn load(path: &str) -> Result<Scene, String> {
+        let file = File::open(path).map_err(|e| format!("Could not open scene {}: {}", path, e))?;
+        serde_yaml::from_reader(file).map_err(|e| format!("Could not read scene {}: {}", path, e))
+    }
+}
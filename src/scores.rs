@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data;
+
+// Game mode scoring launches by efficiency, from the launch tool
+pub const LAUNCH: &str = "launch";
+
+// High scores kept for each game mode
+const KEEP: usize = 10;
+
+// Longest name that can be entered
+pub const NAME_LENGTH: usize = 16;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Entry {
+    pub name: String,
+    pub score: f64, // Fraction of a perfect score, from 0 to 1
+}
+
+// Best scores for each game mode, kept in scores.json in the data directory
+#[derive(Serialize, Deserialize, Default)]
+pub struct Scores {
+    modes: BTreeMap<String, Vec<Entry>>, // Entries for each game mode, best first
+}
+
+impl Scores {
+    fn path() -> Option<PathBuf> {
+        Some(data::dir()?.join("scores.json"))
+    }
+
+    // Reads the saved scores, starting afresh if there are none yet
+    pub fn load() -> io::Result<Scores> {
+        let path = match Scores::path() {
+            Some(path) => path,
+            None => return Ok(Scores::default()),
+        };
+        match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Scores::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Scores::path().ok_or_else(|| io::Error::other("No data directory (set HOME or XDG_DATA_HOME)"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    // Whether a score would make it onto the table for the given game mode
+    pub fn qualifies(&self, mode: &str, score: f64) -> bool {
+        match self.modes.get(mode) {
+            Some(entries) => entries.len() < KEEP || entries.iter().any(|entry| score > entry.score),
+            None => true,
+        }
+    }
+
+    pub fn add(&mut self, mode: &str, name: &str, score: f64) {
+        let entries = self.modes.entry(mode.to_string()).or_default();
+        let rank = entries.iter().take_while(|entry| entry.score >= score).count();
+        entries.insert(rank, Entry { name: name.to_string(), score });
+        entries.truncate(KEEP);
+    }
+
+    // Table of every game mode's scores, for printing
+    pub fn table(&self) -> String {
+        if self.modes.is_empty() {
+            return "No high scores yet".to_string();
+        }
+        let mut lines = Vec::new();
+        for (mode, entries) in self.modes.iter() {
+            lines.push(mode.clone());
+            for (i, entry) in entries.iter().enumerate() {
+                lines.push(format!("{:>4}. {:<width$} {:>4.0}%", i + 1, entry.name, entry.score * 100.0, width = NAME_LENGTH));
+            }
+        }
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,173 @@
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::{Mode, Simulation};
+use crate::tuning::Tuning;
+
+// Version of the share code format, written as its first byte so older codes can be told apart
+const VERSION: u8 = 1;
+
+// Largest a share code may inflate to, so a bad code cannot eat all the memory
+const MAX_SIZE: usize = 1 << 20;
+
+// URL-safe base64 digits, so a code survives being pasted into links and chat
+const DIGITS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// What can move or change of a planet, rounded so the code stays short
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SharedPlanet {
+    color: [f32; 4],
+    mass: f64,
+    radius: f64,
+    x: f64,
+    y: f64,
+    v_x: f64,
+    v_y: f64,
+}
+
+// The planets and tuning of a running simulation, packed into a short string that can be shared
+// without a scene file and unpacked with --from_code. Satellites are left out, as are any scene's
+// walls, emitters and the like.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShareCode {
+    pub seed: u64,
+    pub mode: Mode,
+    pub width: f64,  // Size of the world the planets were placed in
+    pub height: f64,
+    pub tuning: Tuning,
+    planets: Vec<SharedPlanet>,
+}
+
+fn round(x: f64) -> f64 {
+    (x * 1000.0).round() / 1000.0
+}
+
+impl ShareCode {
+    pub fn capture(sim: &Simulation) -> ShareCode {
+        let params = &sim.params;
+        let planets: Vec<SharedPlanet> = sim.planets.iter()
+            .filter(|planet| !planet.fixed())
+            .map(|planet| SharedPlanet {
+                color: planet.color.map(|c| (c * 1000.0).round() / 1000.0),
+                mass: round(planet.mass),
+                radius: round(planet.radius),
+                x: round(planet.x),
+                y: round(planet.y),
+                v_x: round(planet.v_x),
+                v_y: round(planet.v_y),
+            })
+            .collect();
+        ShareCode {
+            seed: sim.seed,
+            mode: params.mode,
+            width: params.width,
+            height: params.height,
+            tuning: Tuning {
+                gravity_constant: Some(params.gravity_constant),
+                add_chance: Some(params.add_chance),
+                num_planets: Some(planets.len()),
+                palette: params.palette,
+                anisotropy: params.anisotropy,
+                oblateness: params.oblateness,
+            },
+            planets,
+        }
+    }
+
+    // Moves the planets of a simulation set up from this code to where they were when it was made.
+    // As with autosaves, they are only moved if there are as many, which there are unless a scene
+    // or demo has changed the setup.
+    pub fn restore(&self, sim: &mut Simulation) {
+        if sim.planets.iter().filter(|planet| !planet.fixed()).count() != self.planets.len() {
+            return;
+        }
+        for (planet, shared) in sim.planets.iter_mut().filter(|planet| !planet.fixed()).zip(self.planets.iter()) {
+            planet.color = shared.color;
+            planet.mass = shared.mass;
+            planet.radius = shared.radius;
+            planet.x = shared.x;
+            planet.y = shared.y;
+            planet.v_x = shared.v_x;
+            planet.v_y = shared.v_y;
+        }
+    }
+
+    // The code as a string: its version, then its contents as deflated JSON, all in base64
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("Could not write share code");
+        let mut bytes = vec![VERSION];
+        bytes.extend(compress_to_vec(&json, 10));
+        let mut code = String::with_capacity(bytes.len() * 4 / 3 + 3);
+        for chunk in bytes.chunks(3) {
+            let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+            for i in 0..=chunk.len() {
+                code.push(DIGITS[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            }
+        }
+        code
+    }
+
+    pub fn decode(code: &str) -> Result<ShareCode, String> {
+        let invalid = || "Share code is not valid: check it was copied in full".to_string();
+        let mut bytes = Vec::with_capacity(code.len() * 3 / 4);
+        let (mut bits, mut count) = (0u32, 0);
+        for c in code.trim().bytes() {
+            let digit = DIGITS.iter().position(|&d| d == c).ok_or_else(invalid)?;
+            bits = bits << 6 | digit as u32;
+            count += 6;
+            if count >= 8 {
+                count -= 8;
+                bytes.push((bits >> count) as u8);
+            }
+        }
+        // Whatever is left over is padding from the last byte's digits, which is all zeros and never
+        // a whole digit, so anything else means the code was cut short or mistyped
+        if count >= 6 || bits & ((1 << count) - 1) != 0 {
+            return Err(invalid());
+        }
+        match bytes.split_first() {
+            Some((&VERSION, deflated)) => {
+                let json = decompress_to_vec_with_limit(deflated, MAX_SIZE).map_err(|_| invalid())?;
+                serde_json::from_slice(&json).map_err(|_| invalid())
+            },
+            Some((version, _)) => Err(format!("Share code is from another version of orbits (format {})", version)),
+            None => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShareCode;
+    use crate::simulation::{test_params, Simulation};
+
+    // Codes from a range of setups, between them packing every number of bytes mod 3
+    fn codes() -> Vec<ShareCode> {
+        (1..=4).flat_map(|planets| (0..4).map(move |seed| ShareCode::capture(&Simulation::new(test_params(planets), seed)))).collect()
+    }
+
+    #[test]
+    fn codes_decode_to_what_was_encoded() {
+        let mut lengths = [false; 3];
+        for code in codes() {
+            let encoded = code.encode();
+            lengths[encoded.len() * 3 / 4 % 3] = true;
+            let decoded = ShareCode::decode(&encoded).unwrap();
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&code).unwrap());
+        }
+        assert_eq!(lengths, [true; 3], "codes did not cover every length of bytes mod 3");
+    }
+
+    #[test]
+    fn truncated_codes_are_rejected() {
+        for code in codes() {
+            let encoded = code.encode();
+            for end in 0..encoded.len() {
+                assert!(ShareCode::decode(&encoded[..end]).is_err(), "{} decoded when cut to {} digits", encoded, end);
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+// Gravity that pulls harder along one axis than the other, and optionally shears sideways, for
+// stretched orbits that could never happen under real gravity. Every pull between bodies is passed
+// through the symmetric matrix [[x, shear], [shear, y]], so x and y scale the effective G along each
+// axis and shear turns pulls towards the diagonals.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Anisotropy {
+    pub x: f64,     // Multiple of G along x
+    pub y: f64,     // Multiple of G along y
+    #[serde(default)]
+    pub shear: f64, // How much of each axis' pull leaks into the other
+}
+
+impl Anisotropy {
+    // Reads anisotropy written as "x,y" or "x,y,shear"
+    pub fn parse(s: &str) -> Result<Anisotropy, String> {
+        let values: Vec<f64> = s.split(',')
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("Anisotropy must be two or three numbers x,y[,shear]: {}", s))?;
+        match values[..] {
+            [x, y] => Ok(Anisotropy { x, y, shear: 0.0 }),
+            [x, y, shear] => Ok(Anisotropy { x, y, shear }),
+            _ => Err(format!("Anisotropy must be two or three numbers x,y[,shear]: {}", s)),
+        }
+    }
+
+    // A pull as it acts under this anisotropy
+    pub fn apply(self, a_x: f64, a_y: f64) -> (f64, f64) {
+        (self.x * a_x + self.shear * a_y, self.shear * a_x + self.y * a_y)
+    }
+}
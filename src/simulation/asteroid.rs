@@ -0,0 +1,210 @@
+use serde::Deserialize;
+
+use super::{Planet, PLANET_MASS};
+
+// Colour asteroids are drawn in, the same for all of them so their shapes read as rock
+pub const ASTEROID_COLOR: [f32; 4] = [0.6, 0.5, 0.4, 1.0];
+
+// Roughly how many point masses an asteroid's mass is spread over. More follow the shape more
+// closely, but every one is another pull to sum for every satellite on every step.
+const MASS_POINTS: f64 = 48.0;
+
+fn default_mass() -> f64 {
+    PLANET_MASS
+}
+
+// A body shaped as a polygon, as given in the `asteroids` section of a scene file, that pulls like a
+// slab of rock of even density filling it. Asteroids are added after the planets and stay where they
+// are put, and satellites crash into them wherever they touch the outline.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Asteroid {
+    pub x: f64,
+    pub y: f64,
+    #[serde(default = "default_mass")]
+    pub mass: f64,              // Positive; 1000 is as heavy as a planet
+    pub points: Vec<[f64; 2]>,  // Corners (in px) relative to (x, y), in order round the outline
+}
+
+impl Asteroid {
+    // Checks the asteroid can be filled with mass: positive, inside at least three corners enclosing
+    // some area
+    pub fn validate(&self) -> Result<(), String> {
+        if self.mass <= 0.0 {
+            return Err("Asteroids must have positive mass".to_string());
+        }
+        if self.points.len() < 3 || area(&self.points) == 0.0 {
+            return Err("Asteroids must have at least three corners enclosing some area".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn planet(&self) -> Planet {
+        let outline = Outline::new(&self.points);
+        let (x, y) = (self.x + outline.centre.0, self.y + outline.centre.1);
+        Planet {
+            color: ASTEROID_COLOR,
+            mass: self.mass,
+            radius: outline.reach(),
+            x,
+            y,
+            z: 0.0,
+            v_x: 0.0,
+            v_y: 0.0,
+            accreted: 0.0,
+            atmosphere_height: 0.0,
+            drag_coefficient: 0.0,
+            oblateness: None,
+            star: false,
+            outline: Some(outline),
+            tether: None,
+        }
+    }
+}
+
+// Area enclosed by the corners, whichever way round they go
+fn area(points: &[[f64; 2]]) -> f64 {
+    let twice: f64 = (0..points.len()).map(|i| {
+        let ([x1, y1], [x2, y2]) = (points[i], points[(i + 1) % points.len()]);
+        x1 * y2 - x2 * y1
+    }).sum();
+    twice.abs() / 2.0
+}
+
+// Shape of a planet that is not round, and the point masses standing in for its even density. The
+// masses sit at the centres of the cells of a grid laid over the shape that fall inside it, each
+// softened over half a cell so that the pull stays finite among them.
+pub struct Outline {
+    pub corners: Vec<(f64, f64)>,    // Corners relative to the centre of mass, in order round the outline
+    pub triangles: Vec<[usize; 3]>,  // Corners of triangles filling the shape, for drawing it however concave it is
+    masses: Vec<(f64, f64)>,         // Point masses relative to the centre of mass, sharing the mass equally
+    softening: f64,                  // Length (in px) each point mass's pull is softened over
+    centre: (f64, f64),              // Centre of mass relative to where the corners were given from
+}
+
+impl Outline {
+    fn new(points: &[[f64; 2]]) -> Outline {
+        let spacing = (area(points) / MASS_POINTS).sqrt();
+        let (min_x, max_x) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p[0]), hi.max(p[0])));
+        let (min_y, max_y) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p[1]), hi.max(p[1])));
+        let corners: Vec<(f64, f64)> = points.iter().map(|p| (p[0], p[1])).collect();
+        let mut masses = Vec::new();
+        let mut y = min_y + spacing / 2.0;
+        while y < max_y {
+            let mut x = min_x + spacing / 2.0;
+            while x < max_x {
+                if inside(&corners, x, y) {
+                    masses.push((x, y));
+                }
+                x += spacing;
+            }
+            y += spacing;
+        }
+        // A shape too thin for any cell to fall inside pulls from the middle of its corners
+        if masses.is_empty() {
+            let n = corners.len() as f64;
+            masses.push((corners.iter().map(|c| c.0).sum::<f64>() / n, corners.iter().map(|c| c.1).sum::<f64>() / n));
+        }
+        let n = masses.len() as f64;
+        let centre = (masses.iter().map(|m| m.0).sum::<f64>() / n, masses.iter().map(|m| m.1).sum::<f64>() / n);
+        Outline {
+            triangles: triangulate(&corners),
+            corners: corners.iter().map(|(x, y)| (x - centre.0, y - centre.1)).collect(),
+            masses: masses.iter().map(|(x, y)| (x - centre.0, y - centre.1)).collect(),
+            softening: spacing / 2.0,
+            centre,
+        }
+    }
+
+    // Distance (in px) from the centre of mass to the furthest corner
+    pub fn reach(&self) -> f64 {
+        self.corners.iter().map(|(x, y)| x.hypot(*y)).fold(0.0, f64::max)
+    }
+
+    // Whether a circle of the given radius at (x, y) from the centre of mass overlaps the shape
+    pub fn touches(&self, x: f64, y: f64, radius: f64) -> bool {
+        inside(&self.corners, x, y) || (0..self.corners.len()).any(|i| {
+            let (a, b) = (self.corners[i], self.corners[(i + 1) % self.corners.len()]);
+            segment_distance(a, b, x, y) < radius
+        })
+    }
+
+    // Speed per second (in px/s^2) a point (x, y, z) from the centre of mass loses towards the shape,
+    // with `gm` the gravitational constant times the whole mass, as x, y and z parts pointing away
+    // from it as `planet_pull` gives them
+    pub fn fall(&self, gm: f64, [x, y, z]: [f64; 3], epsilon: f64) -> [f64; 3] {
+        let share = gm / self.masses.len() as f64;
+        let soft = self.softening * self.softening + epsilon * epsilon;
+        let mut a = [0.0; 3];
+        for (m_x, m_y) in self.masses.iter() {
+            let d = [x - m_x, y - m_y, z];
+            let r_sq = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+            if r_sq == 0.0 {
+                continue;
+            }
+            let pull = share / (r_sq + soft) / r_sq.sqrt();
+            for (a, d) in a.iter_mut().zip(d) {
+                *a += pull * d;
+            }
+        }
+        a
+    }
+}
+
+// Triangles filling the outline through the corners, by clipping off one ear after another: a corner
+// turning the same way as the outline as a whole with no other corner inside the triangle it makes
+// with its neighbours. An outline that crosses itself has no ears left at some point, and what
+// remains is filled as a fan.
+fn triangulate(corners: &[(f64, f64)]) -> Vec<[usize; 3]> {
+    let turn = |a: (f64, f64), b: (f64, f64), c: (f64, f64)| (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+    let winding: f64 = (0..corners.len()).map(|i| {
+        let (a, b) = (corners[i], corners[(i + 1) % corners.len()]);
+        a.0 * b.1 - b.0 * a.1
+    }).sum();
+    let mut left: Vec<usize> = (0..corners.len()).collect();
+    let mut triangles = Vec::new();
+    while left.len() > 3 {
+        let n = left.len();
+        let ear = (0..n).find(|&i| {
+            let (a, b, c) = (corners[left[(i + n - 1) % n]], corners[left[i]], corners[left[(i + 1) % n]]);
+            turn(a, b, c) * winding > 0.0 && !left.iter().any(|&j| {
+                let p = corners[j];
+                p != a && p != b && p != c && inside(&[a, b, c], p.0, p.1)
+            })
+        });
+        match ear {
+            Some(i) => {
+                triangles.push([left[(i + n - 1) % n], left[i], left[(i + 1) % n]]);
+                left.remove(i);
+            },
+            None => break,
+        }
+    }
+    for i in 1..left.len() - 1 {
+        triangles.push([left[0], left[i], left[i + 1]]);
+    }
+    triangles
+}
+
+// Whether (x, y) is inside the outline through the corners, counting crossings of a ray to the right
+fn inside(corners: &[(f64, f64)], x: f64, y: f64) -> bool {
+    let mut inside = false;
+    for i in 0..corners.len() {
+        let ((x1, y1), (x2, y2)) = (corners[i], corners[(i + 1) % corners.len()]);
+        if (y1 > y) != (y2 > y) && x < x1 + (y - y1) * (x2 - x1) / (y2 - y1) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+// Distance from (x, y) to the nearest point on the segment from a to b
+fn segment_distance(a: (f64, f64), b: (f64, f64), x: f64, y: f64) -> f64 {
+    let (d_x, d_y) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = d_x * d_x + d_y * d_y;
+    let t = match length_sq > 0.0 {
+        true => (((x - a.0) * d_x + (y - a.1) * d_y) / length_sq).clamp(0.0, 1.0),
+        false => 0.0,
+    };
+    (x - a.0 - t * d_x).hypot(y - a.1 - t * d_y)
+}
@@ -0,0 +1,141 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Origin, Simulation};
+
+// What can move or change of a planet
+#[derive(Serialize, Deserialize)]
+struct SavedPlanet {
+    color: [f32; 4],
+    mass: f64,
+    radius: f64,
+    x: f64,
+    y: f64,
+    v_x: f64,
+    v_y: f64,
+    accreted: f64,
+}
+
+// A live satellite, without its trail or the history its stability is estimated from
+#[derive(Serialize, Deserialize)]
+struct SavedSatellite {
+    color: [f32; 4],
+    radius: f64,
+    mass: f64,
+    x: f64,
+    y: f64,
+    #[serde(default)]
+    z: f64,      // Height out of the plane, when bodies move in three dimensions
+    v_x: f64,
+    v_y: f64,
+    #[serde(default)]
+    v_z: f64,
+    age: f64,    // Seconds of simulation time since it was spawned
+    origin: Origin,
+    generation: u32,
+}
+
+// The bodies of a running simulation, saved every so often so that one restarted after a crash or
+// hang can pick up where it left off rather than starting again from nothing. Everything else, such
+// as trails, ripples and the random number generator, starts afresh.
+#[derive(Serialize, Deserialize)]
+pub struct Autosave {
+    time: f64,
+    planets: Vec<SavedPlanet>,
+    satellites: Vec<SavedSatellite>,
+}
+
+impl Autosave {
+    pub fn capture(sim: &Simulation) -> Autosave {
+        Autosave {
+            time: sim.time,
+            planets: sim.planets.iter()
+                .map(|planet| SavedPlanet {
+                    color: planet.color,
+                    mass: planet.mass,
+                    radius: planet.radius,
+                    x: planet.x,
+                    y: planet.y,
+                    v_x: planet.v_x,
+                    v_y: planet.v_y,
+                    accreted: planet.accreted,
+                })
+                .collect(),
+            satellites: sim.satellites.iter()
+                .filter(|sat| sat.fate.is_none())
+                .map(|sat| SavedSatellite {
+                    color: sat.color,
+                    radius: sat.radius,
+                    mass: sat.mass,
+                    x: sat.x,
+                    y: sat.y,
+                    z: sat.z,
+                    v_x: sat.v_x,
+                    v_y: sat.v_y,
+                    v_z: sat.v_z,
+                    age: sim.time - sat.born,
+                    origin: sat.origin,
+                    generation: sat.generation,
+                })
+                .collect(),
+        }
+    }
+
+    // Writes the save to a file alongside before moving it into place, so that a crash part way
+    // through writing cannot leave a save that is cut short
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let contents = serde_yaml::to_string(self).map_err(|e| format!("Could not write autosave: {}", e))?;
+        let partial = format!("{}.partial", path);
+        fs::write(&partial, contents)
+            .and_then(|_| fs::rename(&partial, path))
+            .map_err(|e| format!("Could not write autosave {}: {}", path, e))
+    }
+
+    // Reads a save, or None if nothing has been saved there yet
+    pub fn load(path: &str) -> Result<Option<Autosave>, String> {
+        if !Path::new(path).exists() {
+            return Ok(None);
+        }
+        let file = fs::File::open(path).map_err(|e| format!("Could not open autosave {}: {}", path, e))?;
+        serde_yaml::from_reader(file).map(Some).map_err(|e| format!("Could not read autosave {}: {}", path, e))
+    }
+
+    // Puts the saved bodies into a simulation set up the same way, replacing its satellites. The
+    // planets are only moved back if there are as many as were saved, as otherwise the save was made
+    // with other settings and they would not line up.
+    pub fn restore(self, sim: &mut Simulation) {
+        sim.time = self.time;
+        if self.planets.len() == sim.planets.len() {
+            for (planet, saved) in sim.planets.iter_mut().zip(self.planets) {
+                planet.color = saved.color;
+                planet.mass = saved.mass;
+                planet.radius = saved.radius;
+                planet.x = saved.x;
+                planet.y = saved.y;
+                planet.v_x = saved.v_x;
+                planet.v_y = saved.v_y;
+                planet.accreted = saved.accreted;
+            }
+            // Moons carry on round their orbits from where they were saved
+            for i in 0..sim.planets.len() {
+                if let Some(mut tether) = sim.planets[i].tether {
+                    tether.find(&sim.planets[i], &sim.planets[tether.parent]);
+                    sim.planets[i].tether = Some(tether);
+                }
+            }
+        }
+        sim.satellites.clear();
+        for saved in self.satellites {
+            let mut sat = sim.new_satellite(saved.color, saved.x, saved.y, saved.v_x, saved.v_y, saved.origin);
+            sat.z = saved.z;
+            sat.v_z = saved.v_z;
+            sat.radius = saved.radius;
+            sat.mass = saved.mass;
+            sat.born = self.time - saved.age;
+            sat.generation = saved.generation;
+            sim.satellites.push(sat);
+        }
+    }
+}
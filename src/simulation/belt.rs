@@ -0,0 +1,75 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+use serde::Deserialize;
+
+use super::Planet;
+
+// A ring of small bodies on orbits around a planet, as given in the `belts` section of a scene file
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Belt {
+    #[serde(default)]
+    pub planet: usize,       // Index of the planet the belt circles
+    pub inner: f64,          // Smallest semi-major axis, in px from the planet's centre
+    pub outer: f64,          // Largest semi-major axis
+    #[serde(default = "default_count")]
+    pub count: usize,        // Bodies in the belt
+    #[serde(default = "default_eccentricity")]
+    pub eccentricity: f64,   // Scale of the Rayleigh distribution eccentricities are drawn from
+    #[serde(default = "default_color")]
+    pub color: [f32; 4],
+}
+
+fn default_count() -> usize { 2000 }
+fn default_eccentricity() -> f64 { 0.05 }
+fn default_color() -> [f32; 4] { [0.7, 0.65, 0.6, 1.0] }
+
+// Keeps the odd very eccentric orbit from the tail of the distribution from diving into the planet
+const MAX_ECCENTRICITY: f64 = 0.5;
+
+// Bodies in the belt of the Kirkwood demo
+const KIRKWOOD_COUNT: usize = 3000;
+
+impl Belt {
+    // Belt around the first planet of a binary reaching from just inside its 4:1 resonance with the
+    // second planet to just beyond the 2:1, taking in the 3:1, 5:2 and 7:3 resonances between, where
+    // the Kirkwood gaps open
+    pub fn kirkwood(planets: &[Planet]) -> Belt {
+        let separation = (planets[1].x - planets[0].x).hypot(planets[1].y - planets[0].y);
+        Belt {
+            planet: 0,
+            inner: 0.38 * separation,
+            outer: 0.66 * separation,
+            count: KIRKWOOD_COUNT,
+            eccentricity: default_eccentricity(),
+            color: default_color(),
+        }
+    }
+
+    // Bodies on random orbits filling the belt evenly by area, each as (x, y, v_x, v_y), going round the
+    // same way as the planets of a binary and carried along with their planet
+    pub fn bodies<R: Rng>(&self, planets: &[Planet], gravity_constant: f64, rng: &mut R) -> Vec<(f64, f64, f64, f64)> {
+        let planet = match planets.get(self.planet) {
+            Some(planet) => planet,
+            None => return Vec::new(),
+        };
+        let mu = gravity_constant * planet.mass;
+        (0..self.count).map(|_| {
+            let u: f64 = rng.gen_range(0.0..1.0);
+            let semi_major = (self.inner.powi(2) + u * (self.outer.powi(2) - self.inner.powi(2))).sqrt();
+            let u: f64 = rng.gen_range(0.0..1.0);
+            let eccentricity = (self.eccentricity * (-2.0 * (1.0 - u).ln()).sqrt()).min(MAX_ECCENTRICITY);
+            let periapsis: f64 = rng.gen_range(0.0..2.0 * PI);
+            let anomaly: f64 = rng.gen_range(0.0..2.0 * PI);
+
+            // Position and velocity at the given true anomaly, in the radial and tangential directions
+            let semi_latus = semi_major * (1.0 - eccentricity * eccentricity);
+            let r = semi_latus / (1.0 + eccentricity * anomaly.cos());
+            let speed = (mu / semi_latus).sqrt();
+            let (v_r, v_t) = (speed * eccentricity * anomaly.sin(), speed * (1.0 + eccentricity * anomaly.cos()));
+            let (sin, cos) = (periapsis + anomaly).sin_cos();
+            (planet.x + r * cos, planet.y + r * sin, planet.v_x + v_r * cos - v_t * sin, planet.v_y + v_r * sin + v_t * cos)
+        }).collect()
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use super::Satellite;
+
+// What happens when bodies run into each other
+#[derive(Clone, Copy, PartialEq)]
+pub enum Collisions {
+    Kill,   // Satellites hitting a planet die, and satellites pass through each other
+    Bounce, // Satellites bounce elastically off each other, and die on hitting a planet
+    Merge,  // Bodies that touch combine their mass, momentum and volume
+}
+
+// Pairs of live satellites touching each other, each pair once with the lower index first. Neighbours
+// are found through a grid of cells as wide as the largest satellite, so each satellite only looks at
+// those nearby. Satellites on rails are left out, as nothing can move them.
+pub fn touching(satellites: &[Satellite]) -> Vec<(usize, usize)> {
+    let free = |sat: &Satellite| sat.fate.is_none() && sat.rails.is_none();
+    let size = 2.0 * satellites.iter().filter(|sat| free(sat)).map(|sat| sat.radius).fold(0.0, f64::max);
+    if size <= 0.0 {
+        return Vec::new();
+    }
+    let cell = |sat: &Satellite| ((sat.x / size).floor() as i64, (sat.y / size).floor() as i64);
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, sat) in satellites.iter().enumerate().filter(|(_, sat)| free(sat)) {
+        grid.entry(cell(sat)).or_default().push(i);
+    }
+
+    let mut pairs = Vec::new();
+    for (i, sat) in satellites.iter().enumerate().filter(|(_, sat)| free(sat)) {
+        let (cx, cy) = cell(sat);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for &j in grid.get(&(cx + dx, cy + dy)).into_iter().flatten().filter(|j| **j > i) {
+                    let other = &satellites[j];
+                    if (sat.x - other.x).hypot(sat.y - other.y) < sat.radius + other.radius {
+                        pairs.push((i, j));
+                    }
+                }
+            }
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+// Merges `other` into `sat`, which ends up at their centre of mass with their combined mass and
+// momentum, and the radius holding both their volumes
+pub fn combine(sat: &mut Satellite, other: &Satellite) {
+    let mass = sat.mass + other.mass;
+    sat.x = (sat.x * sat.mass + other.x * other.mass) / mass;
+    sat.y = (sat.y * sat.mass + other.y * other.mass) / mass;
+    sat.v_x = (sat.v_x * sat.mass + other.v_x * other.mass) / mass;
+    sat.v_y = (sat.v_y * sat.mass + other.v_y * other.mass) / mass;
+    sat.radius = (sat.radius.powi(3) + other.radius.powi(3)).cbrt();
+    sat.mass = mass;
+}
+
+// Mass a satellite is taken to have when bouncing, so that massless satellites bounce as equals
+fn inertia(sat: &Satellite) -> f64 {
+    if sat.mass > 0.0 { sat.mass } else { 1.0 }
+}
+
+// Bounces two touching satellites elastically off each other, with an impulse along the line between
+// their centres that keeps their momentum and kinetic energy. They are also pushed apart until they
+// only just touch, the lighter further, so that they cannot stay stuck together.
+pub fn bounce(a: &mut Satellite, b: &mut Satellite) {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let distance = dx.hypot(dy);
+    if distance == 0.0 {
+        return;
+    }
+    let (n_x, n_y) = (dx / distance, dy / distance);
+    let (mass_a, mass_b) = (inertia(a), inertia(b));
+    let (share_a, share_b) = (mass_b / (mass_a + mass_b), mass_a / (mass_a + mass_b));
+
+    let overlap = a.radius + b.radius - distance;
+    a.x -= n_x * overlap * share_a;
+    a.y -= n_y * overlap * share_a;
+    b.x += n_x * overlap * share_b;
+    b.y += n_y * overlap * share_b;
+
+    // Only satellites closing on each other bounce, so a pair already parting is left to part
+    let closing = (a.v_x - b.v_x) * n_x + (a.v_y - b.v_y) * n_y;
+    if closing > 0.0 {
+        a.v_x -= 2.0 * share_a * closing * n_x;
+        a.v_y -= 2.0 * share_a * closing * n_y;
+        b.v_x += 2.0 * share_b * closing * n_x;
+        b.v_y += 2.0 * share_b * closing * n_y;
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+
+use super::Planet;
+
+// Where a pair of planets stood after one step
+#[derive(Clone, Copy)]
+pub struct Pose {
+    pub x: f64,     // Position of the barycentre of the pair
+    pub y: f64,
+    pub angle: f64, // Angle in radians of the line from the first planet to the second, counting on past whole turns
+}
+
+// The frame turning with a pair of planets, followed from step to step by the barycentre of the pair
+// and the line between them, so it keeps up with any orbit they are on and not only the circular
+// orbit of a binary. Poses are kept for as many steps as trails are long, so each point of a trail
+// can be put back where it was in the frame when it was recorded.
+#[derive(Default)]
+pub struct CoRotation {
+    origin: (f64, f64),    // Position of the barycentre when the pair were first seen, where the view keeps it
+    poses: VecDeque<Pose>, // Pose after each recent step, newest last
+}
+
+impl CoRotation {
+    // Notes where the planets stand now, keeping the last `keep` poses. Anything but exactly two
+    // planets has no frame turning with it, and the frame starts afresh when there are two again.
+    pub fn record(&mut self, planets: &[Planet], keep: usize) {
+        let (a, b) = match planets {
+            [a, b] => (a, b),
+            _ => return self.poses.clear(),
+        };
+        let mass = a.mass + b.mass;
+        let (x, y) = match mass != 0.0 {
+            true => ((a.x * a.mass + b.x * b.mass) / mass, (a.y * a.mass + b.y * b.mass) / mass),
+            false => ((a.x + b.x) / 2.0, (a.y + b.y) / 2.0),
+        };
+        let heading = (b.y - a.y).atan2(b.x - a.x);
+        let angle = match self.poses.back() {
+            // Unwrapped, so the angle keeps counting up past a full turn rather than jumping back
+            Some(last) => {
+                let turn = heading - last.angle;
+                last.angle + (turn + std::f64::consts::PI).rem_euclid(std::f64::consts::TAU) - std::f64::consts::PI
+            },
+            None => {
+                self.origin = (x, y);
+                heading
+            },
+        };
+        self.poses.push_back(Pose { x, y, angle });
+        while self.poses.len() > keep.max(1) {
+            self.poses.pop_front();
+        }
+    }
+
+    // Forgets the frame, to start afresh from the next pose recorded
+    pub fn clear(&mut self) {
+        self.poses.clear();
+    }
+
+    // Latest pose, if there is a pair of planets to turn with
+    pub fn now(&self) -> Option<Pose> {
+        self.poses.back().copied()
+    }
+
+    // Where the barycentre is kept in the view
+    pub fn origin(&self) -> (f64, f64) {
+        self.origin
+    }
+
+    // Point where something seen at `pos` `age` steps ago would be now, had it held still in the frame
+    // turning with the planets, so it is drawn where it was in that frame. Points older than the poses
+    // kept are taken from the oldest.
+    pub fn carry(&self, pos: (f64, f64), age: usize) -> (f64, f64) {
+        let now = match self.poses.back() {
+            Some(&now) => now,
+            None => return pos,
+        };
+        let then = self.poses[self.poses.len().saturating_sub(age + 1)];
+        let (sin, cos) = (now.angle - then.angle).sin_cos();
+        let (x, y) = (pos.0 - then.x, pos.1 - then.y);
+        (now.x + x * cos - y * sin, now.y + x * sin + y * cos)
+    }
+}
@@ -0,0 +1,60 @@
+use super::Satellite;
+
+// Size (in px) of each cell of the density grid
+const CELL_SIZE: f64 = 100.0;
+
+// Live satellites counted over a coarse grid, used to steer new satellites away from crowded regions
+// and towards empty ones so the picture stays balanced over long runs
+pub struct Density {
+    cols: usize,
+    rows: usize,
+    counts: Vec<usize>, // Live satellites in each cell, row by row
+    mean: f64,          // Mean count over all cells
+}
+
+impl Density {
+    pub fn new(width: f64, height: f64) -> Density {
+        let cols = (width / CELL_SIZE).ceil().max(1.0) as usize;
+        let rows = (height / CELL_SIZE).ceil().max(1.0) as usize;
+        Density {
+            cols,
+            rows,
+            counts: vec![0; cols * rows],
+            mean: 0.0,
+        }
+    }
+
+    fn cell(&self, x: f64, y: f64) -> Option<usize> {
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let (col, row) = ((x / CELL_SIZE) as usize, (y / CELL_SIZE) as usize);
+        if col < self.cols && row < self.rows {
+            Some(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    // Recounts the live satellites
+    pub fn update(&mut self, satellites: &[Satellite]) {
+        for count in self.counts.iter_mut() {
+            *count = 0;
+        }
+        let mut total = 0;
+        for sat in satellites.iter().filter(|sat| sat.fate.is_none()) {
+            if let Some(i) = self.cell(sat.x, sat.y) {
+                self.counts[i] += 1;
+                total += 1;
+            }
+        }
+        self.mean = total as f64 / self.counts.len() as f64;
+    }
+
+    // Chance of accepting a new satellite at (x, y): certain where the cell is no busier than
+    // average, falling the more crowded it is
+    pub fn acceptance(&self, x: f64, y: f64) -> f64 {
+        let count = self.cell(x, y).map_or(0, |i| self.counts[i]) as f64;
+        ((self.mean + 1.0) / (count + 1.0)).min(1.0)
+    }
+}
@@ -0,0 +1,100 @@
+use rand::Rng;
+
+use super::{Params, Planet, PLANET_MASS};
+
+// Mean lifetime (in seconds) of a satellite that neither crashes nor escapes
+const MEAN_LIFETIME: f64 = 300.0;
+
+// Mass a planet gains from each satellite that crashes into it
+const ACCRETION: f64 = 10.0;
+
+// Multiple of its starting mass at which a planet breaks up
+const BREAKUP_MASS: f64 = 2.0;
+
+// Satellites thrown off when a planet breaks up
+const BREAKUP_SATELLITES: usize = 40;
+
+// Periods (in seconds) of the slow swings each drifting parameter is made from. They share no
+// common multiple within a day, so the mix of parameters never repeats over a full day.
+const DRIFT_PERIODS: [f64; 3] = [2.1 * 3600.0, 5.3 * 3600.0, 13.7 * 3600.0];
+
+// A parameter that wanders between (1 - amplitude) and (1 + amplitude) times its starting value
+struct Drift {
+    base: f64,
+    amplitude: f64,
+    phases: [f64; 3],
+}
+
+impl Drift {
+    fn new<R: Rng>(base: f64, amplitude: f64, rng: &mut R) -> Drift {
+        let mut phases = [0.0; 3];
+        for phase in phases.iter_mut() {
+            *phase = rng.gen_range(0.0..2.0 * std::f64::consts::PI);
+        }
+        Drift {
+            base,
+            amplitude,
+            phases,
+        }
+    }
+
+    fn value(&self, time: f64) -> f64 {
+        let swing: f64 = DRIFT_PERIODS.iter().zip(self.phases.iter())
+            .map(|(period, phase)| (2.0 * std::f64::consts::PI * time / period + phase).sin())
+            .sum::<f64>() / DRIFT_PERIODS.len() as f64;
+        self.base * (1.0 + self.amplitude * swing)
+    }
+}
+
+// Slow evolution for displays left running all day: satellites age and die, planets grow from what
+// crashes into them and break up into a burst of new satellites, and the spawn rate, launch speed
+// and gravity drift over hours.
+pub struct Ecosystem {
+    add_chance: Drift,
+    sat_velocity: Drift,
+    gravity_constant: Drift,
+}
+
+impl Ecosystem {
+    pub fn new<R: Rng>(params: &Params, rng: &mut R) -> Ecosystem {
+        Ecosystem {
+            add_chance: Drift::new(params.add_chance, 0.9, rng),
+            sat_velocity: Drift::new(params.sat_velocity, 0.3, rng),
+            gravity_constant: Drift::new(params.gravity_constant, 0.2, rng),
+        }
+    }
+
+    // Moves the drifting parameters to their values at the given time
+    pub fn drift(&self, params: &mut Params, time: f64) {
+        params.add_chance = self.add_chance.value(time);
+        params.sat_velocity = self.sat_velocity.value(time);
+        params.gravity_constant = self.gravity_constant.value(time);
+    }
+
+    // Simulation time a satellite born now dies of old age
+    pub fn expiry<R: Rng>(&self, time: f64, rng: &mut R) -> f64 {
+        let u: f64 = rng.gen_range(0.0..1.0);
+        time - MEAN_LIFETIME * (1.0 - u).ln()
+    }
+
+    // Grows a planet by one crashed satellite, keeping its density constant
+    pub fn accrete(&self, planet: &mut Planet) {
+        let radius = planet.radius / planet.mass.cbrt();
+        planet.mass += ACCRETION;
+        planet.accreted += ACCRETION;
+        planet.radius = radius * planet.mass.cbrt();
+    }
+
+    // Shrinks a planet back to its starting size if it has grown too large, returning the
+    // number of satellites it throws off
+    pub fn breakup(&self, planet: &mut Planet) -> usize {
+        if planet.mass < PLANET_MASS * BREAKUP_MASS {
+            return 0;
+        }
+        let radius = planet.radius / planet.mass.cbrt();
+        planet.mass = PLANET_MASS;
+        planet.accreted = 0.0;
+        planet.radius = radius * planet.mass.cbrt();
+        BREAKUP_SATELLITES
+    }
+}
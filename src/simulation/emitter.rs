@@ -0,0 +1,60 @@
+use rand::Rng;
+use serde::Deserialize;
+
+// A fixed point that launches satellites at a steady rate, as given in the `emitters` section of a scene file
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Emitter {
+    pub x: f64,
+    pub y: f64,
+    pub angle: f64,    // Launch direction in degrees clockwise from the positive x axis, so -90 is up
+    #[serde(default = "default_spread")]
+    pub spread: f64,   // Launch directions vary by up to this many degrees either side of `angle`
+    #[serde(default = "default_speed")]
+    pub speed: f64,    // Launch speed in px/s, varying by up to 10% either way
+    #[serde(default = "default_rate")]
+    pub rate: f64,     // Satellites launched per second
+    #[serde(default = "default_enabled")]
+    pub enabled: bool, // Whether the emitter is launching, toggled at runtime by clicking it
+    #[serde(skip)]
+    pending: f64,      // Fraction of a satellite accumulated towards the next launch
+}
+
+fn default_spread() -> f64 { 10.0 }
+fn default_speed() -> f64 { 400.0 }
+fn default_rate() -> f64 { 5.0 }
+fn default_enabled() -> bool { true }
+
+impl Emitter {
+    // A fountain in the middle of the bottom edge, reaching about three quarters of the way up
+    // under the given downward acceleration
+    pub fn fountain(width: f64, height: f64, gravity: f64) -> Emitter {
+        Emitter {
+            x: width / 2.0,
+            y: height,
+            angle: -90.0,
+            spread: 15.0,
+            speed: (2.0 * gravity * height * 0.75).sqrt(),
+            rate: default_rate(),
+            enabled: true,
+            pending: 0.0,
+        }
+    }
+
+    // Launches due over the next `dt` seconds, as (x, y, v_x, v_y). A disabled emitter launches
+    // nothing and picks up where it left off once enabled again.
+    pub fn emit<R: Rng>(&mut self, dt: f64, rng: &mut R) -> Vec<(f64, f64, f64, f64)> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        self.pending += self.rate * dt;
+        let mut launches = Vec::new();
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            let angle = (self.angle + rng.gen_range(-1.0..=1.0) * self.spread).to_radians();
+            let speed = self.speed * rng.gen_range(0.9..=1.1);
+            launches.push((self.x, self.y, speed * angle.cos(), speed * angle.sin()));
+        }
+        launches
+    }
+}
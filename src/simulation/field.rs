@@ -0,0 +1,31 @@
+use serde::Deserialize;
+
+use super::region::Region;
+
+// How a field changes the physics of satellites inside it
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Effect {
+    Weightless,      // Nothing pulls on them, so they coast in straight lines
+    Drag(f64),       // They slow down, their speed falling by a factor of e every 1/rate seconds
+    Boost([f64; 2]), // A constant push, as an acceleration (in px/s^2)
+}
+
+// An area of the world with physics of its own, as given in the `fields` section of a scene file
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Field {
+    pub region: Region,
+    pub effect: Effect,
+}
+
+impl Field {
+    // Tint the field is shown with, faint enough to see satellites through
+    pub fn color(&self) -> [f32; 4] {
+        match self.effect {
+            Effect::Weightless => [0.3, 0.5, 1.0, 0.15],
+            Effect::Drag(_) => [0.8, 0.5, 0.2, 0.15],
+            Effect::Boost(_) => [0.3, 1.0, 0.4, 0.15],
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use serde::Deserialize;
+
+use super::{Planet, Satellite};
+
+// Angular frequency (in rad/s) of the spring pulling satellites to their targets
+const STIFFNESS: f64 = 6.0;
+
+// Fraction of a formation spent ramping the steering force up from nothing
+const RAMP: f64 = 0.05;
+
+// The shape to form and how often, as given in the `formation` section of a scene file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FormationConfig {
+    pub text: Option<String>,  // Text to spell out
+    pub image: Option<String>, // Path of a PNG image to trace, using its opaque (or else dark) pixels
+    #[serde(default = "default_interval")]
+    pub interval: f64,         // Seconds of free flight between formations
+    #[serde(default = "default_duration")]
+    pub duration: f64,         // Seconds satellites are steered for in each formation
+    #[serde(default = "default_points")]
+    pub points: usize,         // Number of satellites forming the shape
+    #[serde(default = "default_size")]
+    pub size: f64,             // Fraction of the world the shape may span
+}
+
+fn default_interval() -> f64 { 30.0 }
+fn default_duration() -> f64 { 10.0 }
+fn default_points() -> usize { 200 }
+fn default_size() -> f64 { 0.8 }
+
+// Filled cells of a bitmap
+pub struct Shape {
+    width: usize,
+    height: usize,
+    cells: Vec<(usize, usize)>,
+}
+
+impl Shape {
+    // The text as drawn in the built-in bitmap font
+    pub fn text(text: &str) -> Shape {
+        Shape {
+            width: text.chars().count() * 8,
+            height: 8,
+            cells: crate::text::pixels(text),
+        }
+    }
+
+    // The opaque pixels of an image with transparency, or its dark pixels otherwise
+    pub fn image(path: &str) -> Result<Shape, String> {
+        let image = image::open(path).map_err(|e| format!("Could not read image {}: {}", path, e))?.to_luma_alpha8();
+        let transparent = image.pixels().any(|pixel| pixel[1] < 128);
+        let cells = image.enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel[1] >= 128 && (transparent || pixel[0] < 128))
+            .map(|(x, y, _)| (x as usize, y as usize))
+            .collect();
+        Ok(Shape {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            cells,
+        })
+    }
+
+    pub fn load(config: &FormationConfig) -> Result<Shape, String> {
+        let shape = match (&config.text, &config.image) {
+            (Some(text), None) => Shape::text(text),
+            (None, Some(path)) => Shape::image(path)?,
+            _ => return Err("Formation needs exactly one of text or image".to_string()),
+        };
+        if shape.cells.is_empty() {
+            return Err("Formation shape is empty".to_string());
+        }
+        Ok(shape)
+    }
+}
+
+// Periodically steers satellites onto points of a shape, then releases them back to gravity
+pub struct Formation {
+    shape: Shape,
+    interval: f64,
+    duration: f64,
+    points: usize,
+    size: f64,
+    start: f64,                        // Simulation time the current or next formation starts
+    forming: bool,                     // Whether satellites are currently being steered
+    targets: HashMap<u64, (f64, f64)>, // Point each steered satellite is heading for, by id
+}
+
+impl Formation {
+    pub fn new(config: &FormationConfig, shape: Shape) -> Formation {
+        Formation {
+            shape,
+            interval: config.interval,
+            duration: config.duration,
+            points: config.points,
+            size: config.size,
+            start: config.interval,
+            forming: false,
+            targets: HashMap::new(),
+        }
+    }
+
+    // Number of satellites wanted when a formation is starting at `time`
+    pub fn starting(&self, time: f64) -> Option<usize> {
+        if !self.forming && time >= self.start {
+            Some(self.points)
+        } else {
+            None
+        }
+    }
+
+    // Picks points across the shape, centred in the world, and sends the nearest satellite to each.
+    // Points on top of a planet are skipped, as satellites sent there would only crash.
+    pub fn start<R: Rng>(&mut self, satellites: &[Satellite], planets: &[Planet], width: f64, height: f64, rng: &mut R) {
+        let scale = (width * self.size / self.shape.width as f64).min(height * self.size / self.shape.height as f64);
+        let left = (width - self.shape.width as f64 * scale) / 2.0;
+        let top = (height - self.shape.height as f64 * scale) / 2.0;
+
+        let mut free: Vec<&Satellite> = satellites.iter().filter(|sat| sat.fate.is_none()).collect();
+        self.targets.clear();
+        for _ in 0..self.points {
+            let cell = self.shape.cells[rng.gen_range(0..self.shape.cells.len())];
+            let x = left + (cell.0 as f64 + rng.gen_range(0.0..1.0)) * scale;
+            let y = top + (cell.1 as f64 + rng.gen_range(0.0..1.0)) * scale;
+            let blocked = planets.iter().any(|planet| {
+                (x - planet.x).powi(2) + (y - planet.y).powi(2) < (planet.radius * 1.5).powi(2)
+            });
+            if blocked || free.is_empty() {
+                continue;
+            }
+            let distance = |sat: &Satellite| (sat.x - x).powi(2) + (sat.y - y).powi(2);
+            let nearest = (0..free.len())
+                .min_by(|a, b| distance(free[*a]).partial_cmp(&distance(free[*b])).unwrap())
+                .unwrap();
+            self.targets.insert(free.swap_remove(nearest).id, (x, y));
+        }
+        self.forming = true;
+    }
+
+    // Releases the satellites once the formation has been held long enough
+    pub fn update(&mut self, time: f64) {
+        if self.forming && time >= self.start + self.duration {
+            self.forming = false;
+            self.targets.clear();
+            self.start += self.duration + self.interval;
+        }
+    }
+
+    // Change in velocity over `dt` from the spring pulling a satellite to its target, if it has one.
+    // The spring is critically damped so satellites settle on their points rather than oscillate.
+    pub fn steer(&self, sat: &Satellite, time: f64, dt: f64) -> Option<(f64, f64)> {
+        let target = self.targets.get(&sat.id)?;
+        let strength = ((time - self.start) / (self.duration * RAMP)).min(1.0);
+        let a_x = STIFFNESS * STIFFNESS * (target.0 - sat.x) - 2.0 * STIFFNESS * sat.v_x;
+        let a_y = STIFFNESS * STIFFNESS * (target.1 - sat.y) - 2.0 * STIFFNESS * sat.v_y;
+        Some((strength * a_x * dt, strength * a_y * dt))
+    }
+}
@@ -0,0 +1,222 @@
+use super::{acceleration, pull, Mode, Params, Planet};
+
+// How a satellite is moved on under gravity each step
+#[derive(Clone, Copy, PartialEq)]
+pub enum Integrator {
+    Euler,  // Semi-implicit Euler: velocity first, then position with the new velocity
+    Verlet, // Velocity Verlet, averaging the pull at the start and end of the step
+    Rk4,    // Classic fourth-order Runge-Kutta
+}
+
+// Pull (in px/s^2) it takes for a step to be split in two, and each time as much again for a further
+// substep
+const SUBSTEP_ACCELERATION: f64 = 1000.0;
+
+// Substeps a step starting at (x, y) is split into: more the harder the planets pull there, up to
+// the most the parameters allow, so that satellites skimming a planet do not stride across the
+// bottom of its well in one step and come out with a slingshot they never earned. The pull is taken
+// as if from rest, which is near enough for deciding how finely to step.
+pub fn substeps(planets: &[Planet], params: &Params, x: f64, y: f64) -> usize {
+    if params.max_substeps <= 1 {
+        return 1;
+    }
+    let (a_x, a_y) = acceleration(planets, params, x, y, 0.0, 0.0);
+    ((a_x.hypot(a_y) / SUBSTEP_ACCELERATION).ceil() as usize).clamp(1, params.max_substeps)
+}
+
+// Acceleration (in px/s^2) of a point at (x, y, z) when bodies move in three dimensions, straight
+// towards each planet and downwards in projectile mode. The corrections for relativity, oblateness
+// and anisotropy are only worked out in the plane, so they are left out.
+fn acceleration_3d(planets: &[Planet], params: &Params, [x, y, z]: [f64; 3]) -> [f64; 3] {
+    let mut a = [0.0; 3];
+    for planet in planets.iter() {
+        if let Some(outline) = planet.outline.as_ref() {
+            let fall = outline.fall(params.gravity_constant * planet.mass, [x - planet.x, y - planet.y, z - planet.z], params.epsilon);
+            for (a, fall) in a.iter_mut().zip(fall) {
+                *a -= fall;
+            }
+            continue;
+        }
+        let d = [x - planet.x, y - planet.y, z - planet.z];
+        let r_sq = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+        if r_sq == 0.0 {
+            continue;
+        }
+        let pull = params.gravity_constant * planet.mass / (r_sq + params.epsilon * params.epsilon) / r_sq.sqrt();
+        for (a, d) in a.iter_mut().zip(d) {
+            *a -= pull * d;
+        }
+    }
+    if params.mode == Mode::Projectile {
+        a[1] += params.gravity;
+    }
+    a
+}
+
+impl Integrator {
+    // Position and velocity `dt` seconds on from `state`, both as [x, y, v_x, v_y]. The planets stay
+    // where they are for the whole step, as they do for Euler.
+    pub fn advance(self, planets: &[Planet], params: &Params, state: [f64; 4], dt: f64) -> [f64; 4] {
+        let [x, y, v_x, v_y] = state;
+        match self {
+            Integrator::Euler => {
+                let (v_x, v_y) = pull(planets, params, x, y, v_x, v_y, dt);
+                [x + v_x * dt, y + v_y * dt, v_x, v_y]
+            },
+            Integrator::Verlet => {
+                // The pull at the end of the step depends on the velocity there too when it is
+                // corrected for relativity, so it is taken at the velocity the start's pull points to
+                let (a_x, a_y) = acceleration(planets, params, x, y, v_x, v_y);
+                let x = x + v_x * dt + 0.5 * a_x * dt * dt;
+                let y = y + v_y * dt + 0.5 * a_y * dt * dt;
+                let (b_x, b_y) = acceleration(planets, params, x, y, v_x + a_x * dt, v_y + a_y * dt);
+                [x, y, v_x + 0.5 * (a_x + b_x) * dt, v_y + 0.5 * (a_y + b_y) * dt]
+            },
+            Integrator::Rk4 => {
+                // Each stage gives the rate of change of position (the velocity) and of velocity
+                let stage = |x: f64, y: f64, v_x: f64, v_y: f64| {
+                    let (a_x, a_y) = acceleration(planets, params, x, y, v_x, v_y);
+                    [v_x, v_y, a_x, a_y]
+                };
+                let k1 = stage(x, y, v_x, v_y);
+                let k2 = stage(x + k1[0] * dt / 2.0, y + k1[1] * dt / 2.0, v_x + k1[2] * dt / 2.0, v_y + k1[3] * dt / 2.0);
+                let k3 = stage(x + k2[0] * dt / 2.0, y + k2[1] * dt / 2.0, v_x + k2[2] * dt / 2.0, v_y + k2[3] * dt / 2.0);
+                let k4 = stage(x + k3[0] * dt, y + k3[1] * dt, v_x + k3[2] * dt, v_y + k3[3] * dt);
+                let step = |i: usize| (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]) * dt / 6.0;
+                [x + step(0), y + step(1), v_x + step(2), v_y + step(3)]
+            },
+        }
+    }
+
+    // As advance, for bodies moving in three dimensions, with `state` as [x, y, z, v_x, v_y, v_z]
+    pub fn advance_3d(self, planets: &[Planet], params: &Params, state: [f64; 6], dt: f64) -> [f64; 6] {
+        let [x, y, z, v_x, v_y, v_z] = state;
+        match self {
+            Integrator::Euler => {
+                let [a_x, a_y, a_z] = acceleration_3d(planets, params, [x, y, z]);
+                let (v_x, v_y, v_z) = (v_x + a_x * dt, v_y + a_y * dt, v_z + a_z * dt);
+                [x + v_x * dt, y + v_y * dt, z + v_z * dt, v_x, v_y, v_z]
+            },
+            Integrator::Verlet => {
+                let a = acceleration_3d(planets, params, [x, y, z]);
+                let position = [x + v_x * dt + 0.5 * a[0] * dt * dt, y + v_y * dt + 0.5 * a[1] * dt * dt, z + v_z * dt + 0.5 * a[2] * dt * dt];
+                let b = acceleration_3d(planets, params, position);
+                let [x, y, z] = position;
+                [x, y, z, v_x + 0.5 * (a[0] + b[0]) * dt, v_y + 0.5 * (a[1] + b[1]) * dt, v_z + 0.5 * (a[2] + b[2]) * dt]
+            },
+            Integrator::Rk4 => {
+                let stage = |state: [f64; 6]| {
+                    let [a_x, a_y, a_z] = acceleration_3d(planets, params, [state[0], state[1], state[2]]);
+                    [state[3], state[4], state[5], a_x, a_y, a_z]
+                };
+                let along = |k: [f64; 6], by: f64| {
+                    let mut moved = state;
+                    for (value, rate) in moved.iter_mut().zip(k) {
+                        *value += rate * by;
+                    }
+                    moved
+                };
+                let k1 = stage(state);
+                let k2 = stage(along(k1, dt / 2.0));
+                let k3 = stage(along(k2, dt / 2.0));
+                let k4 = stage(along(k3, dt));
+                let mut state = state;
+                for (i, value) in state.iter_mut().enumerate() {
+                    *value += (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]) * dt / 6.0;
+                }
+                state
+            },
+        }
+    }
+
+    // As advance_split, for bodies moving in three dimensions
+    pub fn advance_split_3d(self, planets: &[Planet], params: &Params, mut state: [f64; 6], dt: f64, substeps: usize) -> [f64; 6] {
+        let substep = dt / substeps as f64;
+        let shift = |state: &mut [f64; 6], by: f64| {
+            let a = acceleration_3d(planets, params, [state[0], state[1], state[2]]);
+            for (v, a) in state[3..].iter_mut().zip(a) {
+                *v += a * by;
+            }
+        };
+        let resync = self == Integrator::Euler && substeps > 1;
+        if resync {
+            shift(&mut state, (dt - substep) / 2.0);
+        }
+        for _ in 0..substeps {
+            state = self.advance_3d(planets, params, state, substep);
+        }
+        if resync {
+            shift(&mut state, (substep - dt) / 2.0);
+        }
+        state
+    }
+
+    // As advance, split into the given number of equal substeps
+    pub fn advance_split(self, planets: &[Planet], params: &Params, mut state: [f64; 4], dt: f64, substeps: usize) -> [f64; 4] {
+        let substep = dt / substeps as f64;
+
+        // Euler's velocity runs half a step ahead of its position, so it is shifted back to half a
+        // substep ahead for the substeps and forward again after; otherwise each change of step size
+        // leaks energy, and orbits shrink with every close pass
+        let shift = |state: &mut [f64; 4], by: f64| {
+            let (a_x, a_y) = acceleration(planets, params, state[0], state[1], state[2], state[3]);
+            state[2] += a_x * by;
+            state[3] += a_y * by;
+        };
+        let resync = self == Integrator::Euler && substeps > 1;
+        if resync {
+            shift(&mut state, (dt - substep) / 2.0);
+        }
+        for _ in 0..substeps {
+            state = self.advance(planets, params, state, substep);
+        }
+        if resync {
+            shift(&mut state, (substep - dt) / 2.0);
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Integrator;
+    use crate::simulation::{test_params, Simulation};
+
+    // Steps a satellite round a circular orbit about the only planet for a few periods, returning
+    // how far its radius and specific energy strayed, each relative to where they started
+    fn drift(integrator: Integrator, substeps: usize) -> (f64, f64) {
+        let Simulation { params, planets, .. } = Simulation::new(test_params(1), 1);
+        let (centre_x, centre_y) = (planets[0].x, planets[0].y);
+        let mu = params.gravity_constant * planets[0].mass;
+        let radius = 100.0;
+        let speed = (mu / radius).sqrt();
+        let energy = |state: [f64; 4]| {
+            0.5 * (state[2] * state[2] + state[3] * state[3]) - mu / (state[0] - centre_x).hypot(state[1] - centre_y)
+        };
+        let mut state = [centre_x + radius, centre_y, 0.0, speed];
+        let start = energy(state);
+        let (mut radius_drift, mut energy_drift) = (0.0_f64, 0.0_f64);
+        let dt = 1.0 / 120.0;
+        let period = std::f64::consts::TAU * radius / speed;
+        for _ in 0..(5.0 * period / dt) as usize {
+            state = integrator.advance_split(&planets, &params, state, dt, substeps);
+            radius_drift = radius_drift.max(((state[0] - centre_x).hypot(state[1] - centre_y) - radius).abs() / radius);
+            energy_drift = energy_drift.max(((energy(state) - start) / start).abs());
+        }
+        (radius_drift, energy_drift)
+    }
+
+    #[test]
+    fn circular_orbits_stay_circular() {
+        // Bounds on the radius and energy drift for each integrator, whole steps or split, with Euler's
+        // split steps resynced so they are no worse than whole ones
+        let bounds = [(Integrator::Euler, 2e-2, 1e-3), (Integrator::Verlet, 1e-3, 1e-6), (Integrator::Rk4, 1e-6, 1e-7)];
+        for &(integrator, radius_bound, energy_bound) in bounds.iter() {
+            for &substeps in &[1, 4] {
+                let (radius, energy) = drift(integrator, substeps);
+                assert!(radius < radius_bound, "radius drifted by {} with {} substeps", radius, substeps);
+                assert!(energy < energy_bound, "energy drifted by {} with {} substeps", energy, substeps);
+            }
+        }
+    }
+}
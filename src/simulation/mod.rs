@@ -0,0 +1,1716 @@
+pub mod anisotropy;
+pub mod asteroid;
+pub mod autosave;
+pub mod belt;
+pub mod collision;
+pub mod corotation;
+pub mod density;
+pub mod ecosystem;
+pub mod emitter;
+pub mod field;
+pub mod formation;
+pub mod integrator;
+pub mod moon;
+pub mod nbody;
+pub mod oblateness;
+pub mod palette;
+pub mod periodic;
+pub mod pool;
+pub mod portal;
+pub mod quota;
+pub mod region;
+pub mod repulsor;
+pub mod ripple;
+pub mod tree;
+pub mod wall;
+
+use std::collections::VecDeque;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::elements::{self, OrbitTrack};
+use crate::jacobi::RotatingFrame;
+use crate::events::{self, Event};
+use crate::stability::Stability;
+
+use anisotropy::Anisotropy;
+use asteroid::{Asteroid, Outline};
+use belt::Belt;
+use collision::Collisions;
+use corotation::CoRotation;
+use density::Density;
+use ecosystem::Ecosystem;
+use emitter::Emitter;
+use field::{Effect, Field};
+use formation::Formation;
+use integrator::Integrator;
+use moon::{Moon, Tether};
+use oblateness::Oblateness;
+use palette::Palette;
+use periodic::{Orbit, Rails};
+use pool::TrailPool;
+use portal::Portal;
+use quota::Quotas;
+use region::Region;
+use repulsor::Repulsor;
+use ripple::Ripple;
+use tree::Tree;
+use wall::{Wall, WallKind};
+
+pub struct Planet {
+    pub color: [f32; 4],
+    pub mass: f64,
+    pub radius: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,        // Height (in px) out of the plane of the screen, where the planets always move
+    pub v_x: f64,      // Velocity (in px/s), non-zero for planets that move: in a binary, or in orbits mode unless held still
+    pub v_y: f64,
+    pub accreted: f64, // Mass gained from crashed satellites since the planet formed or last broke up
+    pub atmosphere_height: f64, // Height (in px) above the surface the atmosphere reaches, or 0 for none
+    pub drag_coefficient: f64,  // Drag (per px) at the surface, thinning to nothing at the top of the atmosphere
+    pub oblateness: Option<Oblateness>, // Equatorial bulge and spin axis, if the planet is not a perfect sphere
+    pub star: bool,             // Whether the planet shines, its light pushing satellites away
+    pub outline: Option<Outline>, // Shape, if the planet is an asteroid rather than round
+    pub tether: Option<Tether>,   // Orbit about another body the planet is held to, if it is a moon
+}
+
+impl Planet {
+    // Whether the planet has negative mass, pushing satellites away rather than pulling them in
+    pub fn repulsor(&self) -> bool {
+        self.mass < 0.0
+    }
+
+    // Whether the planet is kept where it is put, as repulsors and asteroids are and moons on their
+    // orbits, rather than being pulled around
+    pub fn fixed(&self) -> bool {
+        self.repulsor() || self.outline.is_some() || self.tether.is_some()
+    }
+
+    // Whether a circle of the given radius at (x, y) in the plane of the planet overlaps it
+    pub fn touches(&self, x: f64, y: f64, radius: f64) -> bool {
+        match self.outline.as_ref() {
+            Some(outline) => outline.touches(x - self.x, y - self.y, radius),
+            None => (x - self.x).hypot(y - self.y) < radius + self.radius,
+        }
+    }
+}
+
+pub struct Satellite {
+    pub id: u64,
+    pub color: [f32; 4],
+    pub radius: f64,
+    pub mass: f64,                   // Mass pulling on the other satellites, in n-body mode
+    pub fate: Option<Fate>,
+    pub born: f64,                   // Simulation time the satellite was spawned
+    pub died: f64,                   // Simulation time the satellite died, once it has a fate
+    pub expires: f64,                // Simulation time the satellite dies of old age
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,                      // Height (in px) out of the plane of the screen, when bodies move in three dimensions
+    pub v_x: f64,
+    pub v_y: f64,
+    pub v_z: f64,
+    pub trail: VecDeque<(f64, f64)>,
+    pub heights: VecDeque<f64>,      // Height of each point of the trail, kept when bodies move in three dimensions
+    pub path: Vec<(f64, f64, f64)>,       // Full trail history with heights, only kept when exporting
+    pub keys: Vec<(f64, f64, f64, f64)>,  // Time-stamped position samples with heights, only kept when exporting
+    pub track: Option<OrbitTrack>,   // Smoothed orbit around the dominant planet, if bound to one
+    pub stability: Stability,        // Rolling record of the orbit, used to estimate its stability
+    pub last_planet: Option<usize>,  // Planet the satellite was most recently bound to
+    pub skimming: Option<usize>,     // Planet whose surface the satellite is currently skimming
+    pub rails: Option<Rails>,        // Precomputed orbit the satellite follows exactly, if injected on rails
+    pub origin: Origin,              // What brought the satellite into being
+    pub jumped: f64,                 // Simulation time the satellite last jumped through a portal
+    pub generation: u32,             // Times the satellite has been split, counting from whatever it split from
+    pub thrust: (f64, f64),          // Acceleration (in px/s^2) from the satellite's engine while it is firing
+    pub burn_until: f64,             // Simulation time the engine stops firing
+}
+
+// What brought a satellite into being
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    Random,         // Spawned at random, including to top up a formation
+    Emitter(usize), // Launched by the emitter with the given index
+    User,           // Launched by hand
+    Script,         // Launched by a timeline cue or injected from the command line
+    Breakup,        // Thrown off a planet breaking up
+    Belt,           // Part of a belt from the scene
+    Fragment,       // Split off another satellite
+}
+
+impl Origin {
+    // Names of the kinds of origin, as used by --show_origin
+    pub const NAMES: [&'static str; 7] = ["random", "emitter", "user", "script", "breakup", "belt", "fragment"];
+
+    pub fn name(&self) -> &'static str {
+        Origin::NAMES[self.index()]
+    }
+
+    // Position of the kind of origin in NAMES
+    pub fn index(&self) -> usize {
+        match self {
+            Origin::Random => 0,
+            Origin::Emitter(_) => 1,
+            Origin::User => 2,
+            Origin::Script => 3,
+            Origin::Breakup => 4,
+            Origin::Belt => 5,
+            Origin::Fragment => 6,
+        }
+    }
+}
+
+// How a satellite died
+#[derive(Clone, Copy, PartialEq)]
+pub enum Fate {
+    Crashed(usize), // Hit the planet with the given index
+    Escaped,        // Left the screen with enough energy never to come back
+    Lost,           // Left the screen while still bound to the planets
+    Expired,        // Died of old age
+    Walled,         // Ran into a wall that absorbs satellites
+    Absorbed,       // Merged into a heavier satellite it ran into
+}
+
+// Speed of light (in px/s) for the post-Newtonian correction, slow enough that tight orbits precess
+// by a few degrees each time round
+const LIGHT_SPEED: f64 = 3000.0;
+
+// Speed (in px/s) a point moving at (v_x, v_y) loses towards a planet over `dt` seconds, as x and y
+// parts. With relativity, the pull is strengthened by the first-order post-Newtonian term
+// 3 h^2 / (c^2 r^2), where h is the point's angular momentum about the planet per unit mass, which
+// turns orbits slowly round the way Mercury's does.
+fn planet_pull(planet: &Planet, params: &Params, x: f64, y: f64, v_x: f64, v_y: f64, dt: f64) -> (f64, f64) {
+    // Asteroids pull from the point masses filling their shape, without the corrections for round planets
+    if let Some(outline) = planet.outline.as_ref() {
+        let [a_x, a_y, _] = outline.fall(params.gravity_constant * planet.mass, [x - planet.x, y - planet.y, 0.0], params.epsilon);
+        return params.anisotropy.map_or((a_x * dt, a_y * dt), |anisotropy| anisotropy.apply(a_x * dt, a_y * dt));
+    }
+    let distance_x = x - planet.x;
+    let distance_y = y - planet.y;
+    let distance_sq = (distance_x * distance_x) + (distance_y * distance_y) + (params.epsilon * params.epsilon);
+    let mut delta_velocity = (params.gravity_constant * planet.mass * dt) / (distance_sq);
+    if params.relativity {
+        let h = distance_x * (v_y - planet.v_y) - distance_y * (v_x - planet.v_x);
+        let r_sq = (distance_x * distance_x) + (distance_y * distance_y);
+        delta_velocity *= 1.0 + 3.0 * h * h / (LIGHT_SPEED * LIGHT_SPEED * r_sq);
+    }
+    let angle = distance_y.atan2(distance_x);
+    let (mut dv_x, mut dv_y) = (delta_velocity * angle.cos(), delta_velocity * angle.sin());
+    if let Some(oblateness) = planet.oblateness {
+        let (a_x, a_y) = oblateness.acceleration(distance_x, distance_y, params.gravity_constant * planet.mass, planet.radius);
+        dv_x -= a_x * dt;
+        dv_y -= a_y * dt;
+    }
+    params.anisotropy.map_or((dv_x, dv_y), |anisotropy| anisotropy.apply(dv_x, dv_y))
+}
+
+// Velocity after being pulled for `dt` seconds by the planets, and downwards in projectile mode
+fn pull(planets: &[Planet], params: &Params, x: f64, y: f64, mut v_x: f64, mut v_y: f64, dt: f64) -> (f64, f64) {
+    let (u_x, u_y) = (v_x, v_y);
+    for planet in planets.iter() {
+        let (dv_x, dv_y) = planet_pull(planet, params, x, y, u_x, u_y, dt);
+        v_x -= dv_x;
+        v_y -= dv_y;
+    }
+    if params.mode == Mode::Projectile {
+        v_y += params.gravity * dt;
+    }
+    (v_x, v_y)
+}
+
+// Acceleration (in px/s^2) of a point at (x, y) moving at (v_x, v_y), which is the pull over one
+// second on a point starting from rest but for the velocity the correction for relativity needs
+fn acceleration(planets: &[Planet], params: &Params, x: f64, y: f64, v_x: f64, v_y: f64) -> (f64, f64) {
+    let (mut a_x, mut a_y) = (0.0, 0.0);
+    for planet in planets.iter() {
+        let (dv_x, dv_y) = planet_pull(planet, params, x, y, v_x, v_y, 1.0);
+        a_x -= dv_x;
+        a_y -= dv_y;
+    }
+    if params.mode == Mode::Projectile {
+        a_y += params.gravity;
+    }
+    (a_x, a_y)
+}
+
+// Velocity after `dt` seconds of drag from the atmospheres of any planets the point is inside. Drag
+// grows with the square of the speed through the air, which moves along with its planet, and the air
+// thins linearly from the surface to nothing at the top of the atmosphere. The speed is cut by the
+// implicit factor 1 / (1 + k dt) so that even a steep dive cannot reverse it.
+fn drag(planets: &[Planet], x: f64, y: f64, mut v_x: f64, mut v_y: f64, dt: f64) -> (f64, f64) {
+    for planet in planets.iter().filter(|planet| planet.atmosphere_height > 0.0 && planet.drag_coefficient > 0.0) {
+        let altitude = (x - planet.x).hypot(y - planet.y) - planet.radius;
+        if altitude >= planet.atmosphere_height {
+            continue;
+        }
+        let density = (1.0 - altitude / planet.atmosphere_height).min(1.0);
+        let (air_x, air_y) = (v_x - planet.v_x, v_y - planet.v_y);
+        let keep = 1.0 / (1.0 + planet.drag_coefficient * density * air_x.hypot(air_y) * dt);
+        v_x = planet.v_x + air_x * keep;
+        v_y = planet.v_y + air_y * keep;
+    }
+    (v_x, v_y)
+}
+
+// Acceleration from the light of any stars pushing on a satellite of the given radius. Like gravity
+// it falls off as 1 / r^2, so it is measured against the star's pull: `radiation` is the ratio of the
+// two for a satellite of the usual radius `sat_radius`, and the ratio grows as satellites shrink, as
+// their area falls more slowly than their mass. Light debris with a ratio over 1 is blown right out of
+// the system, streaming away from the star like a comet's tail.
+fn radiation(planets: &[Planet], params: &Params, x: f64, y: f64, radius: f64) -> (f64, f64) {
+    let beta = params.radiation * params.sat_radius / radius.max(f64::EPSILON);
+    let (mut a_x, mut a_y) = (0.0, 0.0);
+    for planet in planets.iter().filter(|planet| planet.star) {
+        let (d_x, d_y) = (x - planet.x, y - planet.y);
+        let r_sq = d_x * d_x + d_y * d_y;
+        let push = beta * params.gravity_constant * planet.mass / (r_sq * r_sq.sqrt());
+        a_x += push * d_x;
+        a_y += push * d_y;
+    }
+    (a_x, a_y)
+}
+
+// Times a satellite can be split, counting the splits of whatever it split from, so that splitting
+// over and over cannot swamp the simulation
+pub const FRAGMENT_GENERATIONS: u32 = 3;
+
+// Fastest kick (in px/s) a fragment gets away from the satellite it split from
+const FRAGMENT_KICK: f64 = 40.0;
+
+// Smallest radius (in px) a fragment is given, however many pieces it split into
+const FRAGMENT_MIN_RADIUS: f64 = 1.0;
+
+// Pieces a satellite is torn into inside a planet's Roche limit, and the fastest kick (in px/s) they
+// get away from each other, gentle enough that they mostly spread along the orbit
+const ROCHE_PIECES: usize = 4;
+const ROCHE_KICK: f64 = 10.0;
+
+// Mass and radius of each planet when created
+pub const PLANET_MASS: f64 = 1000.0;
+pub const PLANET_RADIUS: f64 = 25.0;
+
+// What pulls satellites around
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Orbits,     // Planets, each pulling towards its centre, and on each other unless held still
+    Projectile, // A flat field pulling towards the bottom of the world, with no planets
+    Binary,     // Two planets in a circular orbit about each other
+}
+
+// What happens to satellites that leave the world
+#[derive(Clone, Copy, PartialEq)]
+pub enum Boundary {
+    Kill,                        // They are gone, as escaped or lost
+    Wrap,                        // They come back in at the opposite edge, as on a torus
+    Bounce { restitution: f64 }, // They bounce off the edges, keeping this fraction of their speed into them
+}
+
+// Parameters of the physics and spawning
+pub struct Params {
+    pub width: f64,               // Width of the world
+    pub height: f64,              // Height of the world
+    pub add_chance: f64,          // Chance to add a satellite each frame
+    pub sat_radius: f64,          // Radius (in px) of each satellite
+    pub sat_velocity: f64,        // Initial velocity (in px/s) of each satellite
+    pub gravity_constant: f64,    // 'G' constant used to update velocities
+    pub trail_length: usize,      // Trail length, measured in number of frames of history
+    pub num_planets: usize,       // Number of planets to create on (re)set
+    pub stability_orbits: f64,    // Number of orbits over which stability is estimated
+    pub record_paths: bool,       // Whether to keep each satellite's full trail history
+    pub exclusions: Vec<Region>,  // Regions satellites are never spawned in
+    pub repulsors: Vec<Repulsor>, // Bodies of negative mass added after the planets
+    pub asteroids: Vec<Asteroid>, // Bodies shaped as polygons added after the repulsors
+    pub moons: Vec<Moon>,         // Bodies going round others on rails, added after the asteroids
+    pub ripples: bool,            // Whether merges send out ripples
+    pub ripple_impulse: f64,      // Outward kick (in px/s) ripples give satellites they pass over
+    pub mode: Mode,               // What pulls satellites around
+    pub gravity: f64,             // Downward acceleration (in px/s^2) in projectile mode
+    pub binary_ratio: f64,        // Mass of the second planet relative to the first in binary mode
+    pub palette: Option<Palette>, // Hues satellites are coloured from, rather than any colour
+    pub integrator: Integrator,   // How satellites are moved on under gravity each step
+    pub max_substeps: usize,      // Most substeps a satellite's step is split into where the pull is strong, or 1 never to split
+    pub sat_mass: f64,            // Mass of each satellite, pulling on the others if non-zero
+    pub tug_planets: bool,        // Whether satellites with mass pull the planets around too, in orbits mode
+    pub planet_gravity: bool,     // Whether planets in orbits mode pull on each other and move, rather than staying put
+    pub theta: f64,               // Barnes-Hut opening angle for the pulls between satellites, or 0 to sum every pair exactly
+    pub epsilon: f64,             // Softening length (in px) added to the distance in the planets' pull, or 0 for none
+    pub collisions: Collisions,   // What happens when bodies run into each other
+    pub boundary: Boundary,       // What happens to satellites that leave the world
+    pub atmosphere_height: f64,   // Height (in px) of every planet's atmosphere, or 0 for none
+    pub drag_coefficient: f64,    // Drag (per px) at the bottom of every planet's atmosphere
+    pub anisotropy: Option<Anisotropy>, // Stretching and shearing of every pull between bodies, if any
+    pub relativity: bool,         // Whether the planets' pull has the post-Newtonian correction that makes orbits precess
+    pub oblateness: Option<Oblateness>, // Equatorial bulge and spin axis of every planet, if any
+    pub roche: f64,               // Roche limit, in planet radii from a planet's centre, inside which satellites are torn apart, or 0 for none
+    pub star: Option<usize>,      // Index of the planet that shines as a star, if any
+    pub radiation: f64,           // Push of the star's light against its pull on a satellite of the usual radius
+    pub spawn_bias: Option<f64>,  // Seconds a random satellite must be predicted to last, if spawns are biased towards stable starts
+    pub inclination: Option<f64>, // Steepest tilt (in radians) of random satellites' orbits out of the plane, if bodies move in three dimensions
+    pub max_speed: Option<f64>,   // Fastest (in px/s) satellites may go, if they are held to a speed limit
+    pub world_scale: f64,         // Size of the region satellites are killed outside of, relative to the world, at least 1
+}
+
+// Spawn positions tried before giving up on spawning for a step
+const SPAWN_ATTEMPTS: usize = 100;
+
+// Starts tried for each random satellite when spawns are biased towards stable ones, and the step
+// (in seconds) of the throwaway integration that predicts whether each lasts
+const BIAS_ATTEMPTS: usize = 8;
+const BIAS_DT: f64 = 1.0 / 20.0;
+
+// Left, top, right and bottom edges of the region satellites are killed outside of: the world,
+// grown about its centre by the world scale so that satellites can swing out of view and back
+fn kill_region(params: &Params) -> [f64; 4] {
+    let margin_x = params.width * (params.world_scale - 1.0) / 2.0;
+    let margin_y = params.height * (params.world_scale - 1.0) / 2.0;
+    [-margin_x, -margin_y, params.width + margin_x, params.height + margin_y]
+}
+
+// Returns true if the point with given radius is outside the given region
+fn outside(x: f64, y: f64, radius: f64, [left, top, right, bottom]: [f64; 4]) -> bool {
+    (x + radius < left)
+    | (y + radius < top)
+    | (x - radius > right)
+    | (y - radius > bottom)
+}
+
+// Returns true if the point with given radius has fallen out of the bottom or sides of the given
+// region. Satellites thrown above the top are left to fall back down.
+fn fallen(x: f64, y: f64, radius: f64, [left, _, right, bottom]: [f64; 4]) -> bool {
+    (x + radius < left)
+    | (x - radius > right)
+    | (y - radius > bottom)
+}
+
+// Kinetic plus potential energy per unit mass of a satellite in the planets' gravity, negative if it
+// is bound to them
+pub fn specific_energy(planets: &[Planet], params: &Params, sat: &Satellite) -> f64 {
+    let potential: f64 = planets.iter()
+        .map(|planet| {
+            let distance = ((sat.x - planet.x).powi(2) + (sat.y - planet.y).powi(2) + (sat.z - planet.z).powi(2) + params.epsilon * params.epsilon).sqrt();
+            -params.gravity_constant * planet.mass / distance
+        })
+        .sum();
+    0.5 * (sat.v_x * sat.v_x + sat.v_y * sat.v_y + sat.v_z * sat.v_z) + potential
+}
+
+// Whether a satellite is bound to the planets, so would come back if it left the screen. Nothing is
+// bound in projectile mode, where everything falls out of the bottom in the end.
+pub fn bound(planets: &[Planet], params: &Params, sat: &Satellite) -> bool {
+    params.mode != Mode::Projectile && specific_energy(planets, params, sat) < 0.0
+}
+
+// Brings a point that has left the world back in at the opposite edge, returning whether it was
+// moved. In projectile mode only the sides wrap, so that satellites still fall out of the bottom
+// rather than falling for ever.
+fn wrap(x: &mut f64, y: &mut f64, params: &Params) -> bool {
+    let (old_x, old_y) = (*x, *y);
+    *x = x.rem_euclid(params.width);
+    if params.mode != Mode::Projectile {
+        *y = y.rem_euclid(params.height);
+    }
+    *x != old_x || *y != old_y
+}
+
+// Slows a body going faster than `max_speed` (in px/s) down to it, keeping its direction
+fn cap_speed(v_x: &mut f64, v_y: &mut f64, v_z: &mut f64, max_speed: f64) {
+    let speed = (*v_x * *v_x + *v_y * *v_y + *v_z * *v_z).sqrt();
+    if speed > max_speed {
+        let scale = max_speed / speed;
+        *v_x *= scale;
+        *v_y *= scale;
+        *v_z *= scale;
+    }
+}
+
+// Bounces a body of the given radius that has run into an edge of the world back off it, keeping
+// `restitution` of its speed into the edge
+fn bounce_off_edges(x: &mut f64, y: &mut f64, v_x: &mut f64, v_y: &mut f64, radius: f64, params: &Params, restitution: f64) {
+    if *x < radius {
+        *x = radius;
+        *v_x = v_x.abs() * restitution;
+    } else if *x > params.width - radius {
+        *x = params.width - radius;
+        *v_x = -v_x.abs() * restitution;
+    }
+    if *y < radius {
+        *y = radius;
+        *v_y = v_y.abs() * restitution;
+    } else if *y > params.height - radius {
+        *y = params.height - radius;
+        *v_y = -v_y.abs() * restitution;
+    }
+}
+
+// Returns a random color
+pub fn random_color<R: Rng>(rng: &mut R) -> [f32; 4] {
+    [rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0), 1.0]
+}
+
+// Creates the initial planet layout for the given number of planets and window size
+fn create_planets<R: Rng>(num_planets: usize, width: f64, height: f64, rng: &mut R) -> Vec<Planet> {
+    let mut planets: Vec<Planet> = Vec::new();
+    let radius: f64 = width.min(height) / 4.0;
+    match num_planets {
+        0 => panic!("Num_planets must be greater than 0"),
+        1 => {
+            planets.push(Planet {
+                color: random_color(rng),
+                mass: PLANET_MASS,
+                radius: PLANET_RADIUS,
+                x: width / 2.0,
+                y: height / 2.0,
+                z: 0.0,
+                v_x: 0.0,
+                v_y: 0.0,
+                accreted: 0.0,
+                atmosphere_height: 0.0,
+                drag_coefficient: 0.0,
+                oblateness: None,
+                star: false,
+                outline: None,
+                tether: None,
+            });
+        },
+        2 => {
+            planets.push(Planet {
+                color: random_color(rng),
+                mass: PLANET_MASS,
+                radius: PLANET_RADIUS,
+                x: width / 2.0 - radius,
+                y: height / 2.0,
+                z: 0.0,
+                v_x: 0.0,
+                v_y: 0.0,
+                accreted: 0.0,
+                atmosphere_height: 0.0,
+                drag_coefficient: 0.0,
+                oblateness: None,
+                star: false,
+                outline: None,
+                tether: None,
+            });
+            planets.push(Planet {
+                color: random_color(rng),
+                mass: PLANET_MASS,
+                radius: PLANET_RADIUS,
+                x: width / 2.0 + radius,
+                y: height / 2.0,
+                z: 0.0,
+                v_x: 0.0,
+                v_y: 0.0,
+                accreted: 0.0,
+                atmosphere_height: 0.0,
+                drag_coefficient: 0.0,
+                oblateness: None,
+                star: false,
+                outline: None,
+                tether: None,
+            });
+        },
+        n => {
+            for i in 0..n {
+                let theta = (std::f64::consts::PI * 2.0 * i as f64 / n as f64) - std::f64::consts::FRAC_PI_2;
+                let x = width / 2.0 + (theta.cos() * radius);
+                let y = height / 2.0 + (theta.sin() * radius);
+                planets.push(Planet {
+                    color: random_color(rng),
+                    mass: PLANET_MASS,
+                    radius: PLANET_RADIUS,
+                    x,
+                    y,
+                    z: 0.0,
+                    v_x: 0.0,
+                    v_y: 0.0,
+                    accreted: 0.0,
+                    atmosphere_height: 0.0,
+                    drag_coefficient: 0.0,
+                    oblateness: None,
+                    star: false,
+                    outline: None,
+                    tether: None,
+                });
+            }
+        },
+    }
+    planets
+}
+
+// Creates the planets for a fresh start in the given mode
+fn initial_planets<R: Rng>(params: &Params, rng: &mut R) -> Vec<Planet> {
+    let mut planets = match params.mode {
+        Mode::Orbits => {
+            let mut planets = create_planets(params.num_planets, params.width, params.height, rng);
+            if params.planet_gravity {
+                nbody::circular_velocities(&mut planets, params.width / 2.0, params.height / 2.0, params.gravity_constant);
+            }
+            planets
+        },
+        Mode::Projectile => Vec::new(),
+        Mode::Binary => binary_planets(params, rng),
+    };
+    for planet in planets.iter_mut() {
+        planet.atmosphere_height = params.atmosphere_height;
+        planet.drag_coefficient = params.drag_coefficient;
+        planet.oblateness = params.oblateness;
+    }
+    if let Some(star) = params.star.and_then(|i| planets.get_mut(i)) {
+        star.star = true;
+    }
+    planets.extend(params.repulsors.iter().map(|repulsor| repulsor.planet()));
+    planets.extend(params.asteroids.iter().map(|asteroid| asteroid.planet()));
+    for moon in params.moons.iter() {
+        let planet = moon.planet(&planets[moon.parent]);
+        planets.push(planet);
+    }
+    planets
+}
+
+// Two planets in a circular orbit about their barycentre at the centre of the world, the second
+// `binary_ratio` times as massive as the first and sized to match
+fn binary_planets<R: Rng>(params: &Params, rng: &mut R) -> Vec<Planet> {
+    let mut planets = create_planets(2, params.width, params.height, rng);
+    let ratio = params.binary_ratio;
+    let separation = planets[1].x - planets[0].x;
+    planets[1].mass = PLANET_MASS * ratio;
+    planets[1].radius = PLANET_RADIUS * ratio.cbrt();
+    planets[0].x = params.width / 2.0 - separation * ratio / (1.0 + ratio);
+    planets[1].x = params.width / 2.0 + separation / (1.0 + ratio);
+    let omega = (params.gravity_constant * PLANET_MASS * (1.0 + ratio) / separation.powi(3)).sqrt();
+    for planet in planets.iter_mut() {
+        planet.v_y = omega * (planet.x - params.width / 2.0);
+    }
+    planets
+}
+
+// The simulated planets and satellites, advanced in fixed steps. Everything random is drawn
+// from a seeded generator so that a run is fully determined by its seed and parameters.
+pub struct Simulation {
+    pub seed: u64,                    // Seed the random number generator started from
+    pub rng: ChaCha8Rng,              // Random number generator
+    pub planets: Vec<Planet>,         // Data for planets
+    pub satellites: Vec<Satellite>,   // Data for satellites
+    pub next_id: u64,                 // Id given to the next satellite
+    pub time: f64,                    // Simulation time elapsed in seconds
+    pub steps: u64,                   // Number of steps taken
+    pub dt: f64,                      // Length of the last step in seconds
+    pub co_rotation: CoRotation,      // Frame turning with the planets, when there are two of them
+    pub events: Vec<Event>,           // Events detected during the last step
+    pub removed: Vec<u64>,            // Ids of satellites taken out during the last step for going to infinity or NaN
+    pub formation: Option<Formation>, // Shape satellites are periodically steered into
+    pub ecosystem: Option<Ecosystem>, // Slow evolution of the whole scene
+    pub density: Option<Density>,     // Crowding of live satellites, if spawning is balanced by it
+    pub ripples: Vec<Ripple>,         // Ripples still spreading from recent merges
+    pub emitters: Vec<Emitter>,       // Fixed launchers of satellites
+    pub belts: Vec<Belt>,             // Rings of bodies filled in at the start and on every reset
+    pub walls: Vec<Wall>,             // Static walls satellites bounce off or die on
+    pub fields: Vec<Field>,           // Areas with physics of their own
+    pub portals: Vec<Portal>,         // Linked pairs of mouths satellites jump between
+    pub spawning: bool,               // Whether new satellites appear by themselves, from spawns, emitters and formations
+    pub quotas: Quotas,               // Satellites brought in from each origin, and caps on how many may be alive
+    pub trails: TrailPool,            // Trail buffers of removed satellites, to reuse for new ones
+    pub params: Params,
+}
+
+impl Simulation {
+    pub fn new(params: Params, seed: u64) -> Simulation {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let planets = initial_planets(&params, &mut rng);
+        let mut co_rotation = CoRotation::default();
+        co_rotation.record(&planets, params.trail_length + 1);
+        Simulation {
+            seed,
+            rng,
+            planets,
+            satellites: Vec::new(),
+            next_id: 0,
+            time: 0.0,
+            steps: 0,
+            dt: 0.0,
+            co_rotation,
+            events: Vec::new(),
+            removed: Vec::new(),
+            formation: None,
+            ecosystem: None,
+            density: None,
+            ripples: Vec::new(),
+            emitters: Vec::new(),
+            belts: Vec::new(),
+            walls: Vec::new(),
+            fields: Vec::new(),
+            portals: Vec::new(),
+            spawning: true,
+            quotas: Quotas::default(),
+            trails: TrailPool::default(),
+            params,
+        }
+    }
+
+    // Clears all satellites and creates a fresh set of planets
+    pub fn reset(&mut self) {
+        self.planets = initial_planets(&self.params, &mut self.rng);
+        for sat in self.satellites.drain(..) {
+            self.trails.give(sat.trail);
+        }
+        self.ripples.clear();
+        self.co_rotation.clear();
+        self.co_rotation.record(&self.planets, self.params.trail_length + 1);
+        self.fill_belts();
+    }
+
+    // Fills every belt with fresh bodies
+    pub fn fill_belts(&mut self) {
+        for i in 0..self.belts.len() {
+            let color = self.belts[i].color;
+            for (x, y, v_x, v_y) in self.belts[i].bodies(&self.planets, self.params.gravity_constant, &mut self.rng) {
+                self.add_satellite(color, x, y, v_x, v_y, Origin::Belt);
+            }
+        }
+    }
+
+    // Picks a random point outside all exclusion zones by rejection sampling, giving up after a
+    // fixed number of attempts so a scene that excludes (almost) everything cannot stall a step.
+    // When balancing by density, points in crowded regions are also often rejected, which shifts
+    // new satellites towards emptier ones.
+    fn spawn_position(&mut self) -> Option<(f64, f64)> {
+        for _ in 0..SPAWN_ATTEMPTS {
+            let x: f64 = self.rng.gen_range(0.0..self.params.width);
+            let y: f64 = self.rng.gen_range(0.0..self.params.height);
+            if self.params.exclusions.iter().any(|region| region.contains(x, y, &self.planets)) {
+                continue;
+            }
+            // Satellites spawned inside a planet simply crash, but inside a repulsor they would be
+            // flung out from its centre
+            if self.planets.iter().any(|planet| planet.repulsor() && (x - planet.x).hypot(y - planet.y) < planet.radius + self.params.sat_radius) {
+                continue;
+            }
+            if let Some(density) = self.density.as_ref() {
+                if self.rng.gen_range(0.0..1.0) >= density.acceptance(x, y) {
+                    continue;
+                }
+            }
+            return Some((x, y));
+        }
+        None
+    }
+
+    // Adds a new satellite at a random position and heading. When spawns are biased towards stable
+    // starts, a few are tried and the first predicted to last is kept; if none are, the last one
+    // tried is spawned anyway, so the spawn rate and spread are much the same either way.
+    fn spawn(&mut self) {
+        let color: [f32; 4] = self.sat_color();
+        let attempts = if self.params.spawn_bias.is_some() { BIAS_ATTEMPTS } else { 1 };
+        let mut start = None;
+        for _ in 0..attempts {
+            let (x, y) = match self.spawn_position() {
+                Some(position) => position,
+                None => break,
+            };
+            let angle: f64 = self.rng.gen_range(0.0..2.0 * std::f64::consts::PI);
+            let v_x: f64 = self.params.sat_velocity * angle.cos();
+            let v_y: f64 = self.params.sat_velocity * angle.sin();
+            start = Some((x, y, v_x, v_y));
+            if self.lasts(x, y, v_x, v_y) {
+                break;
+            }
+        }
+        if let Some((x, y, v_x, v_y)) = start {
+            // In three dimensions, tip the velocity out of the plane so the orbit is inclined
+            let (v_x, v_y, v_z) = match self.params.inclination {
+                Some(steepest) if steepest > 0.0 => {
+                    let (sin, cos) = self.rng.gen_range(-steepest..steepest).sin_cos();
+                    (v_x * cos, v_y * cos, self.params.sat_velocity * sin)
+                },
+                _ => (v_x, v_y, 0.0),
+            };
+            if self.add_satellite(color, x, y, v_x, v_y, Origin::Random).is_some() {
+                if let Some(sat) = self.satellites.last_mut() {
+                    sat.v_z = v_z;
+                }
+            }
+        }
+    }
+
+    // Whether a satellite starting here is predicted to last the spawn bias's horizon without crashing
+    // or escaping, or always if spawns are not biased
+    fn lasts(&self, x: f64, y: f64, v_x: f64, v_y: f64) -> bool {
+        match self.params.spawn_bias {
+            Some(horizon) => {
+                let steps = (horizon / BIAS_DT).ceil() as usize;
+                self.predict(x, y, v_x, v_y, steps, BIAS_DT).len() > steps
+            },
+            None => true,
+        }
+    }
+
+    // Colour for a new satellite, from the palette if there is one
+    pub fn sat_color(&mut self) -> [f32; 4] {
+        match self.params.palette {
+            Some(palette) => palette.color(&mut self.rng),
+            None => random_color(&mut self.rng),
+        }
+    }
+
+    // Throws satellites off in every direction from a planet's surface
+    fn burst(&mut self, planet: usize, count: usize) {
+        let (planet_x, planet_y, radius) = (self.planets[planet].x, self.planets[planet].y, self.planets[planet].radius);
+        for _ in 0..count {
+            let color: [f32; 4] = self.sat_color();
+            let angle: f64 = self.rng.gen_range(0.0..2.0 * std::f64::consts::PI);
+            let distance = radius + self.params.sat_radius * 2.0;
+            let speed = self.params.sat_velocity * self.rng.gen_range(1.0..2.0);
+            self.add_satellite(color, planet_x + distance * angle.cos(), planet_y + distance * angle.sin(), speed * angle.cos(), speed * angle.sin(), Origin::Breakup);
+        }
+    }
+
+    // Adds a satellite unless its origin is over quota, returning its id if it was added
+    pub fn add_satellite(&mut self, color: [f32; 4], x: f64, y: f64, v_x: f64, v_y: f64, origin: Origin) -> Option<u64> {
+        if !self.quotas.admit(origin, &self.satellites) {
+            return None;
+        }
+        let sat = self.new_satellite(color, x, y, v_x, v_y, origin);
+        self.satellites.push(sat);
+        Some(self.next_id - 1)
+    }
+
+    // A new satellite with the next id, not yet added or counted against its quota
+    fn new_satellite(&mut self, color: [f32; 4], x: f64, y: f64, v_x: f64, v_y: f64, origin: Origin) -> Satellite {
+        let expires = match self.ecosystem.as_ref() {
+            Some(ecosystem) => ecosystem.expiry(self.time, &mut self.rng),
+            None => f64::INFINITY,
+        };
+        let sat = Satellite {
+            id: self.next_id,
+            color,
+            radius: self.params.sat_radius,
+            mass: self.params.sat_mass,
+            fate: None,
+            born: self.time,
+            died: 0.0,
+            expires,
+            x,
+            y,
+            z: 0.0,
+            v_x,
+            v_y,
+            v_z: 0.0,
+            // Belts have too many bodies to keep trails for
+            trail: match origin {
+                Origin::Belt => VecDeque::new(),
+                _ => self.trails.take(self.params.trail_length),
+            },
+            heights: VecDeque::new(),
+            path: Vec::new(),
+            keys: Vec::new(),
+            track: None,
+            stability: Stability::default(),
+            last_planet: None,
+            skimming: None,
+            rails: None,
+            origin,
+            jumped: f64::NEG_INFINITY,
+            generation: 0,
+            thrust: (0.0, 0.0),
+            burn_until: f64::NEG_INFINITY,
+        };
+        self.next_id += 1;
+        sat
+    }
+
+    // Adds a satellite on a periodic orbit, either left to the simulation's physics like any other
+    // or fixed to the orbit as precomputed
+    pub fn inject(&mut self, orbit: Orbit, on_rails: bool) -> Result<(), String> {
+        let rails = if on_rails {
+            Some(Rails::new(&orbit, &self.planets, self.params.gravity_constant, self.time)?)
+        } else {
+            None
+        };
+        let color: [f32; 4] = self.sat_color();
+        if self.add_satellite(color, orbit.x, orbit.y, orbit.v_x, orbit.v_y, Origin::Script).is_none() {
+            return Err("Too many scripted satellites for their quota".to_string());
+        }
+        if let Some(sat) = self.satellites.last_mut() {
+            sat.rails = rails;
+        }
+        Ok(())
+    }
+
+    // Adds a satellite launched by hand or by a script, returning its id unless its origin is over quota
+    pub fn launch(&mut self, x: f64, y: f64, v_x: f64, v_y: f64, origin: Origin) -> Option<u64> {
+        let color: [f32; 4] = self.sat_color();
+        self.add_satellite(color, x, y, v_x, v_y, origin)
+    }
+
+    // Fires the engine of the live satellite with the given id, accelerating it by (a_x, a_y) px/s^2
+    // for `duration` seconds on top of gravity, or until fired again. Returns false if there is no
+    // such satellite.
+    pub fn thrust(&mut self, id: u64, (a_x, a_y): (f64, f64), duration: f64) -> bool {
+        let time = self.time;
+        match self.satellites.iter_mut().find(|sat| sat.id == id && sat.fate.is_none()) {
+            Some(sat) => {
+                sat.thrust = (a_x, a_y);
+                sat.burn_until = time + duration;
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Firework: splits every live satellite into `count` pieces flying apart with small random kicks
+    // that cancel out, so the pieces carry on together at the speed the satellite had. Each piece
+    // takes its share of the satellite's area and mass, and the satellite itself carries on as one of them.
+    // Belt bodies, satellites on rails and those already split FRAGMENT_GENERATIONS times are left
+    // whole, and splitting stops once the fragment quota is full. Returns how many pieces were added.
+    pub fn split(&mut self, count: usize) -> usize {
+        if count < 2 {
+            return 0;
+        }
+        let parents: Vec<usize> = (0..self.satellites.len())
+            .filter(|i| {
+                let sat = &self.satellites[*i];
+                sat.fate.is_none() && sat.origin != Origin::Belt && sat.rails.is_none() && sat.generation < FRAGMENT_GENERATIONS
+            })
+            .collect();
+        let mut room = self.quotas.admit_many(Origin::Fragment, parents.len() * (count - 1), &self.satellites);
+        let mut fragments: Vec<Satellite> = Vec::with_capacity(room);
+        for i in parents {
+            let pieces = count.min(room + 1);
+            if pieces < 2 {
+                break;
+            }
+            room -= pieces - 1;
+            fragments.extend(self.fragment(i, pieces, FRAGMENT_KICK));
+        }
+        let added = fragments.len();
+        self.satellites.extend(fragments);
+        added
+    }
+
+    // Splits the satellite at index `i` into `pieces`, kicked apart at up to `kick` px/s in random
+    // directions with the kicks cancelling out. The satellite carries on as the first piece, and the
+    // others are returned to be added.
+    fn fragment(&mut self, i: usize, pieces: usize, kick: f64) -> Vec<Satellite> {
+        let mut kicks: Vec<(f64, f64)> = (0..pieces)
+            .map(|_| {
+                let angle: f64 = self.rng.gen_range(0.0..2.0 * std::f64::consts::PI);
+                let speed: f64 = self.rng.gen_range(0.0..kick);
+                (speed * angle.cos(), speed * angle.sin())
+            })
+            .collect();
+        let mean = kicks.iter().fold((0.0, 0.0), |sum, kick| (sum.0 + kick.0, sum.1 + kick.1));
+        let mean = (mean.0 / pieces as f64, mean.1 / pieces as f64);
+        for kick in kicks.iter_mut() {
+            kick.0 -= mean.0;
+            kick.1 -= mean.1;
+        }
+
+        let (color, x, y, v_x, v_y) = {
+            let sat = &self.satellites[i];
+            (sat.color, sat.x, sat.y, sat.v_x, sat.v_y)
+        };
+        let (z, v_z) = (self.satellites[i].z, self.satellites[i].v_z);
+        let radius = (self.satellites[i].radius / (pieces as f64).sqrt()).max(FRAGMENT_MIN_RADIUS);
+        let mass = self.satellites[i].mass / pieces as f64;
+        let generation = self.satellites[i].generation + 1;
+        let mut fragments = Vec::with_capacity(pieces - 1);
+        for kick in kicks[1..].iter() {
+            let mut fragment = self.new_satellite(color, x, y, v_x + kick.0, v_y + kick.1, Origin::Fragment);
+            fragment.z = z;
+            fragment.v_z = v_z;
+            fragment.radius = radius;
+            fragment.mass = mass;
+            fragment.generation = generation;
+            fragments.push(fragment);
+        }
+        let sat = &mut self.satellites[i];
+        sat.v_x += kicks[0].0;
+        sat.v_y += kicks[0].1;
+        sat.radius = radius;
+        sat.mass = mass;
+        sat.generation = generation;
+        fragments
+    }
+
+    // Tears apart whole satellites that stray inside a planet's Roche limit, where its tides outpull
+    // their own gravity, into pieces that drift apart along the orbit. The pieces are left whole
+    // after that, as are belt bodies and satellites on rails, and tearing stops once the fragment
+    // quota is full.
+    fn disrupt(&mut self) {
+        let roche = self.params.roche;
+        let planets = &self.planets;
+        let torn: Vec<(usize, usize)> = self.satellites.iter()
+            .enumerate()
+            .filter(|(_, sat)| sat.fate.is_none() && sat.origin != Origin::Belt && sat.rails.is_none() && sat.generation == 0)
+            .filter_map(|(i, sat)| {
+                let planet = planets.iter().position(|planet| !planet.repulsor() && (sat.x - planet.x).hypot(sat.y - planet.y) < roche * planet.radius)?;
+                Some((i, planet))
+            })
+            .collect();
+        if torn.is_empty() {
+            return;
+        }
+        let mut room = self.quotas.admit_many(Origin::Fragment, torn.len() * (ROCHE_PIECES - 1), &self.satellites);
+        let mut fragments: Vec<Satellite> = Vec::with_capacity(room);
+        for (i, planet) in torn {
+            let pieces = ROCHE_PIECES.min(room + 1);
+            if pieces < 2 {
+                break;
+            }
+            room -= pieces - 1;
+            fragments.extend(self.fragment(i, pieces, ROCHE_KICK));
+            let sat = &self.satellites[i];
+            self.events.push(Event::Disruption {
+                satellite: sat.id,
+                planet,
+                x: sat.x,
+                y: sat.y,
+            });
+        }
+        self.satellites.extend(fragments);
+    }
+
+    // Path a satellite starting now would follow over the next `steps` steps of `dt` seconds, ignoring
+    // formations, ripples and any motion of the planets, and ending early if it would crash or escape.
+    // In a wrapped world, the path breaks with a point that is not a number where it crosses an edge,
+    // and in a boxed one it bounces off them.
+    pub fn predict(&self, mut x: f64, mut y: f64, mut v_x: f64, mut v_y: f64, steps: usize, dt: f64) -> Vec<(f64, f64)> {
+        let escaped = match self.params.mode {
+            Mode::Orbits | Mode::Binary => outside,
+            Mode::Projectile => fallen,
+        };
+        let region = kill_region(&self.params);
+        let mut path = vec![(x, y)];
+        for _ in 0..steps {
+            let substeps = integrator::substeps(&self.planets, &self.params, x, y);
+            let [new_x, new_y, new_v_x, new_v_y] = self.params.integrator.advance_split(&self.planets, &self.params, [x, y, v_x, v_y], dt, substeps);
+            x = new_x;
+            y = new_y;
+            v_x = new_v_x;
+            v_y = new_v_y;
+            if let Some(max_speed) = self.params.max_speed {
+                cap_speed(&mut v_x, &mut v_y, &mut 0.0, max_speed);
+            }
+            match self.params.boundary {
+                Boundary::Kill => {},
+                Boundary::Wrap => {
+                    if wrap(&mut x, &mut y, &self.params) {
+                        path.push((f64::NAN, f64::NAN));
+                    }
+                },
+                Boundary::Bounce { restitution } => bounce_off_edges(&mut x, &mut y, &mut v_x, &mut v_y, self.params.sat_radius, &self.params, restitution),
+            }
+            path.push((x, y));
+            let crashed = self.planets.iter().any(|planet| planet.touches(x, y, self.params.sat_radius));
+            if crashed || escaped(x, y, self.params.sat_radius, region) {
+                break;
+            }
+        }
+        path
+    }
+
+    // Live satellites bound to the planets, and those not
+    pub fn bound_counts(&self) -> (usize, usize) {
+        let live = self.satellites.iter().filter(|sat| sat.fate.is_none());
+        let bound = live.clone().filter(|sat| bound(&self.planets, &self.params, sat)).count();
+        (bound, live.count() - bound)
+    }
+
+    // Centre of mass of the planets, and of the satellites too when they have mass, if there is any mass
+    // to find the centre of. Repulsors are left out, as their negative mass would throw it off.
+    pub fn barycentre(&self) -> Option<(f64, f64)> {
+        let planets = self.planets.iter().filter(|planet| planet.mass > 0.0).map(|planet| (planet.mass, planet.x, planet.y));
+        let satellites = self.satellites.iter().filter(|sat| sat.mass > 0.0 && sat.fate.is_none()).map(|sat| (sat.mass, sat.x, sat.y));
+        let (mass, x, y) = planets.chain(satellites).fold((0.0, 0.0, 0.0), |(mass, x, y), (m, body_x, body_y)| (mass + m, x + m * body_x, y + m * body_y));
+        match mass > 0.0 {
+            true => Some((x / mass, y / mass)),
+            false => None,
+        }
+    }
+
+    // Frame turning with the planets in binary mode
+    pub fn rotating_frame(&self) -> Option<RotatingFrame> {
+        if self.params.mode != Mode::Binary {
+            return None;
+        }
+        let (a, b) = (&self.planets[0], &self.planets[1]);
+        let mass = a.mass + b.mass;
+        let separation = ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt();
+        Some(RotatingFrame {
+            x: (a.x * a.mass + b.x * b.mass) / mass,
+            y: (a.y * a.mass + b.y * b.mass) / mass,
+            omega: (self.params.gravity_constant * mass / separation.powi(3)).sqrt(),
+        })
+    }
+
+    // Turns the planets of a binary about their barycentre, keeping their orbit exactly circular
+    fn move_planets(&mut self, dt: f64) {
+        let frame = self.rotating_frame().unwrap();
+        let (sin, cos) = (frame.omega * dt).sin_cos();
+        for planet in self.planets.iter_mut() {
+            let (x, y) = (planet.x - frame.x, planet.y - frame.y);
+            planet.x = frame.x + x * cos - y * sin;
+            planet.y = frame.y + x * sin + y * cos;
+            planet.v_x = -frame.omega * (planet.y - frame.y);
+            planet.v_y = frame.omega * (planet.x - frame.x);
+        }
+    }
+
+    // Turns each moon on round its orbit by `dt` seconds and puts it there about its parent. Parents
+    // come before their moons, so moons of moons follow where their parent has just been put.
+    fn move_moons(&mut self, dt: f64) {
+        for i in 0..self.planets.len() {
+            if let Some(mut tether) = self.planets[i].tether {
+                tether.turn(dt);
+                let (parents, moons) = self.planets.split_at_mut(i);
+                tether.place(&mut moons[0], &parents[tether.parent]);
+                moons[0].tether = Some(tether);
+            }
+        }
+    }
+
+    // Moves the planets on by `dt` seconds, however long, without simulating anything else, for
+    // starting part-way through a scene's history. Binary planets turn through the whole interval at
+    // once, which is exact for their circular orbit, as do moons about their parents; planets in
+    // other modes are left where they start.
+    pub fn pass_time(&mut self, dt: f64) {
+        if self.params.mode == Mode::Binary {
+            self.move_planets(dt);
+        }
+        self.move_moons(dt);
+        if self.params.mode == Mode::Binary {
+            self.co_rotation.record(&self.planets, self.params.trail_length + 1);
+        }
+    }
+
+    // Starts and ends formations, spawning extra satellites when there are too few to form the shape
+    fn update_formation(&mut self) {
+        let mut formation = match self.formation.take() {
+            Some(formation) => formation,
+            None => return,
+        };
+        if let Some(points) = formation.starting(self.time) {
+            let alive = self.satellites.iter().filter(|sat| sat.fate.is_none()).count();
+            if self.spawning {
+                for _ in alive..points {
+                    self.spawn();
+                }
+            }
+            formation.start(&self.satellites, &self.planets, self.params.width, self.params.height, &mut self.rng);
+        }
+        formation.update(self.time);
+        self.formation = Some(formation);
+    }
+
+    // Advances the simulation by `dt` seconds. Satellites that die are kept until their trail has
+    // faded, so callers can inspect satellites that died during this step.
+    pub fn step(&mut self, dt: f64) {
+        let width = self.params.width;
+        let height = self.params.height;
+        self.time += dt;
+        self.steps += 1;
+        self.dt = dt;
+        self.events.clear();
+        self.removed.clear();
+        let trails = &mut self.trails;
+        self.satellites.retain_mut(|sat| {
+            let keep = sat.fate.is_none() | !sat.trail.is_empty();
+            if !keep {
+                trails.give(std::mem::take(&mut sat.trail));
+            }
+            keep
+        });
+        let time = self.time;
+        self.ripples.retain(|ripple| ripple.remaining(time) > 0.0);
+
+        if let Some(ecosystem) = self.ecosystem.as_ref() {
+            ecosystem.drift(&mut self.params, self.time);
+        }
+
+        if let Some(density) = self.density.as_mut() {
+            density.update(&self.satellites);
+        }
+
+        // Chance to add a new satellite
+        // TODO make dependent on dt
+        if self.spawning && self.params.mode != Mode::Projectile {
+            let c: f64 = self.rng.gen_range(0.0..1.0);
+            if c < self.params.add_chance {
+                self.spawn();
+            }
+        }
+
+        // Launch satellites from emitters
+        if self.spawning {
+            for i in 0..self.emitters.len() {
+                for (x, y, v_x, v_y) in self.emitters[i].emit(dt, &mut self.rng) {
+                    let color: [f32; 4] = self.sat_color();
+                    self.add_satellite(color, x, y, v_x, v_y, Origin::Emitter(i));
+                }
+            }
+        }
+
+        self.update_formation();
+
+        if self.params.mode == Mode::Binary {
+            self.move_planets(dt);
+        }
+
+        // With mass, satellites pull on each other, and can tug the planets along. Past a handful of
+        // satellites, a Barnes-Hut tree is far quicker than summing every pair.
+        let (gravity_constant, sat_mass, softening) = (self.params.gravity_constant, self.params.sat_mass, self.params.sat_radius);
+        let tree = match sat_mass > 0.0 && self.params.theta > 0.0 {
+            true => Some(Tree::build(&self.satellites, gravity_constant, softening, self.params.theta)),
+            false => None,
+        };
+        let mutual = match (sat_mass > 0.0, tree.as_ref()) {
+            (true, Some(tree)) => tree.mutual_pulls(&self.satellites),
+            (true, None) => nbody::mutual_pulls(&self.satellites, gravity_constant, softening),
+            (false, _) => Vec::new(),
+        };
+        // In orbits mode, planets pull on each other unless held still, and satellites with mass can
+        // tug them along too
+        let tug = sat_mass > 0.0 && self.params.tug_planets;
+        if self.params.mode == Mode::Orbits && (self.params.planet_gravity || tug) {
+            let mut pulls = match self.params.planet_gravity {
+                true => nbody::planet_mutual_pulls(&self.planets, gravity_constant),
+                false => vec![(0.0, 0.0); self.planets.len()],
+            };
+            if tug {
+                let tugs: Vec<(f64, f64)> = match tree.as_ref() {
+                    Some(tree) => self.planets.iter().map(|planet| tree.pull(None, planet.x, planet.y)).collect(),
+                    None => nbody::planet_pulls(&self.planets, &self.satellites, gravity_constant, softening),
+                };
+                for (pull, tug) in pulls.iter_mut().zip(tugs) {
+                    pull.0 += tug.0;
+                    pull.1 += tug.1;
+                }
+            }
+            for (planet, (a_x, a_y)) in self.planets.iter_mut().zip(pulls).filter(|(planet, _)| !planet.fixed()) {
+                let (a_x, a_y) = self.params.anisotropy.map_or((a_x, a_y), |anisotropy| anisotropy.apply(a_x, a_y));
+                planet.v_x += a_x * dt;
+                planet.v_y += a_y * dt;
+                planet.x += planet.v_x * dt;
+                planet.y += planet.v_y * dt;
+            }
+        }
+        self.move_moons(dt);
+
+        // Kicks on top of gravity: the satellite's own engine, steering towards a point of the current
+        // formation, getting pushed away by passing ripples and by starlight, the drag of any
+        // atmosphere it is skimming, and the drag and boosts of any fields the satellite is in
+        let formation = self.formation.as_ref();
+        let ripples = &self.ripples;
+        let ripple_impulse = self.params.ripple_impulse;
+        let fields = &self.fields;
+        let planets = &self.planets;
+        let params = &self.params;
+        let kick = |sat: &mut Satellite| {
+            if time < sat.burn_until {
+                sat.v_x += sat.thrust.0 * dt;
+                sat.v_y += sat.thrust.1 * dt;
+            }
+            if let Some((dv_x, dv_y)) = formation.and_then(|formation| formation.steer(sat, time, dt)) {
+                sat.v_x += dv_x;
+                sat.v_y += dv_y;
+            }
+            if ripple_impulse > 0.0 {
+                for ripple in ripples.iter() {
+                    if let Some((dv_x, dv_y)) = ripple.impulse(sat.x, sat.y, time, dt, ripple_impulse) {
+                        sat.v_x += dv_x;
+                        sat.v_y += dv_y;
+                    }
+                }
+            }
+            if params.radiation > 0.0 {
+                let (a_x, a_y) = radiation(planets, params, sat.x, sat.y, sat.radius);
+                sat.v_x += a_x * dt;
+                sat.v_y += a_y * dt;
+            }
+            let (x, y) = (sat.x, sat.y);
+            let (v_x, v_y) = drag(planets, x, y, sat.v_x, sat.v_y, dt);
+            sat.v_x = v_x;
+            sat.v_y = v_y;
+            for field in fields.iter().filter(|field| field.region.contains(x, y, planets)) {
+                match field.effect {
+                    Effect::Weightless => {},
+                    Effect::Drag(rate) => {
+                        let keep = (-rate * dt).exp();
+                        sat.v_x *= keep;
+                        sat.v_y *= keep;
+                    },
+                    Effect::Boost([a_x, a_y]) => {
+                        sat.v_x += a_x * dt;
+                        sat.v_y += a_y * dt;
+                    },
+                }
+            }
+        };
+
+        // Update satellites
+        let integrator = self.params.integrator;
+        let anisotropy = self.params.anisotropy;
+        let walls = &self.walls;
+        let portals = &self.portals;
+        for (i, sat) in self.satellites.iter_mut().enumerate() {
+            let from = (sat.x, sat.y);
+
+            // Nothing pulls on satellites in a weightless field
+            let weightless = fields.iter().any(|field| field.effect == Effect::Weightless && field.region.contains(sat.x, sat.y, planets));
+            if let (false, Some(&(a_x, a_y))) = (weightless, mutual.get(i)) {
+                let (a_x, a_y) = anisotropy.map_or((a_x, a_y), |anisotropy| anisotropy.apply(a_x, a_y));
+                sat.v_x += a_x * dt;
+                sat.v_y += a_y * dt;
+            }
+
+            // Update velocities and positions. Euler kicks the velocity before moving with it; the
+            // others, and Euler split into substeps, move the whole step under gravity and kick the
+            // velocity they end up with.
+            let substeps = match weightless {
+                true => 1,
+                false => integrator::substeps(&self.planets, &self.params, sat.x, sat.y),
+            };
+            if weightless {
+                kick(sat);
+                sat.x += sat.v_x * dt;
+                sat.y += sat.v_y * dt;
+                sat.z += sat.v_z * dt;
+            } else if self.params.inclination.is_some() {
+                let [x, y, z, v_x, v_y, v_z] = integrator.advance_split_3d(&self.planets, &self.params, [sat.x, sat.y, sat.z, sat.v_x, sat.v_y, sat.v_z], dt, substeps);
+                sat.x = x;
+                sat.y = y;
+                sat.z = z;
+                sat.v_x = v_x;
+                sat.v_y = v_y;
+                sat.v_z = v_z;
+                kick(sat);
+            } else if integrator == Integrator::Euler && substeps == 1 {
+                let (v_x, v_y) = pull(&self.planets, &self.params, sat.x, sat.y, sat.v_x, sat.v_y, dt);
+                sat.v_x = v_x;
+                sat.v_y = v_y;
+                kick(sat);
+                sat.x += sat.v_x * dt;
+                sat.y += sat.v_y * dt;
+            } else {
+                let [x, y, v_x, v_y] = integrator.advance_split(&self.planets, &self.params, [sat.x, sat.y, sat.v_x, sat.v_y], dt, substeps);
+                sat.x = x;
+                sat.y = y;
+                sat.v_x = v_x;
+                sat.v_y = v_y;
+                kick(sat);
+            }
+            if let Some(rails) = sat.rails.as_ref() {
+                let [x, y, v_x, v_y] = rails.state(time);
+                sat.x = x;
+                sat.y = y;
+                sat.v_x = v_x;
+                sat.v_y = v_y;
+            }
+
+            // Hold anything flung out too fast, as by a pass right by a planet's centre, to the speed limit
+            if let (Some(max_speed), None) = (self.params.max_speed, sat.rails.as_ref()) {
+                cap_speed(&mut sat.v_x, &mut sat.v_y, &mut sat.v_z, max_speed);
+            }
+
+            // Bounce off or die on the first wall in the way
+            if sat.fate.is_none() && sat.rails.is_none() {
+                let to = (sat.x, sat.y);
+                let hit = walls.iter()
+                    .filter_map(|wall| wall.hit(from, to, sat.radius).map(|(t, normal)| (t, normal, wall.kind)))
+                    .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+                if let Some((t, (n_x, n_y), kind)) = hit {
+                    sat.x = from.0 + (to.0 - from.0) * t;
+                    sat.y = from.1 + (to.1 - from.1) * t;
+                    match kind {
+                        WallKind::Bounce => {
+                            let towards = sat.v_x * n_x + sat.v_y * n_y;
+                            if towards < 0.0 {
+                                sat.v_x -= 2.0 * towards * n_x;
+                                sat.v_y -= 2.0 * towards * n_y;
+                            }
+                        },
+                        WallKind::Absorb => {
+                            sat.fate = Some(Fate::Walled);
+                            sat.died = time;
+                        },
+                    }
+                }
+            }
+
+            // Jump through the first portal crossed into, unless only just out of one
+            if sat.fate.is_none() && sat.rails.is_none() {
+                let to = (sat.x, sat.y);
+                let jumped = sat.jumped;
+                let jump = portals.iter()
+                    .filter(|portal| time - jumped >= portal.cooldown)
+                    .find_map(|portal| portal.jump(from, to, (sat.v_x, sat.v_y)));
+                if let Some(((x, y), (v_x, v_y))) = jump {
+                    sat.x = x;
+                    sat.y = y;
+                    sat.v_x = v_x;
+                    sat.v_y = v_y;
+                    sat.jumped = time;
+                    // Its old orbit means nothing where it comes out, and its trail breaks rather
+                    // than streaking across between the mouths
+                    sat.track = None;
+                    if sat.origin != Origin::Belt {
+                        sat.trail.push_back((f64::NAN, f64::NAN));
+                        if self.params.inclination.is_some() {
+                            sat.heights.push_back(f64::NAN);
+                        }
+                    }
+                }
+            }
+
+            // Come back in at the opposite edge of a wrapped world, breaking the trail as through a
+            // portal; the planets do not pull across the edges, so its orbit starts afresh too. In a
+            // boxed world, bounce off the edges instead.
+            if sat.fate.is_none() && sat.rails.is_none() {
+                match self.params.boundary {
+                    Boundary::Kill => {},
+                    Boundary::Wrap => {
+                        if wrap(&mut sat.x, &mut sat.y, &self.params) {
+                            sat.track = None;
+                            if sat.origin != Origin::Belt {
+                                sat.trail.push_back((f64::NAN, f64::NAN));
+                                if self.params.inclination.is_some() {
+                                    sat.heights.push_back(f64::NAN);
+                                }
+                            }
+                        }
+                    },
+                    Boundary::Bounce { restitution } => bounce_off_edges(&mut sat.x, &mut sat.y, &mut sat.v_x, &mut sat.v_y, sat.radius, &self.params, restitution),
+                }
+            }
+
+            // Update orbit tracking
+            if sat.fate.is_none() {
+                let elements = elements::osculating(sat.x, sat.y, sat.v_x, sat.v_y, &self.planets, self.params.gravity_constant);
+                sat.track = OrbitTrack::update(sat.track, elements, sat.last_planet, self.time, dt);
+                sat.stability.sample(sat.track.as_ref(), self.time, self.params.stability_orbits);
+                if let Some(track) = sat.track {
+                    sat.last_planet = Some(track.elements.planet);
+
+                    // Captured from another planet once it completes its first orbit
+                    let orbits = track.orbits(self.time);
+                    if let Some(from) = track.previous {
+                        if orbits >= 1.0 && orbits - dt / track.elements.period < 1.0 {
+                            self.events.push(Event::Capture {
+                                satellite: sat.id,
+                                from,
+                                planet: track.elements.planet,
+                                x: sat.x,
+                                y: sat.y,
+                            });
+                        }
+                    }
+                }
+            }
+
+            // Update trails
+            let dead = sat.fate.is_some();
+            if !dead {
+                // Belts have too many bodies to keep trails for
+                if sat.origin != Origin::Belt {
+                    sat.trail.push_back((sat.x, sat.y));
+                    if self.params.inclination.is_some() {
+                        sat.heights.push_back(sat.z);
+                    }
+                }
+                if self.params.record_paths {
+                    sat.path.push((sat.x, sat.y, sat.z));
+                }
+            }
+            if (sat.trail.len() > self.params.trail_length) | dead {
+                sat.trail.pop_front();
+            }
+            // Heights go along with the oldest points of the trail
+            while sat.heights.len() > sat.trail.len() {
+                sat.heights.pop_front();
+            }
+        }
+
+        match self.params.collisions {
+            Collisions::Kill => {},
+            Collisions::Bounce => self.bounce_satellites(),
+            Collisions::Merge => self.merge_satellites(),
+        }
+
+        // Destroy satellites if they pass outside the screen or hit a planet
+        let planets = &(self.planets);
+        let escaped = match self.params.mode {
+            Mode::Orbits | Mode::Binary => outside,
+            Mode::Projectile => fallen,
+        };
+        let region = kill_region(&self.params);
+        let depth = width.max(height) * self.params.world_scale;
+        for sat in self.satellites.iter_mut() {
+            if sat.fate.is_some() {
+                continue;
+            }
+            for planet in planets.iter().filter(|planet| planet.repulsor()) {
+                if (sat.x - planet.x).hypot(sat.y - planet.y) < sat.radius + planet.radius {
+                    repulsor::bounce(planet, sat);
+                }
+            }
+            let crashed = planets.iter().position(|planet| {
+                if planet.repulsor() {
+                    return false;
+                }
+                if planet.outline.is_some() {
+                    return (sat.z - planet.z).abs() < sat.radius && planet.touches(sat.x, sat.y, sat.radius);
+                }
+                let distance_x = sat.x - planet.x;
+                let distance_y = sat.y - planet.y;
+                let distance_z = sat.z - planet.z;
+                let distance_sq = (distance_x * distance_x) + (distance_y * distance_y) + (distance_z * distance_z);
+                distance_sq.sqrt() < sat.radius + planet.radius
+            });
+            sat.fate = match crashed {
+                Some(i) => Some(Fate::Crashed(i)),
+                None if escaped(sat.x, sat.y, sat.radius, region) || sat.z.abs() - sat.radius > depth => match bound(planets, &self.params, sat) {
+                    true => Some(Fate::Lost),
+                    false => Some(Fate::Escaped),
+                },
+                None if self.time >= sat.expires => Some(Fate::Expired),
+                None => None,
+            };
+            if sat.fate.is_some() {
+                sat.died = self.time;
+            }
+            if let Some(Fate::Crashed(planet)) = sat.fate {
+                self.events.push(Event::Merge {
+                    satellite: sat.id,
+                    planet,
+                    x: sat.x,
+                    y: sat.y,
+                });
+            }
+            if self.params.ripples && matches!(sat.fate, Some(Fate::Crashed(_))) {
+                self.ripples.push(Ripple {
+                    x: sat.x,
+                    y: sat.y,
+                    born: self.time,
+                    strength: 1.0,
+                });
+            }
+
+            // A near miss is reported once the satellite pulls away from the surface it skimmed
+            if sat.fate.is_none() {
+                let skimming = planets.iter().position(|planet| planet.touches(sat.x, sat.y, sat.radius + events::NEAR_MISS_DISTANCE));
+                if let (Some(planet), None) = (sat.skimming, skimming) {
+                    self.events.push(Event::NearMiss {
+                        satellite: sat.id,
+                        planet,
+                        x: sat.x,
+                        y: sat.y,
+                    });
+                }
+                sat.skimming = skimming;
+            }
+        }
+
+        if self.params.roche > 0.0 {
+            self.disrupt();
+        }
+        if self.params.collisions == Collisions::Merge {
+            self.merge_crashes();
+        }
+        self.update_ecosystem();
+        self.remove_invalid();
+        self.co_rotation.record(&self.planets, self.params.trail_length + 1);
+    }
+
+    // Takes out satellites whose position or velocity is no longer a finite number, as after passing
+    // exactly through a planet's centre, before they can spoil the drawing and the pulls on everything
+    // else. Their ids are kept in `removed` until the next step, to be reported.
+    fn remove_invalid(&mut self) {
+        let (trails, removed) = (&mut self.trails, &mut self.removed);
+        self.satellites.retain_mut(|sat| {
+            let valid = [sat.x, sat.y, sat.z, sat.v_x, sat.v_y, sat.v_z].iter().all(|value| value.is_finite());
+            if !valid {
+                removed.push(sat.id);
+                trails.give(std::mem::take(&mut sat.trail));
+            }
+            valid
+        });
+    }
+
+    // Bounces satellites that have run into each other apart
+    fn bounce_satellites(&mut self) {
+        for (i, j) in collision::touching(&self.satellites) {
+            let (before, after) = self.satellites.split_at_mut(j);
+            collision::bounce(&mut before[i], &mut after[0]);
+        }
+    }
+
+    // Merges satellites that have run into each other, the lighter into the heavier
+    fn merge_satellites(&mut self) {
+        for (i, j) in collision::touching(&self.satellites) {
+            let (before, after) = self.satellites.split_at_mut(j);
+            let (a, b) = (&mut before[i], &mut after[0]);
+            if a.fate.is_some() || b.fate.is_some() {
+                continue;
+            }
+            let (sat, other) = if b.mass > a.mass { (b, a) } else { (a, b) };
+            collision::combine(sat, other);
+            other.fate = Some(Fate::Absorbed);
+            other.died = self.time;
+        }
+    }
+
+    // Grows planets by the mass, momentum and volume of the satellites that crashed into them this step
+    fn merge_crashes(&mut self) {
+        let time = self.time;
+        for sat in self.satellites.iter().filter(|sat| sat.died == time) {
+            if let Some(Fate::Crashed(i)) = sat.fate {
+                let planet = &mut self.planets[i];
+                let mass = planet.mass + sat.mass;
+                planet.v_x = (planet.v_x * planet.mass + sat.v_x * sat.mass) / mass;
+                planet.v_y = (planet.v_y * planet.mass + sat.v_y * sat.mass) / mass;
+                planet.radius = (planet.radius.powi(3) + sat.radius.powi(3)).cbrt();
+                planet.accreted += sat.mass;
+                planet.mass = mass;
+            }
+        }
+    }
+
+    // Grows planets from the satellites that crashed into them this step, unless they already took in
+    // the satellites' own mass on merging, breaking up any that grow too large
+    fn update_ecosystem(&mut self) {
+        let ecosystem = match self.ecosystem.take() {
+            Some(ecosystem) => ecosystem,
+            None => return,
+        };
+        let time = self.time;
+        let accrete = self.params.collisions != Collisions::Merge;
+        for sat in self.satellites.iter().filter(|sat| accrete && sat.died == time) {
+            if let Some(Fate::Crashed(i)) = sat.fate {
+                ecosystem.accrete(&mut self.planets[i]);
+            }
+        }
+        for i in 0..self.planets.len() {
+            let count = ecosystem.breakup(&mut self.planets[i]);
+            if count > 0 {
+                self.events.push(Event::Breakup {
+                    planet: i,
+                    x: self.planets[i].x,
+                    y: self.planets[i].y,
+                });
+                if self.params.ripples {
+                    self.ripples.push(Ripple {
+                        x: self.planets[i].x,
+                        y: self.planets[i].y,
+                        born: self.time,
+                        strength: 5.0,
+                    });
+                }
+                self.burst(i, count);
+            }
+        }
+        self.ecosystem = Some(ecosystem);
+    }
+
+    // Hash of the simulation state, for detecting any change in behaviour between builds: the time,
+    // how far the random number generator has got, and the mass, size, position and velocity of every
+    // planet and satellite along with how each satellite died, if it has.
+    // Uses FNV-1a over the exact bits of every value, so it is the same on every run of the same
+    // binary on the same platform, though not necessarily across platforms or toolchains.
+    pub fn state_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut feed = |value: u64| {
+            for byte in value.to_le_bytes().iter() {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+            }
+        };
+        feed(self.time.to_bits());
+        feed(self.next_id);
+        let word = self.rng.get_word_pos();
+        feed(word as u64);
+        feed((word >> 64) as u64);
+        for planet in self.planets.iter() {
+            feed(planet.mass.to_bits());
+            feed(planet.radius.to_bits());
+            feed(planet.x.to_bits());
+            feed(planet.y.to_bits());
+            feed(planet.z.to_bits());
+            feed(planet.v_x.to_bits());
+            feed(planet.v_y.to_bits());
+        }
+        for sat in self.satellites.iter() {
+            feed(sat.id);
+            feed(sat.x.to_bits());
+            feed(sat.y.to_bits());
+            feed(sat.v_x.to_bits());
+            feed(sat.v_y.to_bits());
+            if self.params.inclination.is_some() {
+                feed(sat.z.to_bits());
+                feed(sat.v_z.to_bits());
+            }
+            feed(match sat.fate {
+                None => 0,
+                Some(Fate::Escaped) => 1,
+                Some(Fate::Crashed(i)) => 2 + i as u64,
+                Some(Fate::Expired) => u64::MAX,
+                Some(Fate::Walled) => u64::MAX - 1,
+                Some(Fate::Absorbed) => u64::MAX - 2,
+                Some(Fate::Lost) => u64::MAX - 3,
+            });
+        }
+        hash
+    }
+}
+
+// Parameters as the command line leaves them, with nothing spawned at random, for tests to build
+// small simulations from
+#[cfg(test)]
+pub fn test_params(num_planets: usize) -> Params {
+    Params {
+        width: 800.0,
+        height: 800.0,
+        add_chance: 0.0,
+        sat_radius: 5.0,
+        sat_velocity: 200.0,
+        gravity_constant: 4000.0,
+        trail_length: 100,
+        num_planets,
+        stability_orbits: 5.0,
+        record_paths: false,
+        exclusions: Vec::new(),
+        repulsors: Vec::new(),
+        asteroids: Vec::new(),
+        moons: Vec::new(),
+        ripples: false,
+        ripple_impulse: 0.0,
+        mode: Mode::Orbits,
+        gravity: 400.0,
+        binary_ratio: 1.0,
+        palette: None,
+        integrator: Integrator::Euler,
+        max_substeps: 1,
+        sat_mass: 0.0,
+        tug_planets: false,
+        planet_gravity: false,
+        theta: 0.0,
+        epsilon: 0.0,
+        collisions: Collisions::Kill,
+        boundary: Boundary::Kill,
+        atmosphere_height: 0.0,
+        drag_coefficient: 0.0,
+        anisotropy: None,
+        relativity: false,
+        oblateness: None,
+        roche: 0.0,
+        star: None,
+        radiation: 0.0,
+        spawn_bias: None,
+        inclination: None,
+        max_speed: None,
+        world_scale: 1.0,
+    }
+}
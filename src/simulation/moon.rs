@@ -0,0 +1,111 @@
+use serde::Deserialize;
+
+use super::{Planet, PLANET_MASS, PLANET_RADIUS};
+
+// Colour moons are drawn in, the same for all of them so they read as lesser bodies than the planets
+pub const MOON_COLOR: [f32; 4] = [0.8, 0.8, 0.75, 1.0];
+
+fn default_mass() -> f64 {
+    PLANET_MASS / 10.0
+}
+
+fn default_radius() -> f64 {
+    PLANET_RADIUS / 2.0
+}
+
+// A body circling one of the planets, as given in the `moons` section of a scene file. Moons are
+// added after the asteroids and go round their parent on rails, at a steady distance and speed
+// however the parent moves, while satellites feel the pull of both. A moon's parent can be any body
+// before it, including another moon.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Moon {
+    pub parent: usize,   // Index of the body the moon goes round, counting planets, repulsors, asteroids and then moons
+    pub distance: f64,   // Radius (in px) of the moon's orbit
+    pub period: f64,     // Seconds each orbit takes, negative to go round the other way
+    #[serde(default)]
+    pub phase: f64,      // Angle (in degrees) round its orbit the moon starts at, clockwise from the right
+    #[serde(default = "default_mass")]
+    pub mass: f64,       // Positive; 100 by default, a tenth as heavy as a planet
+    #[serde(default = "default_radius")]
+    pub radius: f64,     // Radius (in px) satellites crash into it at
+}
+
+impl Moon {
+    // Checks the moon can go round something: a body before it, at a positive distance in a finite
+    // time, with positive mass and size. `before` is how many bodies come before the first moon.
+    pub fn validate(&self, index: usize, before: usize) -> Result<(), String> {
+        if self.parent >= before + index {
+            return Err(format!("Moon {} must go round a body before it, of which there are {}", index, before + index));
+        }
+        if self.distance <= 0.0 || self.period == 0.0 || !self.period.is_finite() {
+            return Err("Moons must have a positive distance and a non-zero period".to_string());
+        }
+        if self.mass <= 0.0 || self.radius <= 0.0 {
+            return Err("Moons must have positive mass and a positive radius".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn planet(&self, parent: &Planet) -> Planet {
+        let mut planet = Planet {
+            color: MOON_COLOR,
+            mass: self.mass,
+            radius: self.radius,
+            x: parent.x,
+            y: parent.y,
+            z: 0.0,
+            v_x: 0.0,
+            v_y: 0.0,
+            accreted: 0.0,
+            atmosphere_height: 0.0,
+            drag_coefficient: 0.0,
+            oblateness: None,
+            star: false,
+            outline: None,
+            tether: None,
+        };
+        let tether = Tether {
+            parent: self.parent,
+            distance: self.distance,
+            omega: std::f64::consts::TAU / self.period,
+            angle: self.phase.to_radians(),
+        };
+        tether.place(&mut planet, parent);
+        planet.tether = Some(tether);
+        planet
+    }
+}
+
+// Circular orbit a moon is held to about its parent
+#[derive(Clone, Copy)]
+pub struct Tether {
+    pub parent: usize, // Index of the body the moon goes round
+    pub distance: f64, // Radius (in px) of the orbit
+    pub omega: f64,    // Angular speed (in radians/s), negative going round the other way
+    pub angle: f64,    // Angle (in radians) round the orbit the moon is at now
+}
+
+impl Tether {
+    // Turns the moon on round its orbit by `dt` seconds
+    pub fn turn(&mut self, dt: f64) {
+        self.angle = (self.angle + self.omega * dt).rem_euclid(std::f64::consts::TAU);
+    }
+
+    // Puts the moon where it is on its orbit about the parent as it stands now, moving with the
+    // parent as well as round it
+    pub fn place(&self, moon: &mut Planet, parent: &Planet) {
+        let (sin, cos) = self.angle.sin_cos();
+        moon.x = parent.x + self.distance * cos;
+        moon.y = parent.y + self.distance * sin;
+        moon.z = parent.z;
+        moon.v_x = parent.v_x - self.omega * self.distance * sin;
+        moon.v_y = parent.v_y + self.omega * self.distance * cos;
+    }
+
+    // Picks up the angle round the orbit from where the moon has been put, as when planets are
+    // restored from a save
+    pub fn find(&mut self, moon: &Planet, parent: &Planet) {
+        self.angle = (moon.y - parent.y).atan2(moon.x - parent.x);
+    }
+}
@@ -0,0 +1,76 @@
+use super::{Planet, Satellite};
+
+// Acceleration (in px/s^2) towards a body of the given mass `dx`, `dy` away, softened by `softening`
+// px so that close passes do not fling satellites off at absurd speeds
+pub fn attraction(gravity_constant: f64, mass: f64, dx: f64, dy: f64, softening: f64) -> (f64, f64) {
+    let distance_sq = dx * dx + dy * dy + softening * softening;
+    let scale = gravity_constant * mass / (distance_sq * distance_sq.sqrt());
+    (dx * scale, dy * scale)
+}
+
+// Acceleration of each satellite from the pull of every other live one, summed over every pair. Dead
+// satellites neither pull nor are pulled.
+pub fn mutual_pulls(satellites: &[Satellite], gravity_constant: f64, softening: f64) -> Vec<(f64, f64)> {
+    let mut pulls = vec![(0.0, 0.0); satellites.len()];
+    for i in 0..satellites.len() {
+        if satellites[i].fate.is_some() {
+            continue;
+        }
+        for j in (i + 1)..satellites.len() {
+            if satellites[j].fate.is_some() {
+                continue;
+            }
+            let (a_x, a_y) = attraction(gravity_constant, 1.0, satellites[j].x - satellites[i].x, satellites[j].y - satellites[i].y, softening);
+            pulls[i].0 += a_x * satellites[j].mass;
+            pulls[i].1 += a_y * satellites[j].mass;
+            pulls[j].0 -= a_x * satellites[i].mass;
+            pulls[j].1 -= a_y * satellites[i].mass;
+        }
+    }
+    pulls
+}
+
+// Acceleration of each planet from the pull of the live satellites
+pub fn planet_pulls(planets: &[Planet], satellites: &[Satellite], gravity_constant: f64, softening: f64) -> Vec<(f64, f64)> {
+    planets.iter()
+        .map(|planet| {
+            satellites.iter()
+                .filter(|sat| sat.fate.is_none())
+                .map(|sat| attraction(gravity_constant, sat.mass, sat.x - planet.x, sat.y - planet.y, softening))
+                .fold((0.0, 0.0), |sum, a| (sum.0 + a.0, sum.1 + a.1))
+        })
+        .collect()
+}
+
+// Acceleration of each planet from the pull of the others, softened by the radius of the one pulling
+// so that planets passing through each other are not flung apart
+pub fn planet_mutual_pulls(planets: &[Planet], gravity_constant: f64) -> Vec<(f64, f64)> {
+    planets.iter().enumerate()
+        .map(|(i, planet)| {
+            planets.iter().enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, other)| attraction(gravity_constant, other.mass, other.x - planet.x, other.y - planet.y, other.radius))
+                .fold((0.0, 0.0), |sum, a| (sum.0 + a.0, sum.1 + a.1))
+        })
+        .collect()
+}
+
+// Sets planets laid out symmetrically about (x, y) moving on circular orbits about it under each
+// other's pull, turning the same way as a binary
+pub fn circular_velocities(planets: &mut [Planet], x: f64, y: f64, gravity_constant: f64) {
+    let pulls = planet_mutual_pulls(planets, gravity_constant);
+    for (planet, (a_x, a_y)) in planets.iter_mut().zip(pulls) {
+        let (dx, dy) = (planet.x - x, planet.y - y);
+        let distance = dx.hypot(dy);
+        if distance == 0.0 {
+            continue;
+        }
+        let inward = -(a_x * dx + a_y * dy) / distance;
+        if inward <= 0.0 {
+            continue;
+        }
+        let omega = (inward / distance).sqrt();
+        planet.v_x = -omega * dy;
+        planet.v_y = omega * dx;
+    }
+}
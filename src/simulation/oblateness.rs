@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+// Flattening of a spinning planet, which bulges at its equator and so pulls harder there and more
+// weakly over its poles. Only the J2 term of its field is kept, the largest by far for real planets.
+// The spin axis starts pointing straight out of the screen, is tipped over by `tilt` and then turned
+// round to `heading`; the world is a slice through the planet, so orbits cannot tip out of it and
+// regress their nodes, but they precess within it, at a rate that depends on the axis.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Oblateness {
+    pub j2: f64,      // Strength of the equatorial bulge, about 0.001 for the Earth
+    #[serde(default)]
+    pub tilt: f64,    // Angle (in degrees) the spin axis is tipped over from out of the screen
+    #[serde(default)]
+    pub heading: f64, // Direction (in degrees) in the world the axis is tipped towards
+}
+
+impl Oblateness {
+    // Reads oblateness written as "j2" or "j2,tilt,heading"
+    pub fn parse(s: &str) -> Result<Oblateness, String> {
+        let values: Vec<f64> = s.split(',')
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("Oblateness must be one or three numbers j2[,tilt,heading]: {}", s))?;
+        match values[..] {
+            [j2] => Ok(Oblateness { j2, tilt: 0.0, heading: 0.0 }),
+            [j2, tilt, heading] => Ok(Oblateness { j2, tilt, heading }),
+            _ => Err(format!("Oblateness must be one or three numbers j2[,tilt,heading]: {}", s)),
+        }
+    }
+
+    // Part of the spin axis lying in the world, which is all of it the world can feel
+    pub fn axis(self) -> (f64, f64) {
+        let (sin, cos) = self.heading.to_radians().sin_cos();
+        let tipped = self.tilt.to_radians().sin();
+        (tipped * cos, tipped * sin)
+    }
+
+    // Acceleration (in px/s^2) on top of the planet's plain pull at (x, y) from its centre, given
+    // its GM and radius: the gradient of the J2 potential GM J2 R^2 (3 s^2 - 1) / (2 r^3), where s is
+    // the sine of the latitude. It pulls inwards harder over the equator and less over the poles.
+    pub fn acceleration(self, x: f64, y: f64, gm: f64, radius: f64) -> (f64, f64) {
+        let (n_x, n_y) = self.axis();
+        let r_sq = x * x + y * y;
+        let along = x * n_x + y * n_y;
+        let s_sq = along * along / r_sq;
+        let scale = gm * self.j2 * radius * radius / (2.0 * r_sq * r_sq * r_sq.sqrt());
+        (
+            scale * ((15.0 * s_sq - 3.0) * x - 6.0 * along * n_x),
+            scale * ((15.0 * s_sq - 3.0) * y - 6.0 * along * n_y),
+        )
+    }
+}
@@ -0,0 +1,42 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// Range of hues satellites are coloured from, in place of entirely random colours
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct Palette {
+    pub hue: f64,    // Centre of the range, in degrees round the colour wheel
+    pub spread: f64, // Width of the range, in degrees
+}
+
+impl Palette {
+    // Palette `t` of the way from this one to another, turning the hue the short way round
+    pub fn mix(self, other: Palette, t: f64) -> Palette {
+        let turn = (other.hue - self.hue + 180.0).rem_euclid(360.0) - 180.0;
+        Palette {
+            hue: (self.hue + turn * t).rem_euclid(360.0),
+            spread: self.spread + (other.spread - self.spread) * t,
+        }
+    }
+
+    // Picks a colour within the range, fairly saturated and bright so it shows up against black
+    pub fn color<R: Rng>(&self, rng: &mut R) -> [f32; 4] {
+        let hue = (self.hue + self.spread * (rng.gen_range(0.0..1.0) - 0.5)).rem_euclid(360.0);
+        let saturation = rng.gen_range(0.5..1.0);
+        let value = rng.gen_range(0.7..1.0);
+
+        // Standard HSV to RGB conversion
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+        let (r, g, b) = match (hue / 60.0) as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        [(r + m) as f32, (g + m) as f32, (b + m) as f32, 1.0]
+    }
+}
@@ -0,0 +1,250 @@
+use super::Planet;
+
+// Integration steps per second of simulation time when following an orbit
+const STEPS_PER_SECOND: f64 = 1000.0;
+
+// Points recorded around an orbit for satellites on rails
+const RAIL_POINTS: usize = 2000;
+
+// Largest mismatch (in px and px/s) between the start and end of an orbit that counts as closed
+const TOLERANCE: f64 = 1e-6;
+
+const MAX_ITERATIONS: usize = 50;
+
+type State = [f64; 4]; // (x, y, v_x, v_y)
+
+// A starting state and the time taken to return to it
+#[derive(Clone, Copy)]
+pub struct Orbit {
+    pub x: f64,
+    pub y: f64,
+    pub v_x: f64,
+    pub v_y: f64,
+    pub period: f64,
+}
+
+impl Orbit {
+    // Reads an orbit written as "x,y,v_x,v_y,period"
+    pub fn parse(s: &str) -> Result<Orbit, String> {
+        let values: Vec<f64> = s.split(',')
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("Orbit must be five numbers x,y,v_x,v_y,period: {}", s))?;
+        match values[..] {
+            [x, y, v_x, v_y, period] if period > 0.0 => Ok(Orbit { x, y, v_x, v_y, period }),
+            _ => Err(format!("Orbit must be five numbers x,y,v_x,v_y,period with a positive period: {}", s)),
+        }
+    }
+
+    fn state(&self) -> State {
+        [self.x, self.y, self.v_x, self.v_y]
+    }
+
+    fn from_state(s: State, period: f64) -> Orbit {
+        Orbit {
+            x: s[0],
+            y: s[1],
+            v_x: s[2],
+            v_y: s[3],
+            period,
+        }
+    }
+}
+
+impl std::fmt::Display for Orbit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{},{},{},{},{}", self.x, self.y, self.v_x, self.v_y, self.period)
+    }
+}
+
+// Rate of change of a state under the pull of the planets
+fn derivative(s: &State, planets: &[Planet], gravity_constant: f64) -> State {
+    let (mut a_x, mut a_y) = (0.0, 0.0);
+    for planet in planets {
+        let (d_x, d_y) = (s[0] - planet.x, s[1] - planet.y);
+        let distance = (d_x * d_x + d_y * d_y).sqrt();
+        let a = gravity_constant * planet.mass / (distance * distance * distance);
+        a_x -= a * d_x;
+        a_y -= a * d_y;
+    }
+    [s[2], s[3], a_x, a_y]
+}
+
+fn rk4(s: &State, h: f64, planets: &[Planet], gravity_constant: f64) -> State {
+    let add = |s: &State, k: &State, f: f64| [s[0] + k[0] * f, s[1] + k[1] * f, s[2] + k[2] * f, s[3] + k[3] * f];
+    let k1 = derivative(s, planets, gravity_constant);
+    let k2 = derivative(&add(s, &k1, h / 2.0), planets, gravity_constant);
+    let k3 = derivative(&add(s, &k2, h / 2.0), planets, gravity_constant);
+    let k4 = derivative(&add(s, &k3, h), planets, gravity_constant);
+    let mut next = *s;
+    for i in 0..4 {
+        next[i] += h / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+    }
+    next
+}
+
+// Follows a state for `time` seconds, recording `samples` evenly spaced states along the way (the
+// first being the start), or failing if it hits a planet
+fn propagate(start: &State, time: f64, samples: usize, planets: &[Planet], gravity_constant: f64) -> Result<(State, Vec<State>), String> {
+    // A whole number of steps between recorded states
+    let per_sample = ((time * STEPS_PER_SECOND / samples.max(1) as f64).ceil() as usize).max(1);
+    let steps = per_sample * samples.max(1);
+    let h = time / steps as f64;
+    let mut s = *start;
+    let mut recorded = Vec::with_capacity(samples);
+    for step in 0..steps {
+        if samples > 0 && step % per_sample == 0 {
+            recorded.push(s);
+        }
+        s = rk4(&s, h, planets, gravity_constant);
+        if planets.iter().any(|planet| (s[0] - planet.x).powi(2) + (s[1] - planet.y).powi(2) < planet.radius * planet.radius) {
+            return Err("Orbit crashes into a planet".to_string());
+        }
+    }
+    Ok((s, recorded))
+}
+
+// Solves a x = b for a small square matrix by Gaussian elimination with partial pivoting
+fn solve(mut a: [[f64; 4]; 4], mut b: [f64; 4]) -> Option<[f64; 4]> {
+    for col in 0..4 {
+        let pivot = (col..4).max_by(|i, j| a[*i][col].abs().partial_cmp(&a[*j][col].abs()).unwrap())?;
+        if a[pivot][col].abs() < 1e-300 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..4 {
+            let f = a[row][col] / a[col][col];
+            let pivot_row = a[col];
+            for (k, value) in a[row].iter_mut().enumerate().skip(col) {
+                *value -= f * pivot_row[k];
+            }
+            b[row] -= f * b[col];
+        }
+    }
+    let mut x = [0.0; 4];
+    for row in (0..4).rev() {
+        let sum: f64 = (row + 1..4).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+// Mismatch between the start of an orbit and where it ends up after one period
+fn residual(orbit: &Orbit, planets: &[Planet], gravity_constant: f64) -> Result<(State, State), String> {
+    let (end, _) = propagate(&orbit.state(), orbit.period, 0, planets, gravity_constant)?;
+    let start = orbit.state();
+    Ok(([end[0] - start[0], end[1] - start[1], end[2] - start[2], end[3] - start[3]], end))
+}
+
+fn norm(v: &State) -> f64 {
+    v.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+// Refines a guess into a periodic orbit by differential correction: Newton's method on the start state
+// and period together, taking the smallest correction that closes the orbit to first order since
+// any point along an orbit (and often any nearby energy) closes it equally well. Steps are damped
+// (Levenberg-Marquardt) as orbits near a planet respond very unevenly to changes in their start. The period may not
+// fall below half the guess, which would otherwise shrink towards the trivial orbit of no time at all.
+// Returns the orbit and the number of iterations taken.
+pub fn find(guess: Orbit, planets: &[Planet], gravity_constant: f64) -> Result<(Orbit, usize), String> {
+    let mut orbit = guess;
+    let (mut f, mut end) = residual(&orbit, planets, gravity_constant)?;
+    let mut damping = 0.0;
+    for iteration in 0..MAX_ITERATIONS {
+        if norm(&f) < TOLERANCE {
+            return Ok((orbit, iteration));
+        }
+
+        // Jacobian of the residual: finite differences for the start state, exact for the period
+        let mut jacobian = [[0.0; 5]; 4];
+        let start = orbit.state();
+        for j in 0..4 {
+            let eps = 1e-6 * start[j].abs().max(1.0);
+            let mut nudged = start;
+            nudged[j] += eps;
+            let (g, _) = residual(&Orbit::from_state(nudged, orbit.period), planets, gravity_constant)?;
+            for (row, values) in jacobian.iter_mut().enumerate() {
+                values[j] = (g[row] - f[row]) / eps;
+            }
+        }
+        let rate = derivative(&end, planets, gravity_constant);
+        for (row, value) in rate.iter().enumerate() {
+            jacobian[row][4] = *value;
+        }
+
+        let mut jjt = [[0.0; 4]; 4];
+        for (i, row) in jjt.iter_mut().enumerate() {
+            for (k, value) in row.iter_mut().enumerate() {
+                *value = (0..5).map(|j| jacobian[i][j] * jacobian[k][j]).sum();
+            }
+        }
+
+        // Damped minimum-norm step, delta = -J^T (J J^T + damping I)^-1 f, raising the damping (and so
+        // shortening the step) until it improves the fit, and lowering it again once steps succeed
+        loop {
+            let mut damped = jjt;
+            for (i, row) in damped.iter_mut().enumerate() {
+                row[i] += damping;
+            }
+            let y = solve(damped, f).ok_or("Differential correction failed: singular Jacobian")?;
+            let delta: Vec<f64> = (0..5).map(|j| -(0..4).map(|i| jacobian[i][j] * y[i]).sum::<f64>()).collect();
+            let candidate = Orbit {
+                x: orbit.x + delta[0],
+                y: orbit.y + delta[1],
+                v_x: orbit.v_x + delta[2],
+                v_y: orbit.v_y + delta[3],
+                period: orbit.period + delta[4],
+            };
+            if candidate.period > guess.period / 2.0 {
+                if let Ok((g, e)) = residual(&candidate, planets, gravity_constant) {
+                    if norm(&g) < norm(&f) {
+                        orbit = candidate;
+                        f = g;
+                        end = e;
+                        damping /= 10.0;
+                        break;
+                    }
+                }
+            }
+            damping = (damping * 10.0).max(1e-6);
+            if damping > 1e12 {
+                return Err(format!("Differential correction stalled with a mismatch of {:.3e}", norm(&f)));
+            }
+        }
+    }
+    Err(format!("Differential correction did not converge, mismatch still {:.3e}", norm(&f)))
+}
+
+// A precomputed orbit that a satellite follows exactly, ignoring the simulation's own integrator
+pub struct Rails {
+    states: Vec<State>, // Evenly spaced states around one period
+    period: f64,
+    start: f64,         // Simulation time the satellite set off
+}
+
+impl Rails {
+    pub fn new(orbit: &Orbit, planets: &[Planet], gravity_constant: f64, start: f64) -> Result<Rails, String> {
+        let (_, states) = propagate(&orbit.state(), orbit.period, RAIL_POINTS, planets, gravity_constant)?;
+        Ok(Rails {
+            states,
+            period: orbit.period,
+            start,
+        })
+    }
+
+    // State at the given time, interpolating between recorded states
+    pub fn state(&self, time: f64) -> State {
+        let phase = ((time - self.start) / self.period).rem_euclid(1.0) * self.states.len() as f64;
+        let i = phase.floor() as usize % self.states.len();
+        let j = (i + 1) % self.states.len();
+        let t = phase - phase.floor();
+        let (a, b) = (&self.states[i], &self.states[j]);
+        [
+            a[0] + (b[0] - a[0]) * t,
+            a[1] + (b[1] - a[1]) * t,
+            a[2] + (b[2] - a[2]) * t,
+            a[3] + (b[3] - a[3]) * t,
+        ]
+    }
+}
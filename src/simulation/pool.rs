@@ -0,0 +1,42 @@
+use std::collections::VecDeque;
+
+// Most spare buffers the pool holds on to, enough to refill a full screen of satellites; any handed
+// back beyond that are freed, so a mass die-off does not pin its memory for good
+const MAX_SPARE: usize = 4096;
+
+// Trail buffers handed back by satellites that have gone, given out again to new ones so that
+// thousands of satellites spawning and dying each minute do not each allocate a trail and free it.
+// Buffers are handed out with room for a whole trail, so they never grow while in use either.
+#[derive(Default)]
+pub struct TrailPool {
+    spare: Vec<VecDeque<(f64, f64)>>,
+    pub reused: u64,    // Buffers given out again from the pool
+    pub allocated: u64, // Buffers that had to be allocated afresh
+}
+
+impl TrailPool {
+    // An empty buffer with room for a trail of the given length, plus the newest point before the
+    // oldest is dropped and a break where the satellite jumps through a portal
+    pub fn take(&mut self, length: usize) -> VecDeque<(f64, f64)> {
+        match self.spare.pop() {
+            Some(mut trail) => {
+                self.reused += 1;
+                trail.reserve(length + 2);
+                trail
+            },
+            None => {
+                self.allocated += 1;
+                VecDeque::with_capacity(length + 2)
+            },
+        }
+    }
+
+    // Takes back the buffer of a satellite that is being removed
+    pub fn give(&mut self, mut trail: VecDeque<(f64, f64)>) {
+        if trail.capacity() == 0 || self.spare.len() >= MAX_SPARE {
+            return;
+        }
+        trail.clear();
+        self.spare.push(trail);
+    }
+}
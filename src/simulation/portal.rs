@@ -0,0 +1,55 @@
+use serde::Deserialize;
+
+// Seconds a satellite must wait after a jump before it can jump again, unless the scene says otherwise
+fn default_cooldown() -> f64 {
+    1.0
+}
+
+// One end of a portal: a circle satellites fall into
+#[derive(Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct Mouth {
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+}
+
+impl Mouth {
+    fn contains(&self, (x, y): (f64, f64)) -> bool {
+        (x - self.x).hypot(y - self.y) <= self.radius
+    }
+}
+
+// A pair of linked mouths, as given in the `portals` section of a scene file. A satellite crossing
+// into either comes out of the other at the same place relative to its centre (scaled to its size),
+// with the same speed.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Portal {
+    pub a: Mouth,
+    pub b: Mouth,
+    #[serde(default)]
+    pub rotation: f64, // Degrees everything is turned through going from `a` to `b`, and back going the other way
+    #[serde(default = "default_cooldown")]
+    pub cooldown: f64, // Seconds after a jump before the same satellite can jump again
+}
+
+impl Portal {
+    // Where a satellite moving from `from` to `to` with the given velocity comes out, and with what
+    // velocity, if the move takes it into one of the mouths. Satellites already inside a mouth, as
+    // they are just after coming out of one, do not jump.
+    pub fn jump(&self, from: (f64, f64), to: (f64, f64), velocity: (f64, f64)) -> Option<((f64, f64), (f64, f64))> {
+        let (enter, exit, rotation) = if !self.a.contains(from) && self.a.contains(to) {
+            (self.a, self.b, self.rotation)
+        } else if !self.b.contains(from) && self.b.contains(to) {
+            (self.b, self.a, -self.rotation)
+        } else {
+            return None;
+        };
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let turn = |(x, y): (f64, f64)| (x * cos - y * sin, x * sin + y * cos);
+        let scale = exit.radius / enter.radius;
+        let (d_x, d_y) = turn((to.0 - enter.x, to.1 - enter.y));
+        Some(((exit.x + d_x * scale, exit.y + d_y * scale), turn(velocity)))
+    }
+}
@@ -0,0 +1,51 @@
+use super::{Origin, Satellite};
+
+// Satellites brought in from each kind of origin, with optional caps on how many may be alive at once so
+// that, for example, one participant can't flood an installation with hand-launched satellites
+#[derive(Default)]
+pub struct Quotas {
+    pub limits: [Option<usize>; Origin::NAMES.len()],  // Most satellites of each origin alive at once
+    pub spawned: [usize; Origin::NAMES.len()],         // Satellites of each origin let in so far
+    pub refused: [usize; Origin::NAMES.len()],         // Satellites of each origin turned away by their cap
+}
+
+impl Quotas {
+    // Reads a cap written as "origin=N", e.g. "user=5"
+    pub fn parse(s: &str) -> Result<(usize, usize), String> {
+        let (name, limit) = s.split_once('=').ok_or_else(|| format!("Quota must be written as origin=N: {}", s))?;
+        let kind = Origin::NAMES.iter().position(|n| *n == name)
+            .ok_or_else(|| format!("Quota origin must be one of: {}: {}", Origin::NAMES.join(", "), s))?;
+        let limit = limit.parse().map_err(|_| format!("Quota must be a whole number: {}", s))?;
+        Ok((kind, limit))
+    }
+
+    // Whether a new satellite of the given origin is let in, counting it either way
+    pub fn admit(&mut self, origin: Origin, satellites: &[Satellite]) -> bool {
+        let kind = origin.index();
+        if let Some(limit) = self.limits[kind] {
+            let alive = satellites.iter().filter(|sat| sat.fate.is_none() && sat.origin.index() == kind).count();
+            if alive >= limit {
+                self.refused[kind] += 1;
+                return false;
+            }
+        }
+        self.spawned[kind] += 1;
+        true
+    }
+
+    // How many of `wanted` new satellites of the given origin are let in at once, counting them all,
+    // without counting the live satellites over again for each one
+    pub fn admit_many(&mut self, origin: Origin, wanted: usize, satellites: &[Satellite]) -> usize {
+        let kind = origin.index();
+        let admitted = match self.limits[kind] {
+            Some(limit) => {
+                let alive = satellites.iter().filter(|sat| sat.fate.is_none() && sat.origin.index() == kind).count();
+                wanted.min(limit.saturating_sub(alive))
+            },
+            None => wanted,
+        };
+        self.spawned[kind] += admitted;
+        self.refused[kind] += wanted - admitted;
+        admitted
+    }
+}
@@ -0,0 +1,36 @@
+use serde::Deserialize;
+
+use super::Planet;
+
+// An area of the world, as given in a scene file
+#[derive(Deserialize, Clone)]
+#[serde(tag = "shape", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Region {
+    Circle { x: f64, y: f64, radius: f64 },
+    Rect { x: f64, y: f64, width: f64, height: f64 }, // (x, y) is the top left corner
+    Planets,                                          // The surface of every planet
+    Polygon { points: Vec<[f64; 2]> },                // Corners in order, either way round
+}
+
+impl Region {
+    pub fn contains(&self, x: f64, y: f64, planets: &[Planet]) -> bool {
+        match *self {
+            Region::Circle { x: cx, y: cy, radius } => (x - cx).powi(2) + (y - cy).powi(2) < radius * radius,
+            Region::Rect { x: rx, y: ry, width, height } => x >= rx && x < rx + width && y >= ry && y < ry + height,
+            Region::Planets => planets.iter().any(|planet| {
+                (x - planet.x).powi(2) + (y - planet.y).powi(2) < planet.radius * planet.radius
+            }),
+            // Inside if a ray heading right from the point crosses the edges an odd number of times
+            Region::Polygon { ref points } => {
+                let mut inside = false;
+                for (i, a) in points.iter().enumerate() {
+                    let b = points[(i + 1) % points.len()];
+                    if (a[1] > y) != (b[1] > y) && x < a[0] + (y - a[1]) / (b[1] - a[1]) * (b[0] - a[0]) {
+                        inside = !inside;
+                    }
+                }
+                inside
+            },
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use serde::Deserialize;
+
+use super::{Planet, Satellite, PLANET_RADIUS};
+
+// Colour repulsors are drawn in, the same for all of them so they stand apart from the planets
+pub const REPULSOR_COLOR: [f32; 4] = [0.55, 0.55, 0.65, 1.0];
+
+fn default_radius() -> f64 {
+    PLANET_RADIUS
+}
+
+// A body of negative mass, as given in the `repulsors` section of a scene file, that pushes
+// satellites away as hard as a planet of the same size would pull them in. Repulsors are added after
+// the planets and stay where they are put: satellites bounce off them rather than crashing, and
+// nothing spawns inside them.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Repulsor {
+    pub x: f64,
+    pub y: f64,
+    pub mass: f64,   // Negative; -1000 pushes as hard as a planet pulls
+    #[serde(default = "default_radius")]
+    pub radius: f64, // Radius (in px) satellites bounce off at
+}
+
+impl Repulsor {
+    pub fn planet(&self) -> Planet {
+        Planet {
+            color: REPULSOR_COLOR,
+            mass: self.mass,
+            radius: self.radius,
+            x: self.x,
+            y: self.y,
+            z: 0.0,
+            v_x: 0.0,
+            v_y: 0.0,
+            accreted: 0.0,
+            atmosphere_height: 0.0,
+            drag_coefficient: 0.0,
+            oblateness: None,
+            star: false,
+            outline: None,
+            tether: None,
+        }
+    }
+}
+
+// Moves a satellite that has run into a repulsor back out to its surface, bouncing it off without
+// losing any speed if it was still heading in
+pub fn bounce(planet: &Planet, sat: &mut Satellite) {
+    let (d_x, d_y) = (sat.x - planet.x, sat.y - planet.y);
+    let distance = d_x.hypot(d_y).max(f64::EPSILON);
+    let (n_x, n_y) = (d_x / distance, d_y / distance);
+    sat.x = planet.x + n_x * (planet.radius + sat.radius);
+    sat.y = planet.y + n_y * (planet.radius + sat.radius);
+    let inwards = (sat.v_x - planet.v_x) * n_x + (sat.v_y - planet.v_y) * n_y;
+    if inwards < 0.0 {
+        sat.v_x -= 2.0 * inwards * n_x;
+        sat.v_y -= 2.0 * inwards * n_y;
+    }
+}
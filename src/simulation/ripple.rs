@@ -0,0 +1,36 @@
+// Speed (in px/s) at which ripples spread
+pub const SPEED: f64 = 300.0;
+
+// Seconds a ripple lasts before fading out
+pub const LIFETIME: f64 = 1.5;
+
+// A ring spreading out from where a satellite merged with a planet
+pub struct Ripple {
+    pub x: f64,
+    pub y: f64,
+    pub born: f64,     // Simulation time the merge happened
+    pub strength: f64, // Relative size of the merge, 1 for a single satellite
+}
+
+impl Ripple {
+    // Radius of the ring at the given time
+    pub fn radius(&self, time: f64) -> f64 {
+        (time - self.born) * SPEED
+    }
+
+    // How much of the ripple is left, from 1 when it starts down to 0 when it has faded out
+    pub fn remaining(&self, time: f64) -> f64 {
+        (1.0 - (time - self.born) / LIFETIME).max(0.0)
+    }
+
+    // Change in velocity for a satellite at (x, y) as the ring passes over it between
+    // `time - dt` and `time`, pushing it directly away from the merge
+    pub fn impulse(&self, x: f64, y: f64, time: f64, dt: f64, impulse: f64) -> Option<(f64, f64)> {
+        let distance = ((x - self.x).powi(2) + (y - self.y).powi(2)).sqrt();
+        if distance < self.radius(time - dt) || distance >= self.radius(time) || distance == 0.0 {
+            return None;
+        }
+        let dv = impulse * self.strength * self.remaining(time);
+        Some((dv * (x - self.x) / distance, dv * (y - self.y) / distance))
+    }
+}
@@ -0,0 +1,209 @@
+use super::nbody::attraction;
+use super::Satellite;
+
+// Depth past which cells are no longer split, so satellites sitting on top of each other cannot
+// split them forever
+const MAX_DEPTH: usize = 32;
+
+// A square cell of the quadtree, holding either satellites directly or four quarter-size cells
+struct Cell {
+    x: f64,                       // Left edge
+    y: f64,                       // Top edge
+    size: f64,                    // Length of each side
+    mass: f64,                    // Total mass of the satellites anywhere inside
+    sum_x: f64,                   // Sums of their positions weighted by mass, for the centre of mass
+    sum_y: f64,
+    bodies: Vec<usize>,           // Satellites held directly, by index, while the cell is a leaf
+    children: Option<[usize; 4]>, // Indices of the quarters, once split
+}
+
+impl Cell {
+    fn new(x: f64, y: f64, size: f64) -> Cell {
+        Cell {
+            x,
+            y,
+            size,
+            mass: 0.0,
+            sum_x: 0.0,
+            sum_y: 0.0,
+            bodies: Vec::new(),
+            children: None,
+        }
+    }
+
+    // Which quarter a point falls in, numbered left to right then top to bottom
+    fn quarter(&self, x: f64, y: f64) -> usize {
+        let half = self.size / 2.0;
+        (x >= self.x + half) as usize + 2 * (y >= self.y + half) as usize
+    }
+}
+
+// Barnes-Hut quadtree over the live satellites: far enough away, a whole cell of satellites pulls
+// like a single body at their centre of mass, so the pull on each satellite takes O(log n) rather
+// than O(n) work
+pub struct Tree {
+    cells: Vec<Cell>,
+    points: Vec<(f64, f64, f64)>, // Position and mass of every satellite, live or not, by index
+    gravity_constant: f64,
+    softening: f64,               // Distance (in px) pulls are softened over, as for the exact sum
+    theta: f64,                   // Cells smaller than this times their distance away are taken as a whole
+}
+
+impl Tree {
+    pub fn build(satellites: &[Satellite], gravity_constant: f64, softening: f64, theta: f64) -> Tree {
+        let live = || satellites.iter().filter(|sat| sat.fate.is_none());
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for sat in live() {
+            min_x = min_x.min(sat.x);
+            min_y = min_y.min(sat.y);
+            max_x = max_x.max(sat.x);
+            max_y = max_y.max(sat.y);
+        }
+        let size = (max_x - min_x).max(max_y - min_y).max(1.0);
+        let mut tree = Tree {
+            cells: vec![Cell::new(min_x, min_y, size)],
+            points: satellites.iter().map(|sat| (sat.x, sat.y, sat.mass)).collect(),
+            gravity_constant,
+            softening,
+            theta,
+        };
+        for i in (0..satellites.len()).filter(|i| satellites[*i].fate.is_none()) {
+            tree.insert(i);
+        }
+        tree
+    }
+
+    fn insert(&mut self, i: usize) {
+        let (x, y, mass) = self.points[i];
+        let mut cell = 0;
+        let mut depth = 0;
+        loop {
+            self.cells[cell].mass += mass;
+            self.cells[cell].sum_x += x * mass;
+            self.cells[cell].sum_y += y * mass;
+            match self.cells[cell].children {
+                Some(children) => {
+                    cell = children[self.cells[cell].quarter(x, y)];
+                    depth += 1;
+                },
+                None if self.cells[cell].bodies.is_empty() || depth >= MAX_DEPTH => {
+                    self.cells[cell].bodies.push(i);
+                    return;
+                },
+                None => {
+                    // Split the leaf, moving the satellite it held down into its quarter
+                    let (cell_x, cell_y, half) = (self.cells[cell].x, self.cells[cell].y, self.cells[cell].size / 2.0);
+                    let first = self.cells.len();
+                    for j in 0..4 {
+                        self.cells.push(Cell::new(cell_x + half * (j % 2) as f64, cell_y + half * (j / 2) as f64, half));
+                    }
+                    self.cells[cell].children = Some([first, first + 1, first + 2, first + 3]);
+                    for other in std::mem::take(&mut self.cells[cell].bodies) {
+                        let (other_x, other_y, other_mass) = self.points[other];
+                        let child = first + self.cells[cell].quarter(other_x, other_y);
+                        self.cells[child].mass += other_mass;
+                        self.cells[child].sum_x += other_x * other_mass;
+                        self.cells[child].sum_y += other_y * other_mass;
+                        self.cells[child].bodies.push(other);
+                    }
+                    cell = first + self.cells[cell].quarter(x, y);
+                    depth += 1;
+                },
+            }
+        }
+    }
+
+    // Acceleration at (x, y) from every live satellite but `skip`
+    pub fn pull(&self, skip: Option<usize>, x: f64, y: f64) -> (f64, f64) {
+        let (gravity_constant, softening, theta) = (self.gravity_constant, self.softening, self.theta);
+        let (mut a_x, mut a_y) = (0.0, 0.0);
+        let mut stack = vec![0];
+        while let Some(cell) = stack.pop() {
+            let cell = &self.cells[cell];
+            if cell.mass <= 0.0 {
+                continue;
+            }
+            match cell.children {
+                None => {
+                    for &j in cell.bodies.iter().filter(|j| Some(**j) != skip) {
+                        let (other_x, other_y, mass) = self.points[j];
+                        let (dx, dy) = attraction(gravity_constant, mass, other_x - x, other_y - y, softening);
+                        a_x += dx;
+                        a_y += dy;
+                    }
+                },
+                Some(children) => {
+                    // A cell round the point itself is always opened, since lumping it together would
+                    // count the skipped satellite's own mass, however large theta is
+                    let inside = x >= cell.x && x <= cell.x + cell.size && y >= cell.y && y <= cell.y + cell.size;
+                    let (centre_x, centre_y) = (cell.sum_x / cell.mass, cell.sum_y / cell.mass);
+                    let distance = (centre_x - x).hypot(centre_y - y);
+                    if !inside && cell.size < theta * distance {
+                        let (dx, dy) = attraction(gravity_constant, cell.mass, centre_x - x, centre_y - y, softening);
+                        a_x += dx;
+                        a_y += dy;
+                    } else {
+                        stack.extend_from_slice(&children);
+                    }
+                },
+            }
+        }
+        (a_x, a_y)
+    }
+
+    // Acceleration of each satellite from the pull of every other live one, as nbody::mutual_pulls
+    // works out exactly
+    pub fn mutual_pulls(&self, satellites: &[Satellite]) -> Vec<(f64, f64)> {
+        satellites.iter().enumerate()
+            .map(|(i, sat)| match sat.fate {
+                None => self.pull(Some(i), sat.x, sat.y),
+                Some(_) => (0.0, 0.0),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tree;
+    use crate::simulation::{nbody, test_params, Origin, Simulation};
+
+    fn cluster(points: &[(f64, f64)]) -> Simulation {
+        let mut params = test_params(1);
+        params.sat_mass = 1.0;
+        let mut sim = Simulation::new(params, 1);
+        sim.planets.clear();
+        for &(x, y) in points {
+            sim.launch(x, y, 0.0, 0.0, Origin::Script).unwrap();
+        }
+        sim
+    }
+
+    fn assert_close(tree: &[(f64, f64)], exact: &[(f64, f64)], tolerance: f64) {
+        for (&(tree_x, tree_y), &(exact_x, exact_y)) in tree.iter().zip(exact.iter()) {
+            let error = (tree_x - exact_x).hypot(tree_y - exact_y);
+            assert!(error <= tolerance * exact_x.hypot(exact_y), "pull {:?} is not within {} of {:?}", (tree_x, tree_y), tolerance, (exact_x, exact_y));
+        }
+    }
+
+    #[test]
+    fn tree_pulls_match_the_exact_sum() {
+        let points: Vec<(f64, f64)> = (0..60).map(|i| {
+            let angle = i as f64 * 2.4;
+            (400.0 + (20.0 + 5.0 * i as f64) * angle.cos(), 400.0 + (20.0 + 5.0 * i as f64) * angle.sin())
+        }).collect();
+        let sim = cluster(&points);
+        let exact = nbody::mutual_pulls(&sim.satellites, 4000.0, 1.0);
+        assert_close(&Tree::build(&sim.satellites, 4000.0, 1.0, 0.0).mutual_pulls(&sim.satellites), &exact, 1e-9);
+        assert_close(&Tree::build(&sim.satellites, 4000.0, 1.0, 0.5).mutual_pulls(&sim.satellites), &exact, 0.05);
+    }
+
+    #[test]
+    fn satellites_never_pull_themselves() {
+        // With a large theta the root cell looks small from the satellites in its corner, but it holds
+        // them too, so it must still be opened; only the far satellite sees the close pair lumped
+        let sim = cluster(&[(0.0, 0.0), (1.0, 1.0), (100.0, 100.0)]);
+        let exact = nbody::mutual_pulls(&sim.satellites, 4000.0, 1.0);
+        assert_close(&Tree::build(&sim.satellites, 4000.0, 1.0, 3.0).mutual_pulls(&sim.satellites), &exact, 1e-3);
+    }
+}
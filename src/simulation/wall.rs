@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+// What happens to a satellite that runs into a wall
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WallKind {
+    #[default]
+    Bounce, // Bounces off, losing no speed
+    Absorb, // Dies on it
+}
+
+// A straight, static wall from (x1, y1) to (x2, y2), as drawn with the wall tool or given in the
+// `walls` section of a scene file
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Wall {
+    pub x1: f64,
+    pub y1: f64,
+    pub x2: f64,
+    pub y2: f64,
+    #[serde(default)]
+    pub kind: WallKind,
+}
+
+// A scene holding nothing but walls, as written out for saving
+#[derive(Serialize)]
+struct WalledScene<'a> {
+    walls: &'a [Wall],
+}
+
+// Walls as a scene file, ready to save and load with --scene
+pub fn scene(walls: &[Wall]) -> String {
+    serde_yaml::to_string(&WalledScene { walls }).expect("Could not write walls as a scene")
+}
+
+// Earliest fraction of the way along the move from `from` to `to` at which a ray meets the circle
+// of given radius about `centre`, if it does within the move
+fn ray_circle(from: (f64, f64), d: (f64, f64), centre: (f64, f64), radius: f64) -> Option<f64> {
+    let (f_x, f_y) = (from.0 - centre.0, from.1 - centre.1);
+    let a = d.0 * d.0 + d.1 * d.1;
+    let b = 2.0 * (f_x * d.0 + f_y * d.1);
+    let c = f_x * f_x + f_y * f_y - radius * radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    (0.0..=1.0).contains(&t).then_some(t)
+}
+
+impl Wall {
+    // Where a circle of the given radius moving in a straight line from `from` to `to` first touches
+    // the wall, as the fraction of the way along the move and the wall's unit normal there, pointing
+    // back towards the circle. Circles already touching or moving away are not counted, so one that
+    // has just bounced cannot stick.
+    pub fn hit(&self, from: (f64, f64), to: (f64, f64), radius: f64) -> Option<(f64, (f64, f64))> {
+        let d = (to.0 - from.0, to.1 - from.1);
+        let (w_x, w_y) = (self.x2 - self.x1, self.y2 - self.y1);
+        let length = w_x.hypot(w_y);
+        if length == 0.0 {
+            return None;
+        }
+        let (u_x, u_y) = (w_x / length, w_y / length);
+
+        // The flat sides, offset by the radius towards the side the circle starts on
+        let side = (from.0 - self.x1) * -u_y + (from.1 - self.y1) * u_x;
+        let normal = if side >= 0.0 { (-u_y, u_x) } else { (u_y, -u_x) };
+        let gap = side.abs() - radius;
+        let closing = -(d.0 * normal.0 + d.1 * normal.1);
+        let mut best: Option<(f64, (f64, f64))> = None;
+        if gap >= 0.0 && closing > 0.0 && gap <= closing {
+            let t = gap / closing;
+            let along = (from.0 + d.0 * t - self.x1) * u_x + (from.1 + d.1 * t - self.y1) * u_y;
+            if (0.0..=length).contains(&along) {
+                best = Some((t, normal));
+            }
+        }
+
+        // The rounded ends
+        for end in [(self.x1, self.y1), (self.x2, self.y2)].iter() {
+            if let Some(t) = ray_circle(from, d, *end, radius) {
+                if best.is_none_or(|(best_t, _)| t < best_t) {
+                    let (n_x, n_y) = (from.0 + d.0 * t - end.0, from.1 + d.1 * t - end.1);
+                    let n = n_x.hypot(n_y);
+                    if n > 0.0 && d.0 * n_x + d.1 * n_y < 0.0 {
+                        best = Some((t, (n_x / n, n_y / n)));
+                    }
+                }
+            }
+        }
+        best
+    }
+}
@@ -0,0 +1,55 @@
+use crate::events::Event;
+
+// Real seconds time stays slowed after the last event that called for it
+const HOLD: f64 = 2.0;
+
+// Real seconds taken to ease down to the slowest speed, and back up again afterwards
+const EASE_IN: f64 = 0.3;
+const EASE_OUT: f64 = 1.0;
+
+// Cinematic slow motion: eases time down when a satellite skims, merges with or is torn apart by a
+// planet, holds it there for a couple of seconds, then eases it back up. Events during the hold
+// extend it.
+pub struct SlowMotion {
+    slowest: f64, // Fraction of normal speed time slows to
+    level: f64,   // How far slowed, from 0 (normal speed) to 1 (slowest)
+    held: f64,    // Real seconds left before easing back up
+}
+
+impl SlowMotion {
+    pub fn new(slowest: f64) -> SlowMotion {
+        SlowMotion {
+            slowest,
+            level: 0.0,
+            held: 0.0,
+        }
+    }
+
+    // Whether an event is worth slowing down for
+    fn wants(event: &Event) -> bool {
+        matches!(event, Event::NearMiss { .. } | Event::Merge { .. } | Event::Disruption { .. })
+    }
+
+    // Slows down for any of the events from the last update that call for it
+    pub fn notice(&mut self, events: &[Event]) {
+        if events.iter().any(SlowMotion::wants) {
+            self.held = HOLD;
+        }
+    }
+
+    // Eases on by `dt` seconds of real time
+    pub fn update(&mut self, dt: f64) {
+        if self.held > 0.0 {
+            self.held -= dt;
+            self.level = (self.level + dt / EASE_IN).min(1.0);
+        } else {
+            self.level = (self.level - dt / EASE_OUT).max(0.0);
+        }
+    }
+
+    // Fraction of normal speed time runs at, eased in and out smoothly
+    pub fn scale(&self) -> f64 {
+        let eased = self.level * self.level * (3.0 - 2.0 * self.level);
+        1.0 - eased * (1.0 - self.slowest)
+    }
+}
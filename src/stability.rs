@@ -0,0 +1,73 @@
+use std::collections::VecDeque;
+
+use crate::elements::OrbitTrack;
+
+// Semi-major axis samples taken per orbit
+const SAMPLES_PER_ORBIT: f64 = 16.0;
+
+// Rolling record of a satellite's semi-major axis over its last few orbits, used to estimate how
+// regular its orbit is: a satellite on a stable ellipse keeps a near-constant semi-major axis, while
+// one being scattered between planets sees it jump around.
+#[derive(Default)]
+pub struct Stability {
+    samples: VecDeque<(f64, f64)>, // (time, semi-major axis) samples, oldest first
+    since: f64,                    // Start of the orbit track being sampled
+}
+
+impl Stability {
+    // Records the current orbit, keeping only the last `orbits` orbits worth of samples
+    pub fn sample(&mut self, track: Option<&OrbitTrack>, time: f64, orbits: f64) {
+        let track = match track {
+            Some(track) => track,
+            None => {
+                self.samples.clear();
+                return;
+            }
+        };
+        if track.since != self.since {
+            // Bound to a different planet, so start again
+            self.samples.clear();
+            self.since = track.since;
+        }
+
+        let period = track.elements.period;
+        let due = self.samples.back().is_none_or(|(last, _)| time - last >= period / SAMPLES_PER_ORBIT);
+        if due {
+            self.samples.push_back((time, track.elements.semi_major_axis));
+        }
+        while self.samples.front().is_some_and(|(first, _)| time - first > orbits * period) {
+            self.samples.pop_front();
+        }
+    }
+
+    // Coefficient of variation of the semi-major axis, once at least an orbit has been sampled
+    pub fn estimate(&self) -> Option<f64> {
+        if (self.samples.len() as f64) < SAMPLES_PER_ORBIT {
+            return None;
+        }
+        let n = self.samples.len() as f64;
+        let mean = self.samples.iter().map(|(_, a)| a).sum::<f64>() / n;
+        let variance = self.samples.iter().map(|(_, a)| (a - mean).powi(2)).sum::<f64>() / n;
+        Some(variance.sqrt() / mean)
+    }
+}
+
+// Colour for a stability estimate, from cyan for regular orbits to red for chaotic ones,
+// or grey when there is no estimate yet
+pub fn color(estimate: Option<f64>) -> [f32; 4] {
+    const STABLE: [f32; 3] = [0.3, 0.8, 1.0];
+    const CHAOTIC: [f32; 3] = [1.0, 0.2, 0.1];
+    match estimate {
+        Some(cv) => {
+            // Variation of 10% or more counts as fully chaotic
+            let t = (cv / 0.1).min(1.0) as f32;
+            [
+                STABLE[0] + (CHAOTIC[0] - STABLE[0]) * t,
+                STABLE[1] + (CHAOTIC[1] - STABLE[1]) * t,
+                STABLE[2] + (CHAOTIC[2] - STABLE[2]) * t,
+                1.0,
+            ]
+        },
+        None => [0.5, 0.5, 0.5, 1.0],
+    }
+}
@@ -0,0 +1,105 @@
+use std::env;
+use std::ffi::OsString;
+use std::io;
+use std::net::UdpSocket;
+use std::process::{Child, Command};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Longest gap between heartbeats from the child, so that a steady stream of frames does not flood
+// the socket
+const HEARTBEAT_EVERY: Duration = Duration::from_secs(1);
+
+// Pause before restarting a child that crashed or hung, so one that fails straight away does not
+// restart in a tight loop
+const RESTART_DELAY: Duration = Duration::from_secs(2);
+
+// Beats sent by the child to the supervisor over a local UDP socket to show it is still drawing frames
+pub struct Heartbeat {
+    socket: UdpSocket,
+    last: Option<Instant>,
+}
+
+impl Heartbeat {
+    pub fn connect(port: u16) -> io::Result<Heartbeat> {
+        let socket = UdpSocket::bind("127.0.0.1:0")?;
+        socket.connect(("127.0.0.1", port))?;
+        Ok(Heartbeat { socket, last: None })
+    }
+
+    // Called every frame, sending a beat if it is time for one. Returns false once the supervisor has
+    // gone, so that stopping it stops the child too; any other beat that fails to send is dropped, as
+    // the supervisor will notice the silence.
+    pub fn beat(&mut self) -> bool {
+        if self.last.is_some_and(|last| last.elapsed() < HEARTBEAT_EVERY) {
+            return true;
+        }
+        self.last = Some(Instant::now());
+        !matches!(self.socket.send(&[0]), Err(e) if e.kind() == io::ErrorKind::ConnectionRefused)
+    }
+}
+
+// Arguments the program was run with, minus `--supervise` and its value
+fn child_args() -> Vec<OsString> {
+    let mut args = Vec::new();
+    let mut skip = false;
+    for arg in env::args_os().skip(1) {
+        if skip {
+            skip = false;
+        } else if arg == "--supervise" {
+            skip = true;
+        } else if !arg.to_string_lossy().starts_with("--supervise=") {
+            args.push(arg);
+        }
+    }
+    args
+}
+
+// Runs the program again in a child process with the same arguments and restarts it whenever it
+// crashes, or hangs by going `timeout` seconds without a heartbeat, until it exits cleanly. The child
+// autosaves its bodies, to `autosave` or a file in the temporary directory if not given, and picks
+// up from the last save each time it is restarted.
+pub fn run(timeout: f64, autosave: Option<&str>) -> io::Result<()> {
+    let socket = UdpSocket::bind("127.0.0.1:0")?;
+    socket.set_read_timeout(Some(Duration::from_millis(250)))?;
+    let port = socket.local_addr()?.port();
+    let mut args = child_args();
+    args.push("--heartbeat".into());
+    args.push(port.to_string().into());
+    if autosave.is_none() {
+        let path = env::temp_dir().join(format!("orbits-autosave-{}.yml", std::process::id()));
+        args.push("--autosave".into());
+        args.push(path.into_os_string());
+    }
+    let timeout = Duration::from_secs_f64(timeout);
+    let mut restarts = 0;
+    loop {
+        let mut child: Child = Command::new(env::current_exe()?).args(&args).spawn()?;
+        // The first frame can take a while, so the child gets the timeout again to start up
+        let mut last_beat = Instant::now();
+        let mut buf = [0; 1];
+        let hung = loop {
+            if let Some(status) = child.try_wait()? {
+                if status.success() {
+                    return Ok(());
+                }
+                eprintln!("Supervisor: orbits exited with {}", status);
+                break false;
+            }
+            if socket.recv(&mut buf).is_ok() {
+                last_beat = Instant::now();
+            }
+            if last_beat.elapsed() > timeout {
+                break true;
+            }
+        };
+        if hung {
+            eprintln!("Supervisor: no heartbeat for {:.0} s, killing orbits", timeout.as_secs_f64());
+            child.kill()?;
+            child.wait()?;
+        }
+        restarts += 1;
+        eprintln!("Supervisor: restarting from the last autosave (restart {})", restarts);
+        thread::sleep(RESTART_DELAY);
+    }
+}
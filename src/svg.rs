@@ -0,0 +1,83 @@
+use std::fmt::Write;
+
+use graphics::math::Matrix2d;
+use graphics::Viewport;
+
+use crate::draw::{Backend, Blend, DrawList, Shape};
+use crate::text;
+
+// Draws draw lists into an SVG document rather than a window, sized in the window's logical pixels
+#[derive(Default)]
+pub struct Svg {
+    pub document: String,
+}
+
+// Colour as SVG fill or stroke attributes, given the attribute name
+pub fn paint(name: &str, [r, g, b, a]: [f32; 4]) -> String {
+    let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("{}=\"rgb({},{},{})\" {}-opacity=\"{:.3}\"", name, byte(r), byte(g), byte(b), name, a)
+}
+
+// SVG transform equivalent to a draw list transform, which ends in normalised device coordinates,
+// for a window of the given size
+fn matrix(t: Matrix2d, width: f64, height: f64) -> String {
+    let (w, h) = (width / 2.0, height / 2.0);
+    format!(
+        "matrix({:.6} {:.6} {:.6} {:.6} {:.3} {:.3})",
+        w * t[0][0], -h * t[1][0], w * t[0][1], -h * t[1][1], w * (t[0][2] + 1.0), h * (1.0 - t[1][2]),
+    )
+}
+
+impl Backend for Svg {
+    fn render(&mut self, list: &DrawList, viewport: Viewport) {
+        let [width, height] = viewport.window_size;
+        let mut out = String::new();
+        writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">", width, height, width, height).unwrap();
+        let mut blend = Blend::Alpha;
+        for (shape, transform) in list.items.iter() {
+            let m = matrix(*transform, width, height);
+            let mut element = String::new();
+            match shape {
+                Shape::Clear(color) => {
+                    writeln!(out, "<rect width=\"{}\" height=\"{}\" {}/>", width, height, paint("fill", *color)).unwrap();
+                },
+                Shape::Blend(new) => blend = *new,
+                Shape::Rectangle { color, rect } => {
+                    writeln!(element, "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" transform=\"{}\" {}/>", rect[0], rect[1], rect[2], rect[3], m, paint("fill", *color)).unwrap();
+                },
+                Shape::RectangleBorder { color, radius, rect } => {
+                    writeln!(element, "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" transform=\"{}\" fill=\"none\" stroke-width=\"{:.2}\" {}/>", rect[0], rect[1], rect[2], rect[3], m, 2.0 * radius, paint("stroke", *color)).unwrap();
+                },
+                Shape::Ellipse { color, rect } => {
+                    let (rx, ry) = (rect[2] / 2.0, rect[3] / 2.0);
+                    writeln!(element, "<ellipse cx=\"{:.2}\" cy=\"{:.2}\" rx=\"{:.2}\" ry=\"{:.2}\" transform=\"{}\" {}/>", rect[0] + rx, rect[1] + ry, rx, ry, m, paint("fill", *color)).unwrap();
+                },
+                Shape::EllipseBorder { color, radius, rect } => {
+                    let (rx, ry) = (rect[2] / 2.0, rect[3] / 2.0);
+                    writeln!(element, "<ellipse cx=\"{:.2}\" cy=\"{:.2}\" rx=\"{:.2}\" ry=\"{:.2}\" transform=\"{}\" fill=\"none\" stroke-width=\"{:.2}\" {}/>", rect[0] + rx, rect[1] + ry, rx, ry, m, 2.0 * radius, paint("stroke", *color)).unwrap();
+                },
+                Shape::Line { color, radius, ends } => {
+                    writeln!(element, "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" transform=\"{}\" stroke-width=\"{:.2}\" stroke-linecap=\"round\" {}/>", ends[0], ends[1], ends[2], ends[3], m, 2.0 * radius, paint("stroke", *color)).unwrap();
+                },
+                Shape::Polygon { color, points } => {
+                    let points: Vec<String> = points.iter().map(|p| format!("{:.2},{:.2}", p[0], p[1])).collect();
+                    writeln!(element, "<polygon points=\"{}\" transform=\"{}\" {}/>", points.join(" "), m, paint("fill", *color)).unwrap();
+                },
+                Shape::Text { text, color, x, y, scale } => {
+                    // Set font pixels as squares, just as they are drawn in the window
+                    let path: String = text::pixels(text).iter()
+                        .map(|(col, row)| format!("M{:.2} {:.2}h{s}v{s}h-{s}z", x + *col as f64 * scale, y + *row as f64 * scale, s = scale))
+                        .collect();
+                    writeln!(out, "<path d=\"{}\" transform=\"{}\" {}/>", path, m, paint("fill", *color)).unwrap();
+                },
+            }
+            // Additive shapes are added to what is under them with the closest blend mode SVG has
+            if blend == Blend::Additive {
+                element = element.replacen("/>", " style=\"mix-blend-mode:plus-lighter\"/>", 1);
+            }
+            out.push_str(&element);
+        }
+        out.push_str("</svg>\n");
+        self.document = out;
+    }
+}
@@ -0,0 +1,46 @@
+use crate::simulation::Satellite;
+
+// Centre of mass of the live satellites and how they are spread about it, as the ellipse one
+// standard deviation out along the principal axes of their positions
+pub struct Swarm {
+    pub x: f64,
+    pub y: f64,
+    pub major: f64, // Standard deviation (in px) along the direction the swarm is most spread out
+    pub minor: f64, // Standard deviation (in px) across it
+    pub angle: f64, // Direction of the major axis, in radians from the x axis
+}
+
+impl Swarm {
+    // Satellites all weigh the same, so the centre of mass is their mean position. Needs at least two
+    // live satellites.
+    pub fn measure<'a>(satellites: impl Iterator<Item = &'a Satellite>) -> Option<Swarm> {
+        let points: Vec<(f64, f64)> = satellites.filter(|sat| sat.fate.is_none()).map(|sat| (sat.x, sat.y)).collect();
+        if points.len() < 2 {
+            return None;
+        }
+        let n = points.len() as f64;
+        let x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let y = points.iter().map(|p| p.1).sum::<f64>() / n;
+
+        // Covariance of the positions
+        let (mut xx, mut xy, mut yy) = (0.0, 0.0, 0.0);
+        for (p_x, p_y) in points.iter() {
+            let (dx, dy) = (p_x - x, p_y - y);
+            xx += dx * dx;
+            xy += dx * dy;
+            yy += dy * dy;
+        }
+        let (xx, xy, yy) = (xx / n, xy / n, yy / n);
+
+        // Its eigenvalues are the variances along the principal axes
+        let mean = (xx + yy) / 2.0;
+        let spread = ((xx - yy) / 2.0).hypot(xy);
+        Some(Swarm {
+            x,
+            y,
+            major: (mean + spread).sqrt(),
+            minor: (mean - spread).max(0.0).sqrt(),
+            angle: 0.5 * (2.0 * xy).atan2(xx - yy),
+        })
+    }
+}
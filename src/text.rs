@@ -0,0 +1,56 @@
+use font8x8::{UnicodeFonts, BASIC_FONTS};
+use graphics::math::Matrix2d;
+use graphics::{rectangle, Graphics};
+
+// Size in font pixels of each character cell
+pub const CHAR_SIZE: f64 = 8.0;
+
+// Width in screen pixels of the given text when drawn at the given scale
+pub fn width(text: &str, scale: f64) -> f64 {
+    text.chars().count() as f64 * CHAR_SIZE * scale
+}
+
+// Bitmap for a character, falling back to '?' for anything the font lacks
+fn glyph(ch: char) -> [u8; 8] {
+    BASIC_FONTS.get(ch).or_else(|| BASIC_FONTS.get('?')).unwrap()
+}
+
+// Font pixels set when drawing the given text, as (column, row) from the top-left corner
+pub fn pixels(text: &str) -> Vec<(usize, usize)> {
+    let mut pixels = Vec::new();
+    for (i, ch) in text.chars().enumerate() {
+        for (row, bits) in glyph(ch).iter().enumerate() {
+            for col in 0..8 {
+                if bits & (1 << col) != 0 {
+                    pixels.push((i * 8 + col, row));
+                }
+            }
+        }
+    }
+    pixels
+}
+
+// Draws text using the built-in 8x8 bitmap font, with its top-left corner at (x, y)
+// and each font pixel drawn as a `scale`-sized square
+pub fn draw<G: Graphics>(text: &str, color: [f32; 4], x: f64, y: f64, scale: f64, transform: Matrix2d, g: &mut G) {
+    for (i, ch) in text.chars().enumerate() {
+        let glyph = glyph(ch);
+        let left = x + i as f64 * CHAR_SIZE * scale;
+        for (row, bits) in glyph.iter().enumerate() {
+            // Draw each horizontal run of set pixels as a single rectangle
+            let mut col = 0;
+            while col < 8 {
+                if bits & (1 << col) == 0 {
+                    col += 1;
+                    continue;
+                }
+                let start = col;
+                while col < 8 && bits & (1 << col) != 0 {
+                    col += 1;
+                }
+                let rect = [left + start as f64 * scale, y + row as f64 * scale, (col - start) as f64 * scale, scale];
+                rectangle(color, rect, transform, g);
+            }
+        }
+    }
+}
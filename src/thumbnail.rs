@@ -0,0 +1,55 @@
+use std::io;
+use std::path::Path;
+
+use graphics::math::{abs_transform, Matrix2d};
+use graphics::{rectangle, Transformed, Viewport};
+
+use crate::draw::{Backend, DrawList};
+use crate::raster::Raster;
+use crate::simulation::Simulation;
+
+// Length (in px) of the longer side of a thumbnail
+const THUMBNAIL_SIZE: f64 = 200.0;
+
+// Saves a small picture of the whole world as it stands as a PNG, drawn without a window so it can
+// be made from headless runs: planets, satellites and their trails, with none of the overlays
+pub fn save(sim: &Simulation, path: &Path) -> io::Result<()> {
+    let (world_width, world_height) = (sim.params.width, sim.params.height);
+    let zoom = THUMBNAIL_SIZE / world_width.max(world_height);
+    let (width, height) = ((world_width * zoom).round().max(1.0) as u32, (world_height * zoom).round().max(1.0) as u32);
+    let view = abs_transform(width as f64, height as f64).zoom(zoom);
+    let list = picture(sim, zoom, view);
+
+    let mut raster = Raster::default();
+    let viewport = Viewport {
+        rect: [0, 0, width as i32, height as i32],
+        draw_size: [width, height],
+        window_size: [width as f64, height as f64],
+    };
+    raster.render(&list, viewport);
+    raster.image.save(path).map_err(io::Error::other)
+}
+
+// Planets, satellites and their trails drawn through `view`, which shrinks the world by `zoom`
+pub fn picture(sim: &Simulation, zoom: f64, view: Matrix2d) -> DrawList {
+    let mut list = DrawList::default();
+    list.clear([0.0, 0.0, 0.0, 1.0]);
+    for satellite in sim.satellites.iter() {
+        let trail = &satellite.trail;
+        for (a, b) in trail.iter().zip(trail.iter().skip(1)) {
+            // Points that are not a number mark jumps through portals
+            if !a.0.is_nan() && !b.0.is_nan() {
+                list.line(satellite.color, 1.0, [a.0, a.1, b.0, b.1], view);
+            }
+        }
+    }
+    for planet in sim.planets.iter() {
+        list.ellipse(planet.color, rectangle::centered_square(planet.x, planet.y, planet.radius), view);
+    }
+    for satellite in sim.satellites.iter().filter(|sat| sat.fate.is_none()) {
+        // At this size satellites would vanish, so each is at least a pixel across
+        let radius = satellite.radius.max(0.5 / zoom);
+        list.ellipse(satellite.color, rectangle::centered_square(satellite.x, satellite.y, radius), view);
+    }
+    list
+}
@@ -0,0 +1,52 @@
+use graphics::math::Matrix2d;
+use graphics::Transformed;
+
+// Angle (in radians) each press turns or tips the view by
+const STEP: f64 = std::f64::consts::PI / 12.0;
+
+// Furthest the view tips over, short of looking along the plane edge on, where anything above or
+// below it would be lifted off to infinity
+const MAX_PITCH: f64 = 4.0 * std::f64::consts::PI / 9.0;
+
+// Direction a world whose bodies move in three dimensions is looked at from, with an orthographic
+// projection about the centre of the world. Straight down onto the plane of the planets, as it starts
+// out, the view is the same as in two dimensions.
+#[derive(Clone, Copy, Default)]
+pub struct Tilt {
+    yaw: f64,   // Angle (in radians) the plane is turned through about the centre
+    pitch: f64, // Angle (in radians) the plane is tipped away from the viewer, about the horizontal
+}
+
+impl Tilt {
+    // Turns the view a step one way or the other
+    pub fn turn(&mut self, back: bool) {
+        self.yaw += if back { -STEP } else { STEP };
+    }
+
+    // Tips the view a step further over, or back towards looking straight down
+    pub fn tip(&mut self, back: bool) {
+        self.pitch = (self.pitch + if back { -STEP } else { STEP }).clamp(0.0, MAX_PITCH);
+    }
+
+    // Transform from the plane to the view, turning it about the centre (x, y) and squashing it
+    // vertically as it tips away
+    pub fn transform(&self, base: Matrix2d, x: f64, y: f64) -> Matrix2d {
+        base.trans(x, y).scale(1.0, self.pitch.cos()).rot_rad(self.yaw).trans(-x, -y)
+    }
+
+    // Point of the plane drawn where a point at height z above (x, y) shows up in the view, so that
+    // bodies off the plane are drawn through the same transform as everything on it. `turned` is the
+    // angle the plane is turned through on its way into the transform, as when viewing a binary from
+    // the frame turning with it.
+    pub fn lift(&self, x: f64, y: f64, z: f64, turned: f64) -> (f64, f64) {
+        let up = z * self.pitch.tan();
+        let (sin, cos) = (self.yaw + turned).sin_cos();
+        (x - up * sin, y - up * cos)
+    }
+
+    // Transform drawing a sphere at (x, y) round in the view rather than squashed flat like the plane,
+    // from the view's transform, to be used with the sphere centred on the origin
+    pub fn upright(&self, view: Matrix2d, x: f64, y: f64, turned: f64) -> Matrix2d {
+        view.trans(x, y).rot_rad(-(self.yaw + turned)).scale(1.0, 1.0 / self.pitch.cos())
+    }
+}
@@ -0,0 +1,140 @@
+use serde::Deserialize;
+
+use crate::launch::Launcher;
+use crate::simulation::Planet;
+
+// Something that happens at a set time, as given in the `timeline` section of a scene file
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Cue {
+    pub at: f64,                 // Seconds of simulation time after the timeline starts
+    #[serde(default)]
+    pub caption: Option<String>, // Caption shown from now until the next cue with a caption ("" clears it)
+    #[serde(default)]
+    pub launch: Option<Launch>,  // Satellite fired from a planet's surface
+    #[serde(default)]
+    pub split: Option<usize>,    // Number of pieces to split every live satellite into, as a firework
+}
+
+// A satellite fired from a planet's surface, as with the launch tool
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Launch {
+    #[serde(default)]
+    pub planet: usize, // Index of the planet to launch from
+    #[serde(default = "default_bearing")]
+    pub bearing: f64,  // Position on the surface in degrees clockwise from the positive x axis, so -90 is the top
+    #[serde(default = "default_altitude")]
+    pub altitude: f64, // Height above the surface in px
+    #[serde(default)]
+    pub angle: f64,    // Launch angle in degrees above the horizon, 0 to 180 going over the top
+    pub speed: f64,    // Launch speed in px/s
+    #[serde(default)]
+    pub thrust: Option<Thrust>, // Engine burn the satellite starts firing as it is launched
+}
+
+// A steady acceleration from a satellite's engine
+#[derive(Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct Thrust {
+    pub x: f64,        // Acceleration in px/s^2 along x
+    pub y: f64,        // Acceleration in px/s^2 along y, positive being down
+    #[serde(default = "default_duration")]
+    pub duration: f64, // Seconds the engine fires for, for ever by default
+}
+
+fn default_bearing() -> f64 { -90.0 }
+fn default_altitude() -> f64 { 10.0 }
+fn default_duration() -> f64 { f64::INFINITY }
+
+// Height of the cannon in the cannonball demo, enough to see slower shots fall back
+const CANNONBALL_ALTITUDE: f64 = 40.0;
+
+impl Launch {
+    pub fn launcher(&self) -> Launcher {
+        Launcher {
+            planet: self.planet,
+            bearing: self.bearing.to_radians(),
+            altitude: self.altitude,
+            angle: self.angle,
+            speed: self.speed,
+        }
+    }
+}
+
+// Cues played back in order of time, from when the timeline was last (re)started
+pub struct Timeline {
+    cues: Vec<Cue>,
+    next: usize,                 // Index of the next cue to fire
+    started: f64,                // Simulation time the timeline started
+    pub caption: Option<String>, // Caption currently showing
+}
+
+impl Timeline {
+    pub fn new(mut cues: Vec<Cue>) -> Timeline {
+        cues.sort_by(|a, b| a.at.partial_cmp(&b.at).unwrap());
+        Timeline {
+            cues,
+            next: 0,
+            started: 0.0,
+            caption: None,
+        }
+    }
+
+    // Newton's cannonball: horizontal launches from the top of a planet at ever greater speeds,
+    // from falling straight back down through circular and elliptical orbits to escaping
+    pub fn cannonball(planet: &Planet, gravity_constant: f64) -> Timeline {
+        let circular = (gravity_constant * planet.mass / (planet.radius + CANNONBALL_ALTITUDE)).sqrt();
+        let escape = circular * 2f64.sqrt();
+        let cue = |at: f64, caption: &str, speed: Option<f64>| Cue {
+            at,
+            caption: Some(caption.to_string()),
+            launch: speed.map(|speed| Launch {
+                planet: 0,
+                bearing: default_bearing(),
+                altitude: CANNONBALL_ALTITUDE,
+                angle: 0.0,
+                speed,
+                thrust: None,
+            }),
+            split: None,
+        };
+        Timeline::new(vec![
+            cue(0.0, "Newton's cannonball: a cannon high above the planet fires ever faster", None),
+            cue(3.0, "Too slow, and the ball soon falls back to the ground", Some(circular * 0.5)),
+            cue(7.0, "Faster, and it lands further round the planet", Some(circular * 0.7)),
+            cue(11.0, &format!("At {:.0} px/s it falls all the way round: a circular orbit", circular), Some(circular)),
+            cue(17.0, "Faster still, and the orbit stretches into an ellipse", Some(circular * 1.15)),
+            Cue {
+                caption: None,
+                ..cue(22.0, "", Some(circular * 1.3))
+            },
+            cue(28.0, &format!("Beyond {:.0} px/s, escape velocity, it never comes back", escape), Some(escape * 1.05)),
+            cue(34.0, "Press L to try it yourself", None),
+        ])
+    }
+
+    // Starts playing from the beginning at the given simulation time
+    pub fn restart(&mut self, time: f64) {
+        self.next = 0;
+        self.started = time;
+        self.caption = None;
+    }
+
+    // Fires every cue due by the given simulation time, returning the cues fired for their launches
+    // and splits
+    pub fn update(&mut self, time: f64) -> Vec<Cue> {
+        let mut fired = Vec::new();
+        while let Some(cue) = self.cues.get(self.next) {
+            if self.started + cue.at > time {
+                break;
+            }
+            if let Some(caption) = cue.caption.as_ref() {
+                self.caption = Some(caption.clone()).filter(|caption| !caption.is_empty());
+            }
+            fired.push(cue.clone());
+            self.next += 1;
+        }
+        fired
+    }
+}
@@ -0,0 +1,108 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::anisotropy::Anisotropy;
+use crate::simulation::oblateness::Oblateness;
+use crate::simulation::palette::Palette;
+use crate::simulation::Params;
+
+// Ranges the lucky dip picks from, narrow enough that most picks keep satellites in view and lively
+const GRAVITY_CONSTANT: (f64, f64) = (2000.0, 8000.0);
+const ADD_CHANCE: (f64, f64) = (0.005, 0.05);
+const NUM_PLANETS: (usize, usize) = (1, 4);
+const PALETTE_SPREAD: (f64, f64) = (30.0, 120.0);
+
+// Physics, spawning and colour settings a scene can give in place of the defaults, each left out
+// to keep the default. The lucky dip picks all of them at once, but for anisotropy and oblateness,
+// which are left to be asked for.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Tuning {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gravity_constant: Option<f64>, // 'G' constant used to update velocities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub add_chance: Option<f64>,       // Chance to add a satellite each frame
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_planets: Option<usize>,    // Number of planets to create on (re)set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub palette: Option<Palette>,      // Hues satellites are coloured from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anisotropy: Option<Anisotropy>, // Stretching and shearing of gravity, for art
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oblateness: Option<Oblateness>, // Equatorial bulge and spin axis of the planets
+}
+
+// A scene holding nothing but tuning, as written out for saving
+#[derive(Serialize)]
+struct TunedScene<'a> {
+    tuning: &'a Tuning,
+}
+
+impl Tuning {
+    // "I'm feeling lucky": a random pick of every setting
+    pub fn lucky<R: Rng>(rng: &mut R) -> Tuning {
+        // Rounded so they read well and can be typed back in
+        let round = |x: f64, places: i32| (x * 10f64.powi(places)).round() / 10f64.powi(places);
+        Tuning {
+            gravity_constant: Some(round(rng.gen_range(GRAVITY_CONSTANT.0..GRAVITY_CONSTANT.1), -2)),
+            add_chance: Some(round(rng.gen_range(ADD_CHANCE.0..ADD_CHANCE.1), 3)),
+            num_planets: Some(rng.gen_range(NUM_PLANETS.0..=NUM_PLANETS.1)),
+            palette: Some(Palette {
+                hue: round(rng.gen_range(0.0..360.0), 0),
+                spread: round(rng.gen_range(PALETTE_SPREAD.0..PALETTE_SPREAD.1), 0),
+            }),
+            anisotropy: None,
+            oblateness: None,
+        }
+    }
+
+    pub fn apply(&self, params: &mut Params) {
+        if let Some(gravity_constant) = self.gravity_constant {
+            params.gravity_constant = gravity_constant;
+        }
+        if let Some(add_chance) = self.add_chance {
+            params.add_chance = add_chance;
+        }
+        if let Some(num_planets) = self.num_planets {
+            params.num_planets = num_planets;
+        }
+        if self.palette.is_some() {
+            params.palette = self.palette;
+        }
+        if self.anisotropy.is_some() {
+            params.anisotropy = self.anisotropy;
+        }
+        if self.oblateness.is_some() {
+            params.oblateness = self.oblateness;
+        }
+    }
+
+    // One line per setting given, for showing on screen
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(gravity_constant) = self.gravity_constant {
+            lines.push(format!("gravity constant {}", gravity_constant));
+        }
+        if let Some(add_chance) = self.add_chance {
+            lines.push(format!("add chance {}", add_chance));
+        }
+        if let Some(num_planets) = self.num_planets {
+            lines.push(format!("planets {}", num_planets));
+        }
+        if let Some(palette) = self.palette {
+            lines.push(format!("palette hue {} spread {}", palette.hue, palette.spread));
+        }
+        if let Some(anisotropy) = self.anisotropy {
+            lines.push(format!("anisotropy x {} y {} shear {}", anisotropy.x, anisotropy.y, anisotropy.shear));
+        }
+        if let Some(oblateness) = self.oblateness {
+            lines.push(format!("oblateness j2 {} tilt {} heading {}", oblateness.j2, oblateness.tilt, oblateness.heading));
+        }
+        lines
+    }
+
+    // A scene file setting just these, ready to save and load with --scene
+    pub fn scene(&self) -> String {
+        serde_yaml::to_string(&TunedScene { tuning: self }).expect("Could not write tuning as a scene")
+    }
+}
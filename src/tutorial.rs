@@ -0,0 +1,83 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::data;
+
+// Seconds the closing caption is shown before the tutorial ends
+const OUTRO: f64 = 6.0;
+
+// Things the player is asked to do, each moving the tutorial on when done
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    LaunchTool, // Picked up the launch tool
+    Launch,     // Launched a satellite
+    Pause,      // Paused spawning
+    Inspect,    // Hovered over a planet
+}
+
+const STEPS: [(Action, &str); 4] = [
+    (Action::LaunchTool, "Welcome! Press L to pick up the launch tool"),
+    (Action::Launch, "Aim with the arrow keys, then press Space to launch"),
+    (Action::Pause, "Press N to pause new satellites spawning"),
+    (Action::Inspect, "Hover over a planet to see what is orbiting it"),
+];
+
+// Walks a first-time player through the controls with captions, moving on as each is tried. It is
+// shown until finished or skipped once, after which a file in the data directory marks it as seen.
+pub struct Tutorial {
+    step: usize, // Index of the step waiting to be done, or STEPS.len() once all are
+    outro: f64,  // Seconds the closing caption has been shown
+}
+
+impl Tutorial {
+    // $XDG_DATA_HOME/orbits/tutorial-seen, falling back to ~/.local/share/orbits/tutorial-seen
+    fn marker() -> Option<PathBuf> {
+        Some(data::dir()?.join("tutorial-seen"))
+    }
+
+    pub fn new() -> Tutorial {
+        Tutorial { step: 0, outro: 0.0 }
+    }
+
+    // A tutorial for a first run, or None if it has been seen before or there is no data directory
+    // to remember it in
+    pub fn first_run() -> Option<Tutorial> {
+        match Tutorial::marker()?.exists() {
+            true => None,
+            false => Some(Tutorial::new()),
+        }
+    }
+
+    // Remembers that the tutorial has been seen, so it is not shown again
+    pub fn mark_seen() -> io::Result<()> {
+        let path = Tutorial::marker().ok_or_else(|| io::Error::other("No data directory (set HOME or XDG_DATA_HOME)"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, "")
+    }
+
+    pub fn caption(&self) -> &'static str {
+        match STEPS.get(self.step) {
+            Some((_, caption)) => caption,
+            None => "That's the basics! Press H to show the HUD",
+        }
+    }
+
+    // Moves on if the action is the one the current step asks for
+    pub fn done(&mut self, action: Action) {
+        if STEPS.get(self.step).is_some_and(|&(wanted, _)| wanted == action) {
+            self.step += 1;
+        }
+    }
+
+    // Counts down the closing caption, returning true once the tutorial is over
+    pub fn update(&mut self, dt: f64) -> bool {
+        if self.step < STEPS.len() {
+            return false;
+        }
+        self.outro += dt;
+        self.outro >= OUTRO
+    }
+}